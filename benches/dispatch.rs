@@ -0,0 +1,142 @@
+//! Measures logging throughput from many threads at once.
+//!
+//! The log4rs logger resolves the target and its appenders from a snapshot of
+//! the configuration without taking a lock, so threads only contend inside
+//! the appenders themselves. This compares it against the same logger behind
+//! a global mutex, and checks that no record is lost or duplicated while the
+//! configuration is replaced concurrently.
+
+use std::{
+    cell::Cell,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use log::{Level, LevelFilter, Log, Record};
+use log4rs::{
+    append::Append,
+    config::{Appender, Config, Logger, Root},
+};
+
+const THREADS: usize = 8;
+const ITERS: usize = 200_000;
+
+thread_local! {
+    static APPENDED: Cell<u64> = const { Cell::new(0) };
+}
+
+/// Counts records per thread, so the appender itself doesn't contend and the
+/// dispatch path dominates.
+#[derive(Debug)]
+struct Count;
+
+impl Append for Count {
+    fn append(&self, _: &Record) -> anyhow::Result<()> {
+        APPENDED.with(|n| n.set(n.get() + 1));
+        Ok(())
+    }
+
+    fn flush(&self) {}
+}
+
+fn config() -> Config {
+    Config::builder()
+        .appender(Appender::builder().build("count", Box::new(Count)))
+        .loggers(
+            ["app", "app::db", "app::http", "hyper", "tokio"]
+                .iter()
+                .map(|name| Logger::builder().build(*name, LevelFilter::Info)),
+        )
+        .build(Root::builder().appender("count").build(LevelFilter::Info))
+        .unwrap()
+}
+
+fn main() {
+    let count = AtomicU64::new(0);
+    let handle = log4rs::init_config(config()).unwrap();
+
+    let lock_free = bench("log4rs", &count, || log::logger().log(&record()));
+    check(&count, 0);
+
+    let locked = Mutex::new(log4rs::Logger::new(config()));
+    let mutex = bench("global mutex", &count, || {
+        locked.lock().unwrap().log(&record())
+    });
+    check(&count, 0);
+
+    let done = AtomicBool::new(false);
+    let reconfigured = thread::scope(|s| {
+        let reconfigure = s.spawn(|| {
+            let mut n = 0;
+            while !done.load(Ordering::Relaxed) {
+                handle.set_config(config());
+                n += 1;
+                thread::sleep(Duration::from_micros(100));
+            }
+            n
+        });
+        bench("log4rs while reconfiguring", &count, || {
+            log::logger().log(&record())
+        });
+        done.store(true, Ordering::Relaxed);
+        reconfigure.join().unwrap()
+    });
+    check(&count, reconfigured);
+
+    println!(
+        "log4rs is {:.1}x the throughput of a global mutex",
+        mutex.as_secs_f64() / lock_free.as_secs_f64()
+    );
+}
+
+fn record() -> Record<'static> {
+    Record::builder()
+        .level(Level::Info)
+        .target("app::db::pool")
+        .args(format_args!("checked out a connection"))
+        .build()
+}
+
+/// Logs from `THREADS` threads at once, adding the records each appended to
+/// `count`.
+fn bench<F>(name: &str, count: &AtomicU64, f: F) -> Duration
+where
+    F: Fn() + Sync,
+{
+    let start = Instant::now();
+    thread::scope(|s| {
+        for _ in 0..THREADS {
+            s.spawn(|| {
+                for _ in 0..ITERS {
+                    f();
+                }
+                count.fetch_add(APPENDED.with(Cell::take), Ordering::Relaxed);
+            });
+        }
+    });
+    let elapsed = start.elapsed();
+    println!(
+        "{}: {:?} for {} records from {} threads, {:.0} records/s",
+        name,
+        elapsed,
+        THREADS * ITERS,
+        THREADS,
+        (THREADS * ITERS) as f64 / elapsed.as_secs_f64()
+    );
+    elapsed
+}
+
+/// Checks that every record was appended exactly once, then resets the count.
+fn check(count: &AtomicU64, reconfigurations: usize) {
+    let appended = count.swap(0, Ordering::Relaxed);
+    assert_eq!(
+        appended,
+        (THREADS * ITERS) as u64,
+        "records lost or duplicated across {} reconfigurations",
+        reconfigurations
+    );
+}
@@ -0,0 +1,82 @@
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    hint::black_box,
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Instant,
+};
+
+use log::{Level, Record};
+use log4rs::encode::{pattern::PatternEncoder, writer::simple::SimpleWriter, Encode};
+
+const ITERS: usize = 100_000;
+const PATTERN: &str = "{d(%Y-%m-%d %H:%M:%S.%3f)} {l:<5} [{T}] {M}:{L} - {m}{n}";
+
+/// Counts allocations so the bench can report how many each record costs.
+struct Counting;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+fn main() {
+    let encoder = PatternEncoder::new(PATTERN);
+    bench("encode", |buf, record| {
+        encoder.encode(&mut SimpleWriter(buf), record).unwrap()
+    });
+    bench("parse and encode", |buf, record| {
+        PatternEncoder::new(PATTERN)
+            .encode(&mut SimpleWriter(buf), record)
+            .unwrap()
+    });
+}
+
+fn bench<F>(name: &str, mut f: F)
+where
+    F: FnMut(&mut Vec<u8>, &Record),
+{
+    let mut buf = Vec::with_capacity(1024);
+    let mut run = |buf: &mut Vec<u8>| {
+        buf.clear();
+        f(
+            buf,
+            &Record::builder()
+                .level(Level::Info)
+                .target("bench")
+                .module_path(Some("log4rs_bench::pattern"))
+                .line(Some(42))
+                .args(format_args!("request {} took {}ms", black_box(7), 12))
+                .build(),
+        );
+        black_box(&buf);
+    };
+
+    // warm up lazily initialized state such as the start time and thread name
+    run(&mut buf);
+
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+    let start = Instant::now();
+    for _ in 0..ITERS {
+        run(&mut buf);
+    }
+    let elapsed = start.elapsed();
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed) - allocations;
+
+    println!(
+        "{}: {:?} and {:.1} allocations per record",
+        name,
+        elapsed / ITERS as u32,
+        allocations as f64 / ITERS as f64
+    );
+}
@@ -0,0 +1,438 @@
+//! The async appender.
+//!
+//! Requires the `async_appender` feature.
+
+use derivative::Derivative;
+use log::Record;
+use std::{
+    io,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        mpsc::{self, Receiver, SyncSender, TrySendError},
+        Arc, Mutex, PoisonError,
+    },
+    thread::{self, JoinHandle},
+};
+
+#[cfg(feature = "config_parsing")]
+use crate::append::AppenderConfig;
+#[cfg(feature = "config_parsing")]
+use crate::config::{Deserialize, Deserializers};
+
+use crate::{append::Append, priv_record::OwnedRecord};
+
+const DEFAULT_CAPACITY: usize = 1024;
+
+/// The async appender's configuration.
+#[cfg(feature = "config_parsing")]
+#[derive(Clone, Eq, PartialEq, Hash, Debug, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AsyncAppenderConfig {
+    appender: AppenderConfig,
+    capacity: Option<usize>,
+    overflow: Option<OverflowPolicy>,
+}
+
+/// What an `AsyncAppender` does with a record when its queue is full.
+#[cfg_attr(feature = "config_parsing", derive(serde::Deserialize))]
+#[cfg_attr(feature = "config_parsing", serde(rename_all = "lowercase"))]
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub enum OverflowPolicy {
+    /// Wait for the background thread to make room.
+    #[default]
+    Block,
+    /// Discard the record.
+    Drop,
+}
+
+/// Counters describing the health of an `AsyncAppender`'s queue.
+///
+/// A handle is obtained from `AsyncAppender::metrics` and stays live after
+/// the appender is moved into a `Config`, so it can be kept by a metrics
+/// endpoint. The counters are updated without locking and read with relaxed
+/// ordering, so a snapshot taken while records are being logged may be
+/// slightly out of step across counters.
+#[derive(Clone, Debug, Default)]
+pub struct AsyncAppenderMetrics(Arc<Counters>);
+
+#[derive(Debug, Default)]
+struct Counters {
+    enqueued: AtomicU64,
+    dropped: AtomicU64,
+    flushed: AtomicU64,
+}
+
+impl AsyncAppenderMetrics {
+    /// Returns the number of records accepted into the queue.
+    pub fn enqueued(&self) -> u64 {
+        self.0.enqueued.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of records discarded because the queue was full.
+    ///
+    /// This is always 0 with `OverflowPolicy::Block`.
+    pub fn dropped(&self) -> u64 {
+        self.0.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of records the background thread has passed on to
+    /// the wrapped appender, whether or not it wrote them successfully.
+    pub fn flushed(&self) -> u64 {
+        self.0.flushed.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of records waiting in the queue.
+    pub fn queued(&self) -> u64 {
+        self.enqueued().saturating_sub(self.flushed())
+    }
+}
+
+enum Message {
+    Record(OwnedRecord),
+    Flush(SyncSender<()>),
+}
+
+/// An appender which hands records to another appender on a background
+/// thread.
+///
+/// Records are queued in a bounded channel and written in order. Dropping the
+/// appender waits for every queued record to be written.
+///
+/// The wrapped appender runs on the background thread, so thread names, thread
+/// IDs and MDC values seen by its encoder are those of that thread rather than
+/// of the logging call site.
+///
+/// Use `metrics` to observe how many records were queued, dropped and written.
+#[derive(Derivative)]
+#[derivative(Debug)]
+pub struct AsyncAppender {
+    #[derivative(Debug = "ignore")]
+    sender: Mutex<Option<SyncSender<Message>>>,
+    #[derivative(Debug = "ignore")]
+    worker: Option<JoinHandle<()>>,
+    capacity: usize,
+    overflow: OverflowPolicy,
+    metrics: AsyncAppenderMetrics,
+}
+
+impl Append for AsyncAppender {
+    fn append(&self, record: &Record) -> anyhow::Result<()> {
+        let sender = match self.sender() {
+            Some(sender) => sender,
+            None => return Ok(()),
+        };
+        let message = Message::Record(OwnedRecord::new(record));
+        let counters = &self.metrics.0;
+        match self.overflow {
+            OverflowPolicy::Block => match sender.send(message) {
+                Ok(()) => counters.enqueued.fetch_add(1, Ordering::Relaxed),
+                Err(_) => return Err(anyhow::anyhow!("async appender thread has exited")),
+            },
+            OverflowPolicy::Drop => match sender.try_send(message) {
+                Ok(()) => counters.enqueued.fetch_add(1, Ordering::Relaxed),
+                Err(TrySendError::Full(_)) => counters.dropped.fetch_add(1, Ordering::Relaxed),
+                Err(TrySendError::Disconnected(_)) => {
+                    return Err(anyhow::anyhow!("async appender thread has exited"))
+                }
+            },
+        };
+        Ok(())
+    }
+
+    fn flush(&self) {
+        let (tx, rx) = mpsc::sync_channel(1);
+        let sent = match self.sender() {
+            Some(sender) => sender.send(Message::Flush(tx)).is_ok(),
+            None => false,
+        };
+        if sent {
+            let _ = rx.recv();
+        }
+    }
+}
+
+impl Drop for AsyncAppender {
+    fn drop(&mut self) {
+        // Closing the channel lets the worker drain what is left and exit.
+        self.sender
+            .get_mut()
+            .unwrap_or_else(PoisonError::into_inner)
+            .take();
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+impl AsyncAppender {
+    /// Creates a new `AsyncAppender` builder.
+    pub fn builder() -> AsyncAppenderBuilder {
+        AsyncAppenderBuilder {
+            capacity: DEFAULT_CAPACITY,
+            overflow: OverflowPolicy::Block,
+        }
+    }
+
+    /// Returns a copy of the sending half of the queue, so that sending, which
+    /// may block while the queue is full, happens without the lock held.
+    fn sender(&self) -> Option<SyncSender<Message>> {
+        self.sender
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Returns the maximum number of records held in the queue.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns the policy applied when the queue is full.
+    pub fn overflow(&self) -> OverflowPolicy {
+        self.overflow
+    }
+
+    /// Returns a handle to the appender's queue counters.
+    ///
+    /// Take the handle before moving the appender into a `Config`.
+    pub fn metrics(&self) -> AsyncAppenderMetrics {
+        self.metrics.clone()
+    }
+}
+
+fn run(appender: Box<dyn Append>, receiver: Receiver<Message>, metrics: AsyncAppenderMetrics) {
+    for message in receiver {
+        match message {
+            Message::Record(record) => {
+                let result = record.with_record(|record| appender.append(record));
+                metrics.0.flushed.fetch_add(1, Ordering::Relaxed);
+                if let Err(e) = result {
+                    crate::handle_error(&e);
+                }
+            }
+            Message::Flush(done) => {
+                appender.flush();
+                let _ = done.send(());
+            }
+        }
+    }
+    appender.flush();
+}
+
+/// A builder for `AsyncAppender`s.
+pub struct AsyncAppenderBuilder {
+    capacity: usize,
+    overflow: OverflowPolicy,
+}
+
+impl AsyncAppenderBuilder {
+    /// Sets the maximum number of records waiting to be written.
+    ///
+    /// Defaults to 1024.
+    pub fn capacity(mut self, capacity: usize) -> AsyncAppenderBuilder {
+        self.capacity = capacity;
+        self
+    }
+
+    /// Sets what happens to a record logged while the queue is full.
+    ///
+    /// Defaults to `OverflowPolicy::Block`.
+    pub fn overflow(mut self, overflow: OverflowPolicy) -> AsyncAppenderBuilder {
+        self.overflow = overflow;
+        self
+    }
+
+    /// Consumes the `AsyncAppenderBuilder`, spawning the background thread
+    /// which writes to `appender`.
+    ///
+    /// Returns an error if the thread can't be spawned.
+    pub fn build(self, appender: Box<dyn Append>) -> io::Result<AsyncAppender> {
+        let (sender, receiver) = mpsc::sync_channel(self.capacity);
+        let metrics = AsyncAppenderMetrics::default();
+        let worker_metrics = metrics.clone();
+        let worker = thread::Builder::new()
+            .name("log4rs-async".to_owned())
+            .spawn(move || run(appender, receiver, worker_metrics))?;
+
+        Ok(AsyncAppender {
+            sender: Mutex::new(Some(sender)),
+            worker: Some(worker),
+            capacity: self.capacity,
+            overflow: self.overflow,
+            metrics,
+        })
+    }
+}
+
+/// A deserializer for the `AsyncAppender`.
+///
+/// # Configuration
+///
+/// ```yaml
+/// kind: async
+///
+/// # The appender which records are handed to on the background thread.
+/// # Required. Filters should be attached to the async appender itself.
+/// appender:
+///   kind: file
+///   path: log/foo.log
+///
+/// # The maximum number of records waiting to be written. Defaults to 1024.
+/// capacity: 1024
+///
+/// # What to do with a record logged while the queue is full: `block` waits
+/// # for room, `drop` discards the record. Defaults to `block`.
+/// overflow: block
+/// ```
+#[cfg(feature = "config_parsing")]
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub struct AsyncAppenderDeserializer;
+
+#[cfg(feature = "config_parsing")]
+impl Deserialize for AsyncAppenderDeserializer {
+    type Trait = dyn Append;
+
+    type Config = AsyncAppenderConfig;
+
+    fn deserialize(
+        &self,
+        config: AsyncAppenderConfig,
+        deserializers: &Deserializers,
+    ) -> anyhow::Result<Box<Self::Trait>> {
+        if config.appender.has_filters() {
+            anyhow::bail!("filters must be attached to the async appender, not the one it wraps");
+        }
+        let inner = deserializers.deserialize(&config.appender.kind, config.appender.config)?;
+        let mut appender = AsyncAppender::builder();
+        if let Some(capacity) = config.capacity {
+            appender = appender.capacity(capacity);
+        }
+        if let Some(overflow) = config.overflow {
+            appender = appender.overflow(overflow);
+        }
+        Ok(Box::new(appender.build(inner)?))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use log::Level;
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+    use crate::append::test_util::Collect;
+
+    #[test]
+    fn drains_in_order_on_drop() {
+        let records = Arc::new(Mutex::new(vec![]));
+        let appender = AsyncAppender::builder()
+            .capacity(2)
+            .build(Box::new(Collect(records.clone())))
+            .unwrap();
+
+        for i in 0..100 {
+            appender
+                .append(&Record::builder().args(format_args!("{}", i)).build())
+                .unwrap();
+        }
+        drop(appender);
+
+        let expected = (0..100).map(|i| i.to_string()).collect::<Vec<_>>();
+        assert_eq!(*records.lock().unwrap(), expected);
+    }
+
+    #[test]
+    fn flush_waits_for_queue() {
+        let records = Arc::new(Mutex::new(vec![]));
+        let appender = AsyncAppender::builder()
+            .overflow(OverflowPolicy::Drop)
+            .build(Box::new(Collect(records.clone())))
+            .unwrap();
+
+        appender
+            .append(
+                &Record::builder()
+                    .level(Level::Warn)
+                    .args(format_args!("hello"))
+                    .build(),
+            )
+            .unwrap();
+        appender.flush();
+
+        assert_eq!(*records.lock().unwrap(), ["hello"]);
+    }
+
+    /// Reports each record it receives, then waits until the gate is open.
+    #[derive(Debug)]
+    struct Gate(Mutex<mpsc::Sender<()>>, Arc<Mutex<()>>);
+
+    impl Append for Gate {
+        fn append(&self, _: &Record) -> anyhow::Result<()> {
+            let _ = self.0.lock().unwrap().send(());
+            drop(self.1.lock().unwrap());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn metrics() {
+        let (entered, on_enter) = mpsc::channel();
+        let gate = Arc::new(Mutex::new(()));
+        let closed = gate.lock().unwrap();
+        let appender = AsyncAppender::builder()
+            .capacity(2)
+            .overflow(OverflowPolicy::Drop)
+            .build(Box::new(Gate(Mutex::new(entered), gate.clone())))
+            .unwrap();
+        let metrics = appender.metrics();
+
+        // the worker takes the first record and waits at the gate, the next
+        // two fill the queue, and the rest are dropped
+        let record = |i| {
+            appender
+                .append(&Record::builder().args(format_args!("{}", i)).build())
+                .unwrap()
+        };
+        record(0);
+        on_enter.recv().unwrap();
+        (1..10).for_each(record);
+        assert_eq!(metrics.enqueued(), 3);
+        assert_eq!(metrics.dropped(), 7);
+        assert_eq!(metrics.flushed(), 0);
+        assert_eq!(metrics.queued(), 3);
+
+        drop(closed);
+        appender.flush();
+        assert_eq!(metrics.flushed(), 3);
+        assert_eq!(metrics.queued(), 0);
+    }
+
+    #[test]
+    #[cfg(all(
+        feature = "config_parsing",
+        feature = "yaml_format",
+        feature = "file_appender"
+    ))]
+    fn deserialize() {
+        use crate::config::RawConfig;
+
+        let dir = tempfile::tempdir().unwrap();
+        let config = format!(
+            "
+appenders:
+    foo:
+        kind: async
+        capacity: 16
+        overflow: drop
+        appender:
+            kind: file
+            path: {0}/foo.log
+",
+            dir.path().display()
+        );
+
+        let config = ::serde_yaml::from_str::<RawConfig>(&config).unwrap();
+        let errors = config.appenders_lossy(&Deserializers::new()).1;
+        assert!(errors.is_empty(), "{:?}", errors);
+    }
+}
@@ -0,0 +1,178 @@
+//! The composite appender.
+//!
+//! Requires the `composite_appender` feature.
+
+use log::Record;
+
+#[cfg(feature = "config_parsing")]
+use crate::append::AppenderConfig;
+#[cfg(feature = "config_parsing")]
+use crate::config::{Deserialize, Deserializers};
+
+use crate::append::Append;
+
+/// The composite appender's configuration.
+#[cfg(feature = "config_parsing")]
+#[derive(Clone, Eq, PartialEq, Hash, Debug, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CompositeAppenderConfig {
+    appenders: Vec<AppenderConfig>,
+}
+
+/// An appender which hands each record to several other appenders.
+///
+/// Every appender receives every record, even if an earlier one fails. The
+/// errors of all failing appenders are combined into the one returned.
+#[derive(Debug)]
+pub struct CompositeAppender {
+    appenders: Vec<Box<dyn Append>>,
+}
+
+impl Append for CompositeAppender {
+    fn append(&self, record: &Record) -> anyhow::Result<()> {
+        let errors = self
+            .appenders
+            .iter()
+            .filter_map(|appender| appender.append(record).err())
+            .map(|e| e.to_string())
+            .collect::<Vec<_>>();
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(anyhow::anyhow!(errors.join("; ")))
+        }
+    }
+
+    fn flush(&self) {
+        for appender in &self.appenders {
+            appender.flush();
+        }
+    }
+}
+
+impl CompositeAppender {
+    /// Creates a new `CompositeAppender` builder.
+    pub fn builder() -> CompositeAppenderBuilder {
+        CompositeAppenderBuilder { appenders: vec![] }
+    }
+}
+
+/// A builder for `CompositeAppender`s.
+pub struct CompositeAppenderBuilder {
+    appenders: Vec<Box<dyn Append>>,
+}
+
+impl CompositeAppenderBuilder {
+    /// Adds an appender which receives every record.
+    pub fn appender(mut self, appender: Box<dyn Append>) -> CompositeAppenderBuilder {
+        self.appenders.push(appender);
+        self
+    }
+
+    /// Consumes the `CompositeAppenderBuilder`, producing a
+    /// `CompositeAppender`.
+    pub fn build(self) -> CompositeAppender {
+        CompositeAppender {
+            appenders: self.appenders,
+        }
+    }
+}
+
+/// A deserializer for the `CompositeAppender`.
+///
+/// # Configuration
+///
+/// ```yaml
+/// kind: composite
+///
+/// # The appenders which each receive every record. Required. Filters should
+/// # be attached to the composite appender itself.
+/// appenders:
+///   - kind: console
+///   - kind: file
+///     path: log/foo.log
+/// ```
+#[cfg(feature = "config_parsing")]
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub struct CompositeAppenderDeserializer;
+
+#[cfg(feature = "config_parsing")]
+impl Deserialize for CompositeAppenderDeserializer {
+    type Trait = dyn Append;
+
+    type Config = CompositeAppenderConfig;
+
+    fn deserialize(
+        &self,
+        config: CompositeAppenderConfig,
+        deserializers: &Deserializers,
+    ) -> anyhow::Result<Box<Self::Trait>> {
+        let mut appender = CompositeAppender::builder();
+        for inner in config.appenders {
+            if inner.has_filters() {
+                anyhow::bail!(
+                    "filters must be attached to the composite appender, not the ones it wraps"
+                );
+            }
+            appender = appender.appender(deserializers.deserialize(&inner.kind, inner.config)?);
+        }
+        Ok(Box::new(appender.build()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+    use crate::append::test_util::{Collect, Fail};
+
+    #[test]
+    fn failures_dont_stop_other_appenders() {
+        let first = Arc::new(Mutex::new(vec![]));
+        let second = Arc::new(Mutex::new(vec![]));
+        let appender = CompositeAppender::builder()
+            .appender(Box::new(Fail("disk full")))
+            .appender(Box::new(Collect(first.clone())))
+            .appender(Box::new(Fail("broken pipe")))
+            .appender(Box::new(Collect(second.clone())))
+            .build();
+
+        let err = appender
+            .append(&Record::builder().args(format_args!("hello")).build())
+            .unwrap_err();
+
+        assert_eq!(err.to_string(), "disk full; broken pipe");
+        assert_eq!(*first.lock().unwrap(), ["hello"]);
+        assert_eq!(*second.lock().unwrap(), ["hello"]);
+    }
+
+    #[test]
+    #[cfg(all(
+        feature = "config_parsing",
+        feature = "yaml_format",
+        feature = "console_appender",
+        feature = "file_appender"
+    ))]
+    fn deserialize() {
+        use crate::config::RawConfig;
+
+        let dir = tempfile::tempdir().unwrap();
+        let config = format!(
+            "
+appenders:
+    both:
+        kind: composite
+        appenders:
+            - kind: console
+            - kind: file
+              path: {0}/foo.log
+",
+            dir.path().display()
+        );
+
+        let config = ::serde_yaml::from_str::<RawConfig>(&config).unwrap();
+        let errors = config.appenders_lossy(&Deserializers::new()).1;
+        assert!(errors.is_empty(), "{:?}", errors);
+    }
+}
@@ -3,7 +3,7 @@
 //! Requires the `console_appender` feature.
 
 use derivative::Derivative;
-use log::Record;
+use log::{LevelFilter, Record};
 use std::{
     fmt,
     io::{self, Write},
@@ -19,7 +19,7 @@ use crate::{
         self,
         pattern::PatternEncoder,
         writer::{
-            console::{ConsoleWriter, ConsoleWriterLock},
+            console::{ColorMode, ConsoleWriter, ConsoleWriterLock},
             simple::SimpleWriter,
         },
         Encode, Style,
@@ -35,6 +35,8 @@ pub struct ConsoleAppenderConfig {
     target: Option<ConfigTarget>,
     encoder: Option<EncoderConfig>,
     tty_only: Option<bool>,
+    color_mode: Option<ColorMode>,
+    split_level: Option<LevelFilter>,
 }
 
 #[cfg(feature = "config_parsing")]
@@ -49,13 +51,34 @@ enum ConfigTarget {
 enum Writer {
     Tty(ConsoleWriter),
     Raw(StdWriter),
+    #[cfg(test)]
+    Test(std::sync::Arc<std::sync::Mutex<Vec<u8>>>),
 }
 
 impl Writer {
-    fn lock(&self) -> WriterLock {
+    fn new(target: Target, color_mode: Option<ColorMode>) -> Writer {
+        match target {
+            Target::Stderr => match color_mode
+                .map_or_else(ConsoleWriter::stderr, ConsoleWriter::stderr_with_color_mode)
+            {
+                Some(writer) => Writer::Tty(writer),
+                None => Writer::Raw(StdWriter::stderr()),
+            },
+            Target::Stdout => match color_mode
+                .map_or_else(ConsoleWriter::stdout, ConsoleWriter::stdout_with_color_mode)
+            {
+                Some(writer) => Writer::Tty(writer),
+                None => Writer::Raw(StdWriter::stdout()),
+            },
+        }
+    }
+
+    fn lock(&self) -> WriterLock<'_> {
         match *self {
             Writer::Tty(ref w) => WriterLock::Tty(w.lock()),
             Writer::Raw(ref w) => WriterLock::Raw(SimpleWriter(w.lock())),
+            #[cfg(test)]
+            Writer::Test(ref w) => WriterLock::Test(SimpleWriter(TestLock(w.lock().unwrap()))),
         }
     }
 
@@ -72,6 +95,22 @@ impl Writer {
 enum WriterLock<'a> {
     Tty(ConsoleWriterLock<'a>),
     Raw(SimpleWriter<StdWriterLock<'a>>),
+    #[cfg(test)]
+    Test(SimpleWriter<TestLock<'a>>),
+}
+
+#[cfg(test)]
+struct TestLock<'a>(std::sync::MutexGuard<'a, Vec<u8>>);
+
+#[cfg(test)]
+impl<'a> io::Write for TestLock<'a> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
 }
 
 impl<'a> io::Write for WriterLock<'a> {
@@ -79,6 +118,8 @@ impl<'a> io::Write for WriterLock<'a> {
         match *self {
             WriterLock::Tty(ref mut w) => w.write(buf),
             WriterLock::Raw(ref mut w) => w.write(buf),
+            #[cfg(test)]
+            WriterLock::Test(ref mut w) => w.write(buf),
         }
     }
 
@@ -86,6 +127,8 @@ impl<'a> io::Write for WriterLock<'a> {
         match *self {
             WriterLock::Tty(ref mut w) => w.flush(),
             WriterLock::Raw(ref mut w) => w.flush(),
+            #[cfg(test)]
+            WriterLock::Test(ref mut w) => w.flush(),
         }
     }
 
@@ -93,6 +136,8 @@ impl<'a> io::Write for WriterLock<'a> {
         match *self {
             WriterLock::Tty(ref mut w) => w.write_all(buf),
             WriterLock::Raw(ref mut w) => w.write_all(buf),
+            #[cfg(test)]
+            WriterLock::Test(ref mut w) => w.write_all(buf),
         }
     }
 
@@ -100,6 +145,8 @@ impl<'a> io::Write for WriterLock<'a> {
         match *self {
             WriterLock::Tty(ref mut w) => w.write_fmt(fmt),
             WriterLock::Raw(ref mut w) => w.write_fmt(fmt),
+            #[cfg(test)]
+            WriterLock::Test(ref mut w) => w.write_fmt(fmt),
         }
     }
 }
@@ -109,6 +156,8 @@ impl<'a> encode::Write for WriterLock<'a> {
         match *self {
             WriterLock::Tty(ref mut w) => w.set_style(style),
             WriterLock::Raw(ref mut w) => w.set_style(style),
+            #[cfg(test)]
+            WriterLock::Test(ref mut w) => w.set_style(style),
         }
     }
 }
@@ -116,27 +165,49 @@ impl<'a> encode::Write for WriterLock<'a> {
 /// An appender which logs to standard out.
 ///
 /// It supports output styling if standard out is a console buffer on Windows
-/// or is a TTY on Unix.
+/// or is a TTY on Unix. Each stream is checked separately, so logging to a
+/// terminal's standard error is styled even if standard out is redirected.
+/// The color mode overrides the detection.
+///
+/// With a split level set, records at or above it go to standard error and
+/// the rest to the target, so that warnings and errors can be told apart from
+/// regular output.
 #[derive(Derivative)]
 #[derivative(Debug)]
 pub struct ConsoleAppender {
+    target: Target,
     #[derivative(Debug = "ignore")]
     writer: Writer,
     encoder: Box<dyn Encode>,
     do_write: bool,
+    #[derivative(Debug = "ignore")]
+    split: Option<Split>,
+}
+
+/// The standard error stream of a `ConsoleAppender` with a split level.
+struct Split {
+    level: LevelFilter,
+    writer: Writer,
+    do_write: bool,
 }
 
 impl Append for ConsoleAppender {
     fn append(&self, record: &Record) -> anyhow::Result<()> {
-        if self.do_write {
-            let mut writer = self.writer.lock();
-            self.encoder.encode(&mut writer, record)?;
+        self.append_with_encoder(record, &*self.encoder)
+    }
+
+    fn append_with_encoder(&self, record: &Record, encoder: &dyn Encode) -> anyhow::Result<()> {
+        let (writer, do_write) = match self.split {
+            Some(ref split) if record.level() <= split.level => (&split.writer, split.do_write),
+            _ => (&self.writer, self.do_write),
+        };
+        if do_write {
+            let mut writer = writer.lock();
+            encoder.encode(&mut writer, record)?;
             writer.flush()?;
         }
         Ok(())
     }
-
-    fn flush(&self) {}
 }
 
 impl ConsoleAppender {
@@ -146,6 +217,8 @@ impl ConsoleAppender {
             encoder: None,
             target: Target::Stdout,
             tty_only: false,
+            color_mode: None,
+            split_level: LevelFilter::Off,
         }
     }
 }
@@ -155,6 +228,8 @@ pub struct ConsoleAppenderBuilder {
     encoder: Option<Box<dyn Encode>>,
     target: Target,
     tty_only: bool,
+    color_mode: Option<ColorMode>,
+    split_level: LevelFilter,
 }
 
 impl ConsoleAppenderBuilder {
@@ -180,27 +255,50 @@ impl ConsoleAppenderBuilder {
         self
     }
 
+    /// Sets whether output is styled.
+    ///
+    /// `ColorMode::Always` styles output even when the target is not a TTY,
+    /// which then counts as one for `tty_only`. `ColorMode::Never` never
+    /// styles it. Defaults to the mode set by the `NO_COLOR`,
+    /// `CLICOLOR_FORCE` and `CLICOLOR` environment variables.
+    pub fn color_mode(mut self, color_mode: ColorMode) -> ConsoleAppenderBuilder {
+        self.color_mode = Some(color_mode);
+        self
+    }
+
+    /// Sets the level at or above which records go to standard error, with
+    /// the rest going to the target.
+    ///
+    /// Defaults to `LevelFilter::Off`, which sends every record to the target.
+    pub fn split_level(mut self, split_level: LevelFilter) -> ConsoleAppenderBuilder {
+        self.split_level = split_level;
+        self
+    }
+
     /// Consumes the `ConsoleAppenderBuilder`, producing a `ConsoleAppender`.
     pub fn build(self) -> ConsoleAppender {
-        let writer = match self.target {
-            Target::Stderr => match ConsoleWriter::stderr() {
-                Some(writer) => Writer::Tty(writer),
-                None => Writer::Raw(StdWriter::stderr()),
-            },
-            Target::Stdout => match ConsoleWriter::stdout() {
-                Some(writer) => Writer::Tty(writer),
-                None => Writer::Raw(StdWriter::stdout()),
-            },
-        };
-
+        let writer = Writer::new(self.target, self.color_mode);
         let do_write = writer.is_tty() || !self.tty_only;
 
+        let split = if self.split_level == LevelFilter::Off {
+            None
+        } else {
+            let writer = Writer::new(Target::Stderr, self.color_mode);
+            Some(Split {
+                level: self.split_level,
+                do_write: writer.is_tty() || !self.tty_only,
+                writer,
+            })
+        };
+
         ConsoleAppender {
+            target: self.target,
             writer,
             encoder: self
                 .encoder
                 .unwrap_or_else(|| Box::<PatternEncoder>::default()),
             do_write,
+            split,
         }
     }
 }
@@ -227,6 +325,16 @@ pub enum Target {
 /// # Set this boolean when the console appender must only write when the target is a TTY.
 /// tty_only: false
 ///
+/// # Whether output is styled. `auto` styles it only when the target is a
+/// # TTY, `always` also when it is redirected, and `never` not at all.
+/// # Defaults to the mode set by the `NO_COLOR`, `CLICOLOR_FORCE` and
+/// # `CLICOLOR` environment variables, or `auto`.
+/// color_mode: auto
+///
+/// # Records at or above this level are written to stderr instead of the
+/// # target. Defaults to `off`, which writes every record to the target.
+/// split_level: warn
+///
 /// # The encoder to use to format output. Defaults to `kind: pattern`.
 /// encoder:
 ///   kind: pattern
@@ -246,6 +354,21 @@ impl Deserialize for ConsoleAppenderDeserializer {
         config: ConsoleAppenderConfig,
         deserializers: &Deserializers,
     ) -> anyhow::Result<Box<dyn Append>> {
+        Ok(Box::new(self.build(config, deserializers)?))
+    }
+
+    fn supports_encoder_override(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(feature = "config_parsing")]
+impl ConsoleAppenderDeserializer {
+    fn build(
+        &self,
+        config: ConsoleAppenderConfig,
+        deserializers: &Deserializers,
+    ) -> anyhow::Result<ConsoleAppender> {
         let mut appender = ConsoleAppender::builder();
         if let Some(target) = config.target {
             let target = match target {
@@ -257,9 +380,148 @@ impl Deserialize for ConsoleAppenderDeserializer {
         if let Some(tty_only) = config.tty_only {
             appender = appender.tty_only(tty_only);
         }
-        if let Some(encoder) = config.encoder {
-            appender = appender.encoder(deserializers.deserialize(&encoder.kind, encoder.config)?);
+        if let Some(color_mode) = config.color_mode {
+            appender = appender.color_mode(color_mode);
+        }
+        if let Some(split_level) = config.split_level {
+            appender = appender.split_level(split_level);
+        }
+        if let Some(encoder) = deserializers.encoder(config.encoder)? {
+            appender = appender.encoder(encoder);
         }
-        Ok(Box::new(appender.build()))
+        Ok(appender.build())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    #[cfg(unix)]
+    fn color_mode() {
+        let appender = ConsoleAppender::builder()
+            .color_mode(ColorMode::Always)
+            .tty_only(true)
+            .build();
+        assert!(appender.writer.is_tty());
+        assert!(appender.do_write);
+
+        let appender = ConsoleAppender::builder()
+            .target(Target::Stderr)
+            .color_mode(ColorMode::Never)
+            .build();
+        assert!(!appender.writer.is_tty());
+    }
+
+    #[test]
+    fn split_level() {
+        use std::sync::{Arc, Mutex};
+
+        let appender = ConsoleAppender::builder().build();
+        assert!(appender.split.is_none());
+        let appender = ConsoleAppender::builder()
+            .split_level(LevelFilter::Warn)
+            .build();
+        assert_eq!(appender.split.as_ref().unwrap().level, LevelFilter::Warn);
+
+        let stdout = Arc::new(Mutex::new(vec![]));
+        let stderr = Arc::new(Mutex::new(vec![]));
+        let appender = ConsoleAppender {
+            target: Target::Stdout,
+            writer: Writer::Test(stdout.clone()),
+            encoder: Box::new(PatternEncoder::new("{l} {m};")),
+            do_write: true,
+            split: Some(Split {
+                level: LevelFilter::Warn,
+                writer: Writer::Test(stderr.clone()),
+                do_write: true,
+            }),
+        };
+        for level in [
+            log::Level::Error,
+            log::Level::Warn,
+            log::Level::Info,
+            log::Level::Trace,
+        ] {
+            appender
+                .append(
+                    &Record::builder()
+                        .level(level)
+                        .args(format_args!("hello"))
+                        .build(),
+                )
+                .unwrap();
+        }
+        assert_eq!(*stdout.lock().unwrap(), b"INFO hello;TRACE hello;");
+        assert_eq!(*stderr.lock().unwrap(), b"ERROR hello;WARN hello;");
+    }
+
+    #[test]
+    #[cfg(all(feature = "config_parsing", feature = "yaml_format"))]
+    fn deserialize_target() {
+        use crate::config::{Deserializers, RawConfig};
+
+        let config = "
+appenders:
+    out:
+        kind: console
+    err:
+        kind: console
+        target: stderr
+        color_mode: always
+";
+        let config = ::serde_yaml::from_str::<RawConfig>(config).unwrap();
+        let (appenders, errors) = config.appenders_lossy(&Deserializers::new());
+        assert!(errors.is_empty());
+        assert_eq!(appenders.len(), 2);
+
+        for (config, target) in [("{}", Target::Stdout), ("target: stderr", Target::Stderr)] {
+            let config = ::serde_yaml::from_str(config).unwrap();
+            let appender = ConsoleAppenderDeserializer
+                .build(config, &Deserializers::new())
+                .unwrap();
+            assert_eq!(appender.target, target);
+        }
+
+        let config = "
+appenders:
+    bad:
+        kind: console
+        target: stdrr
+";
+        let config = ::serde_yaml::from_str::<RawConfig>(config).unwrap();
+        let errors = config.appenders_lossy(&Deserializers::new()).1;
+        let msg = format!("{:?}", errors);
+        assert!(msg.contains("stdrr"), "{}", msg);
+        assert!(msg.contains("stdout"), "{}", msg);
+        assert!(msg.contains("stderr"), "{}", msg);
+    }
+
+    #[test]
+    #[cfg(all(feature = "config_parsing", feature = "yaml_format"))]
+    fn deserialize_split_level() {
+        use crate::config::{Deserializers, RawConfig};
+
+        let config = "
+appenders:
+    split:
+        kind: console
+        split_level: warn
+";
+        let config = ::serde_yaml::from_str::<RawConfig>(config).unwrap();
+        let (appenders, errors) = config.appenders_lossy(&Deserializers::new());
+        assert!(errors.is_empty(), "{}", errors);
+        assert_eq!(appenders.len(), 1);
+
+        let config = "
+appenders:
+    bad:
+        kind: console
+        split_level: loud
+";
+        let config = ::serde_yaml::from_str::<RawConfig>(config).unwrap();
+        let errors = config.appenders_lossy(&Deserializers::new()).1;
+        assert!(errors.to_string().contains("loud"), "{}", errors);
     }
 }
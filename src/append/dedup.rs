@@ -0,0 +1,380 @@
+//! The dedup appender.
+//!
+//! Requires the `dedup_appender` feature.
+
+use derivative::Derivative;
+use log::{Level, Record};
+use std::{
+    sync::{Mutex, PoisonError},
+    time::{Duration, Instant},
+};
+
+#[cfg(feature = "config_parsing")]
+use crate::append::AppenderConfig;
+#[cfg(feature = "config_parsing")]
+use crate::config::{de_duration, Deserialize, Deserializers};
+
+use crate::append::Append;
+
+const DEFAULT_WINDOW: Duration = Duration::from_secs(10);
+const DEFAULT_MAX_COUNT: u32 = 1000;
+
+/// The dedup appender's configuration.
+#[cfg(feature = "config_parsing")]
+#[derive(Clone, Eq, PartialEq, Hash, Debug, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DedupAppenderConfig {
+    appender: AppenderConfig,
+    #[serde(deserialize_with = "de_duration", default)]
+    window: Option<Duration>,
+    max_count: Option<u32>,
+}
+
+/// The last message passed on, and how often it has been suppressed since.
+struct State {
+    message: String,
+    level: Level,
+    target: String,
+    repeats: u32,
+    since: Instant,
+}
+
+/// An appender which suppresses consecutive duplicate messages before handing
+/// records to another appender.
+///
+/// Records are compared by their formatted message. While the same message
+/// keeps being logged, only the first is passed on. A summary record of the
+/// form `last message repeated N times` takes the place of the rest. It is
+/// written when a different message arrives, when the window has passed
+/// since the last summary, when the repeat count reaches its maximum, and
+/// when the appender is flushed. If the summary can't be written, the
+/// different message is still passed on, and the error is returned with
+/// any of its own.
+#[derive(Derivative)]
+#[derivative(Debug)]
+pub struct DedupAppender {
+    appender: Box<dyn Append>,
+    window: Duration,
+    max_count: u32,
+    #[derivative(Debug = "ignore")]
+    state: Mutex<Option<State>>,
+}
+
+impl Append for DedupAppender {
+    fn append(&self, record: &Record) -> anyhow::Result<()> {
+        let message = record.args().to_string();
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+
+        if let Some(ref mut state) = *state {
+            if state.message == message {
+                state.repeats += 1;
+                if state.repeats >= self.max_count || state.since.elapsed() >= self.window {
+                    return self.summarize(state);
+                }
+                return Ok(());
+            }
+        }
+
+        // a summary which can't be written must not cost the new record too
+        let summary = match *state {
+            Some(ref mut state) => self.summarize(state),
+            None => Ok(()),
+        };
+        *state = Some(State {
+            message,
+            level: record.level(),
+            target: record.target().to_owned(),
+            repeats: 0,
+            since: Instant::now(),
+        });
+        match (self.appender.append(record), summary) {
+            (result, Ok(())) => result,
+            (Ok(()), Err(e)) => Err(e),
+            (Err(e), Err(summary)) => Err(anyhow::anyhow!(
+                "{}; writing the repeat summary before it also failed: {}",
+                e,
+                summary
+            )),
+        }
+    }
+
+    fn flush(&self) {
+        if let Some(ref mut state) = *self.state.lock().unwrap_or_else(PoisonError::into_inner) {
+            if let Err(e) = self.summarize(state) {
+                crate::handle_error(&e);
+            }
+        }
+        self.appender.flush();
+    }
+}
+
+impl Drop for DedupAppender {
+    fn drop(&mut self) {
+        self.flush();
+    }
+}
+
+impl DedupAppender {
+    /// Creates a new `DedupAppender` builder.
+    pub fn builder() -> DedupAppenderBuilder {
+        DedupAppenderBuilder {
+            window: DEFAULT_WINDOW,
+            max_count: DEFAULT_MAX_COUNT,
+        }
+    }
+
+    /// Writes the summary for any suppressed repeats and resets the count.
+    fn summarize(&self, state: &mut State) -> anyhow::Result<()> {
+        let repeats = state.repeats;
+        state.repeats = 0;
+        state.since = Instant::now();
+        if repeats == 0 {
+            return Ok(());
+        }
+        self.appender.append(
+            &Record::builder()
+                .level(state.level)
+                .target(&state.target)
+                .args(format_args!(
+                    "last message repeated {} {}",
+                    repeats,
+                    if repeats == 1 { "time" } else { "times" }
+                ))
+                .build(),
+        )
+    }
+}
+
+/// A builder for `DedupAppender`s.
+pub struct DedupAppenderBuilder {
+    window: Duration,
+    max_count: u32,
+}
+
+impl DedupAppenderBuilder {
+    /// Sets how long repeats of a message are counted before a summary is
+    /// written.
+    ///
+    /// Defaults to 10 seconds.
+    pub fn window(mut self, window: Duration) -> DedupAppenderBuilder {
+        self.window = window;
+        self
+    }
+
+    /// Sets how many repeats of a message are counted before a summary is
+    /// written.
+    ///
+    /// Defaults to 1000. A count of 0 is treated as 1.
+    pub fn max_count(mut self, max_count: u32) -> DedupAppenderBuilder {
+        self.max_count = max_count.max(1);
+        self
+    }
+
+    /// Consumes the `DedupAppenderBuilder`, producing a `DedupAppender` which
+    /// writes to `appender`.
+    pub fn build(self, appender: Box<dyn Append>) -> DedupAppender {
+        DedupAppender {
+            appender,
+            window: self.window,
+            max_count: self.max_count,
+            state: Mutex::new(None),
+        }
+    }
+}
+
+/// A deserializer for the `DedupAppender`.
+///
+/// # Configuration
+///
+/// ```yaml
+/// kind: dedup
+///
+/// # The appender which records are passed on to. Required. Filters should be
+/// # attached to the dedup appender itself.
+/// appender:
+///   kind: console
+///
+/// # How long repeats of a message are counted before a summary is written.
+/// # Defaults to 10 seconds.
+/// window: 10 seconds
+///
+/// # How many repeats of a message are counted before a summary is written.
+/// # At least 1. Defaults to 1000.
+/// max_count: 1000
+/// ```
+#[cfg(feature = "config_parsing")]
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub struct DedupAppenderDeserializer;
+
+#[cfg(feature = "config_parsing")]
+impl Deserialize for DedupAppenderDeserializer {
+    type Trait = dyn Append;
+
+    type Config = DedupAppenderConfig;
+
+    fn deserialize(
+        &self,
+        config: DedupAppenderConfig,
+        deserializers: &Deserializers,
+    ) -> anyhow::Result<Box<Self::Trait>> {
+        if config.appender.has_filters() {
+            anyhow::bail!("filters must be attached to the dedup appender, not the one it wraps");
+        }
+        let inner = deserializers.deserialize(&config.appender.kind, config.appender.config)?;
+        let mut appender = DedupAppender::builder();
+        if let Some(window) = config.window {
+            appender = appender.window(window);
+        }
+        if let Some(max_count) = config.max_count {
+            if max_count == 0 {
+                anyhow::bail!("max_count must be at least 1");
+            }
+            appender = appender.max_count(max_count);
+        }
+        Ok(Box::new(appender.build(inner)))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+    use crate::append::test_util::Collect;
+
+    fn log(appender: &DedupAppender, message: &str) {
+        appender
+            .append(&Record::builder().args(format_args!("{}", message)).build())
+            .unwrap();
+    }
+
+    #[test]
+    fn collapses_repeats() {
+        let records = Arc::new(Mutex::new(vec![]));
+        let appender = DedupAppender::builder().build(Box::new(Collect(records.clone())));
+
+        for _ in 0..4 {
+            log(&appender, "disk full");
+        }
+        log(&appender, "retrying");
+        log(&appender, "retrying");
+        drop(appender);
+
+        assert_eq!(
+            *records.lock().unwrap(),
+            [
+                "disk full",
+                "last message repeated 3 times",
+                "retrying",
+                "last message repeated 1 time",
+            ]
+        );
+    }
+
+    #[test]
+    fn max_count() {
+        let records = Arc::new(Mutex::new(vec![]));
+        let appender = DedupAppender::builder()
+            .max_count(2)
+            .build(Box::new(Collect(records.clone())));
+
+        for _ in 0..5 {
+            log(&appender, "spam");
+        }
+
+        assert_eq!(
+            *records.lock().unwrap(),
+            [
+                "spam",
+                "last message repeated 2 times",
+                "last message repeated 2 times",
+            ]
+        );
+    }
+
+    /// Fails to write the repeat summaries.
+    #[derive(Debug, Default)]
+    struct FailSummaries(Arc<Mutex<Vec<String>>>);
+
+    impl Append for FailSummaries {
+        fn append(&self, record: &Record) -> anyhow::Result<()> {
+            let message = record.args().to_string();
+            if message.starts_with("last message repeated") {
+                anyhow::bail!("disk full");
+            }
+            self.0.lock().unwrap().push(message);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn failed_summary_keeps_record() {
+        let records = Arc::new(Mutex::new(vec![]));
+        let appender = DedupAppender::builder().build(Box::new(FailSummaries(records.clone())));
+
+        log(&appender, "spam");
+        log(&appender, "spam");
+        let err = appender
+            .append(&Record::builder().args(format_args!("other")).build())
+            .unwrap_err();
+        assert_eq!(err.to_string(), "disk full");
+        log(&appender, "again");
+
+        assert_eq!(*records.lock().unwrap(), ["spam", "other", "again"]);
+    }
+
+    #[test]
+    fn window() {
+        let records = Arc::new(Mutex::new(vec![]));
+        let appender = DedupAppender::builder()
+            .window(Duration::ZERO)
+            .build(Box::new(Collect(records.clone())));
+
+        log(&appender, "spam");
+        log(&appender, "spam");
+
+        assert_eq!(
+            *records.lock().unwrap(),
+            ["spam", "last message repeated 1 time"]
+        );
+    }
+
+    #[test]
+    #[cfg(all(
+        feature = "config_parsing",
+        feature = "yaml_format",
+        feature = "console_appender"
+    ))]
+    fn deserialize() {
+        use crate::config::RawConfig;
+
+        let config = "
+appenders:
+    foo:
+        kind: dedup
+        window: 1 minute
+        max_count: 50
+        appender:
+            kind: console
+";
+        let config = ::serde_yaml::from_str::<RawConfig>(config).unwrap();
+        let errors = config.appenders_lossy(&Deserializers::new()).1;
+        assert!(errors.is_empty(), "{:?}", errors);
+
+        let config = "
+appenders:
+    foo:
+        kind: dedup
+        max_count: 0
+        appender:
+            kind: console
+";
+        let config = ::serde_yaml::from_str::<RawConfig>(config).unwrap();
+        let errors = config.appenders_lossy(&Deserializers::new()).1;
+        assert!(
+            errors.to_string().contains("max_count must be at least 1"),
+            "{}",
+            errors
+        );
+    }
+}
@@ -0,0 +1,193 @@
+//! The fallback appender.
+//!
+//! Requires the `fallback_appender` feature.
+
+use log::Record;
+
+#[cfg(feature = "config_parsing")]
+use crate::append::AppenderConfig;
+#[cfg(feature = "config_parsing")]
+use crate::config::{Deserialize, Deserializers};
+
+use crate::append::Append;
+
+/// The fallback appender's configuration.
+#[cfg(feature = "config_parsing")]
+#[derive(Clone, Eq, PartialEq, Hash, Debug, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FallbackAppenderConfig {
+    primary: AppenderConfig,
+    secondary: AppenderConfig,
+}
+
+/// An appender which hands a record to a secondary appender when the primary
+/// one fails to write it.
+///
+/// Each fallback is reported through the error handler. An error is only
+/// returned when both appenders fail.
+#[derive(Debug)]
+pub struct FallbackAppender {
+    primary: Box<dyn Append>,
+    secondary: Box<dyn Append>,
+}
+
+impl Append for FallbackAppender {
+    fn append(&self, record: &Record) -> anyhow::Result<()> {
+        let primary = match self.primary.append(record) {
+            Ok(()) => return Ok(()),
+            Err(e) => e,
+        };
+        crate::handle_error(&anyhow::anyhow!(
+            "falling back to the secondary appender: {}",
+            primary
+        ));
+        self.secondary.append(record).map_err(|secondary| {
+            anyhow::anyhow!("both appenders failed: {}; {}", primary, secondary)
+        })
+    }
+
+    fn flush(&self) {
+        self.primary.flush();
+        self.secondary.flush();
+    }
+}
+
+impl FallbackAppender {
+    /// Creates a new `FallbackAppender` writing to `primary`, and to
+    /// `secondary` when that fails.
+    pub fn new(primary: Box<dyn Append>, secondary: Box<dyn Append>) -> FallbackAppender {
+        FallbackAppender { primary, secondary }
+    }
+}
+
+/// A deserializer for the `FallbackAppender`.
+///
+/// # Configuration
+///
+/// ```yaml
+/// kind: fallback
+///
+/// # The appender records are written to first. Required. Filters should be
+/// # attached to the fallback appender itself.
+/// primary:
+///   kind: tcp
+///   address: 127.0.0.1:5000
+///
+/// # The appender records are written to when the primary fails. Required.
+/// secondary:
+///   kind: file
+///   path: log/spill.log
+/// ```
+#[cfg(feature = "config_parsing")]
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub struct FallbackAppenderDeserializer;
+
+#[cfg(feature = "config_parsing")]
+impl Deserialize for FallbackAppenderDeserializer {
+    type Trait = dyn Append;
+
+    type Config = FallbackAppenderConfig;
+
+    fn deserialize(
+        &self,
+        config: FallbackAppenderConfig,
+        deserializers: &Deserializers,
+    ) -> anyhow::Result<Box<Self::Trait>> {
+        if config.primary.has_filters() || config.secondary.has_filters() {
+            anyhow::bail!(
+                "filters must be attached to the fallback appender, not the ones it wraps"
+            );
+        }
+        let primary = deserializers.deserialize(&config.primary.kind, config.primary.config)?;
+        let secondary =
+            deserializers.deserialize(&config.secondary.kind, config.secondary.config)?;
+        Ok(Box::new(FallbackAppender::new(primary, secondary)))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+    use crate::append::test_util::{Collect, Fail};
+
+    #[test]
+    fn primary_succeeds() {
+        let primary = Arc::new(Mutex::new(vec![]));
+        let secondary = Arc::new(Mutex::new(vec![]));
+        let appender = FallbackAppender::new(
+            Box::new(Collect(primary.clone())),
+            Box::new(Collect(secondary.clone())),
+        );
+
+        appender
+            .append(&Record::builder().args(format_args!("hello")).build())
+            .unwrap();
+
+        assert_eq!(*primary.lock().unwrap(), ["hello"]);
+        assert!(secondary.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn falls_back() {
+        let secondary = Arc::new(Mutex::new(vec![]));
+        let appender = FallbackAppender::new(
+            Box::new(Fail("connection refused")),
+            Box::new(Collect(secondary.clone())),
+        );
+
+        appender
+            .append(&Record::builder().args(format_args!("hello")).build())
+            .unwrap();
+
+        assert_eq!(*secondary.lock().unwrap(), ["hello"]);
+    }
+
+    #[test]
+    fn both_fail() {
+        let appender = FallbackAppender::new(
+            Box::new(Fail("connection refused")),
+            Box::new(Fail("disk full")),
+        );
+
+        let err = appender
+            .append(&Record::builder().args(format_args!("hello")).build())
+            .unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            "both appenders failed: connection refused; disk full"
+        );
+    }
+
+    #[test]
+    #[cfg(all(
+        feature = "config_parsing",
+        feature = "yaml_format",
+        feature = "console_appender",
+        feature = "file_appender"
+    ))]
+    fn deserialize() {
+        use crate::config::RawConfig;
+
+        let dir = tempfile::tempdir().unwrap();
+        let config = format!(
+            "
+appenders:
+    spill:
+        kind: fallback
+        primary:
+            kind: console
+        secondary:
+            kind: file
+            path: {0}/spill.log
+",
+            dir.path().display()
+        );
+
+        let config = ::serde_yaml::from_str::<RawConfig>(&config).unwrap();
+        let errors = config.appenders_lossy(&Deserializers::new()).1;
+        assert!(errors.is_empty(), "{:?}", errors);
+    }
+}
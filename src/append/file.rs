@@ -2,13 +2,19 @@
 //!
 //! Requires the `file_appender` feature.
 
+use chrono::{
+    format::{Fixed, Item, Numeric},
+    DateTime, Datelike, Duration, Local, NaiveDate, NaiveDateTime, TimeZone, Timelike,
+};
 use derivative::Derivative;
-use log::Record;
+use log::{LevelFilter, Record};
 use parking_lot::Mutex;
 use std::{
     fs::{self, File, OpenOptions},
     io::{self, BufWriter, Write},
     path::{Path, PathBuf},
+    str::FromStr,
+    sync::atomic::{AtomicUsize, Ordering},
 };
 
 #[cfg(feature = "config_parsing")]
@@ -18,9 +24,58 @@ use crate::encode::EncoderConfig;
 
 use crate::{
     append::{env_util::expand_env_vars, Append},
-    encode::{pattern::PatternEncoder, writer::simple::SimpleWriter, Encode},
+    encode::{
+        pattern::{parse_date_format, PatternEncoder},
+        writer::simple::SimpleWriter,
+        Encode,
+    },
 };
 
+const DEFAULT_BUFFER_SIZE: usize = 1024;
+
+/// Incremented by `reopen_files`. Each `FileAppender` reopens its file when
+/// this differs from the value it saw when it last opened it.
+static REOPEN_GENERATION: AtomicUsize = AtomicUsize::new(0);
+
+/// Makes every `FileAppender` close and reopen its file before writing its
+/// next record.
+///
+/// This lets an external tool such as `logrotate` move a log file aside, after
+/// which the appender goes on to write to a new file at the original path. The
+/// file is always reopened for appending, even if the appender truncated it
+/// when it was built. If reopening fails, the appender keeps writing to the
+/// file it had open and the error is reported to the error handler.
+///
+/// This only sets a flag, so it may be called from a signal handler.
+pub fn reopen_files() {
+    REOPEN_GENERATION.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Installs a `SIGHUP` handler which calls `reopen_files`, following the
+/// usual contract for Unix daemons whose logs are rotated externally.
+///
+/// This replaces any `SIGHUP` handler already installed. Nothing is installed
+/// unless this is called.
+#[cfg(unix)]
+pub fn reopen_on_sighup() -> io::Result<()> {
+    extern "C" fn handle(_: libc::c_int) {
+        reopen_files();
+    }
+
+    // SAFETY: the action is fully initialized, and the handler only touches
+    // an atomic, which is async-signal-safe.
+    unsafe {
+        let mut action: libc::sigaction = std::mem::zeroed();
+        action.sa_sigaction = handle as extern "C" fn(libc::c_int) as libc::sighandler_t;
+        action.sa_flags = libc::SA_RESTART;
+        libc::sigemptyset(&mut action.sa_mask);
+        if libc::sigaction(libc::SIGHUP, &action, std::ptr::null_mut()) != 0 {
+            return Err(io::Error::last_os_error());
+        }
+    }
+    Ok(())
+}
+
 /// The file appender's configuration.
 #[cfg(feature = "config_parsing")]
 #[derive(Clone, Eq, PartialEq, Hash, Debug, Default, serde::Deserialize)]
@@ -29,27 +84,324 @@ pub struct FileAppenderConfig {
     path: String,
     encoder: Option<EncoderConfig>,
     append: Option<bool>,
+    encoding: Option<String>,
+    create_dirs: Option<bool>,
+    auto_flush: Option<bool>,
+    flush_level: Option<LevelFilter>,
+    buffer_size: Option<usize>,
+    mode: Option<String>,
+    dated_path: Option<bool>,
+}
+
+/// The character encoding a `FileAppender` writes.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub enum Encoding {
+    /// UTF-8 without a byte order mark.
+    #[default]
+    Utf8,
+    /// UTF-8, with a byte order mark at the start of the file.
+    ///
+    /// The mark is only written when the file is empty, so appending to an
+    /// existing log doesn't insert a second one.
+    Utf8Bom,
+    /// ISO-8859-1. Characters outside of it are written as `?`.
+    Latin1,
+}
+
+impl FromStr for Encoding {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Encoding> {
+        match &*s.to_ascii_lowercase() {
+            "utf-8" | "utf8" => Ok(Encoding::Utf8),
+            "utf-8-bom" | "utf8-bom" => Ok(Encoding::Utf8Bom),
+            "latin-1" | "latin1" | "iso-8859-1" => Ok(Encoding::Latin1),
+            _ => Err(anyhow::anyhow!(
+                "unknown encoding `{}`, expected one of `utf-8`, `utf-8-bom` or `latin-1`",
+                s
+            )),
+        }
+    }
+}
+
+fn to_latin1(buf: &[u8]) -> Vec<u8> {
+    String::from_utf8_lossy(buf)
+        .chars()
+        .map(|c| match c {
+            '\0'..='\u{ff}' => c as u8,
+            _ => b'?',
+        })
+        .collect()
 }
 
 /// An appender which logs to a file.
+///
+/// Output is buffered. Unless auto flushing is disabled, the buffer is flushed
+/// after every record, and with it disabled, records at or above the flush
+/// level still are. Either way, whatever is still buffered is written out
+/// when the appender is dropped.
+///
+/// With `dated_path` enabled, the path may contain [`chrono` format
+/// specifiers][chrono] such as `%Y` and `%m`, which are filled in with the
+/// local time. Once the finest of them rolls over, for example because a new
+/// month began, the path is filled in again and the appender moves on to the
+/// new file, creating its directories.
+///
+/// To cooperate with external log rotation, see `reopen_files` and
+/// `reopen_on_sighup`.
+///
+/// [chrono]: https://docs.rs/chrono/latest/chrono/format/strftime/index.html
 #[derive(Derivative)]
 #[derivative(Debug)]
 pub struct FileAppender {
-    path: PathBuf,
     #[derivative(Debug = "ignore")]
-    file: Mutex<SimpleWriter<BufWriter<File>>>,
+    file: Mutex<Output>,
+    #[derivative(Debug = "ignore")]
+    dated_path: Option<DatedPath>,
+    opener: Opener,
     encoder: Box<dyn Encode>,
+    encoding: Encoding,
+    auto_flush: bool,
+    flush_level: LevelFilter,
+}
+
+/// The file currently written to.
+struct Output {
+    path: PathBuf,
+    writer: SimpleWriter<BufWriter<File>>,
+    /// The `REOPEN_GENERATION` when the file was opened.
+    generation: usize,
+    /// When a dated path has to be filled in again. `None` means for every
+    /// record.
+    expires: Option<DateTime<Local>>,
 }
 
 impl Append for FileAppender {
     fn append(&self, record: &Record) -> anyhow::Result<()> {
+        self.append_with_encoder(record, &*self.encoder)
+    }
+
+    fn append_with_encoder(&self, record: &Record, encoder: &dyn Encode) -> anyhow::Result<()> {
         let mut file = self.file.lock();
-        self.encoder.encode(&mut *file, record)?;
-        file.flush()?;
+        let generation = REOPEN_GENERATION.load(Ordering::Relaxed);
+        if let Some(ref dated_path) = self.dated_path {
+            let now = Local::now();
+            if file.expires.map_or(true, |expires| now >= expires) {
+                let (path, expires) = dated_path.format(now);
+                if path == file.path {
+                    file.expires = expires;
+                } else {
+                    let writer = SimpleWriter(self.opener.open(&path)?);
+                    file.writer.flush()?;
+                    *file = Output {
+                        path,
+                        writer,
+                        generation,
+                        expires,
+                    };
+                }
+            }
+        }
+        if file.generation != generation {
+            file.generation = generation;
+            let _ = file.writer.flush();
+            let opener = Opener {
+                append: true,
+                ..self.opener
+            };
+            match opener.open(&file.path) {
+                Ok(writer) => file.writer = SimpleWriter(writer),
+                Err(e) => crate::handle_error(&e.into()),
+            }
+        }
+        let file = &mut file.writer;
+        match self.encoding {
+            Encoding::Utf8 | Encoding::Utf8Bom => encoder.encode(file, record)?,
+            Encoding::Latin1 => {
+                let mut buf = vec![];
+                encoder.encode(&mut SimpleWriter(&mut buf), record)?;
+                file.write_all(&to_latin1(&buf))?;
+            }
+        }
+        if self.auto_flush || record.level() <= self.flush_level {
+            file.flush()?;
+        }
         Ok(())
     }
 
-    fn flush(&self) {}
+    fn flush(&self) {
+        let _ = self.file.lock().writer.flush();
+    }
+}
+
+/// A path containing `chrono` format specifiers.
+struct DatedPath {
+    items: Vec<Item<'static>>,
+    unit: Option<Unit>,
+}
+
+/// A unit of time a path's date specifiers can change by. Ordered from the
+/// finest.
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Debug)]
+enum Unit {
+    Second,
+    Minute,
+    Hour,
+    Day,
+    Month,
+    Year,
+}
+
+impl DatedPath {
+    fn new(path: &str) -> io::Result<DatedPath> {
+        let items = parse_date_format(path).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("invalid date format in path `{}`", path),
+            )
+        })?;
+        // `None`, for specifiers like `%f`, is finer than any unit.
+        let unit = items.iter().try_fold(Unit::Year, |unit, item| {
+            let item_unit = match *item {
+                Item::OwnedLiteral(_) | Item::OwnedSpace(_) => return Some(unit),
+                Item::Numeric(ref numeric, _) => numeric_unit(numeric)?,
+                Item::Fixed(ref fixed) => fixed_unit(fixed)?,
+                _ => return None,
+            };
+            Some(unit.min(item_unit))
+        });
+        Ok(DatedPath { items, unit })
+    }
+
+    /// Returns the path at `now`, and when it has to be filled in again.
+    fn format(&self, now: DateTime<Local>) -> (PathBuf, Option<DateTime<Local>>) {
+        let path = now.format_with_items(self.items.iter()).to_string().into();
+        (path, self.unit.map(|unit| next_boundary(now, unit)))
+    }
+}
+
+fn numeric_unit(numeric: &Numeric) -> Option<Unit> {
+    match *numeric {
+        Numeric::Year
+        | Numeric::YearDiv100
+        | Numeric::YearMod100
+        | Numeric::IsoYear
+        | Numeric::IsoYearDiv100
+        | Numeric::IsoYearMod100 => Some(Unit::Year),
+        Numeric::Month => Some(Unit::Month),
+        Numeric::Day
+        | Numeric::Ordinal
+        | Numeric::WeekFromSun
+        | Numeric::WeekFromMon
+        | Numeric::IsoWeek
+        | Numeric::NumDaysFromSun
+        | Numeric::WeekdayFromMon => Some(Unit::Day),
+        Numeric::Hour | Numeric::Hour12 => Some(Unit::Hour),
+        Numeric::Minute => Some(Unit::Minute),
+        Numeric::Second | Numeric::Timestamp => Some(Unit::Second),
+        _ => None,
+    }
+}
+
+fn fixed_unit(fixed: &Fixed) -> Option<Unit> {
+    match *fixed {
+        Fixed::ShortMonthName | Fixed::LongMonthName => Some(Unit::Month),
+        Fixed::ShortWeekdayName | Fixed::LongWeekdayName => Some(Unit::Day),
+        Fixed::LowerAmPm | Fixed::UpperAmPm => Some(Unit::Hour),
+        // The offset changes with daylight saving time, which not every zone
+        // switches on the hour.
+        Fixed::TimezoneName
+        | Fixed::TimezoneOffset
+        | Fixed::TimezoneOffsetColon
+        | Fixed::TimezoneOffsetColonZ
+        | Fixed::TimezoneOffsetZ => Some(Unit::Minute),
+        _ => None,
+    }
+}
+
+/// Returns the start of the `unit` after the one `now` is in.
+fn next_boundary(now: DateTime<Local>, unit: Unit) -> DateTime<Local> {
+    let local = now.naive_local();
+    let date = local.date();
+    let start = |date: NaiveDate| date.and_hms_opt(0, 0, 0).unwrap();
+    let next: Option<NaiveDateTime> = match unit {
+        Unit::Second => local.with_nanosecond(0).map(|t| t + Duration::seconds(1)),
+        Unit::Minute => date
+            .and_hms_opt(local.hour(), local.minute(), 0)
+            .map(|t| t + Duration::minutes(1)),
+        Unit::Hour => date
+            .and_hms_opt(local.hour(), 0, 0)
+            .map(|t| t + Duration::hours(1)),
+        Unit::Day => date.succ_opt().map(start),
+        Unit::Month => match date.month() {
+            12 => NaiveDate::from_ymd_opt(date.year() + 1, 1, 1),
+            month => NaiveDate::from_ymd_opt(date.year(), month + 1, 1),
+        }
+        .map(start),
+        Unit::Year => NaiveDate::from_ymd_opt(date.year() + 1, 1, 1).map(start),
+    };
+    // The boundary may fall into a gap left by daylight saving time, in which
+    // case the path is checked again a second later.
+    next.and_then(|next| Local.from_local_datetime(&next).earliest())
+        .filter(|next| *next > now)
+        .unwrap_or_else(|| now + Duration::seconds(1))
+}
+
+/// How `FileAppender`s open their files.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+struct Opener {
+    append: bool,
+    encoding: Encoding,
+    create_dirs: bool,
+    buffer_size: usize,
+    #[cfg_attr(not(unix), allow(dead_code))]
+    mode: Option<u32>,
+}
+
+impl Opener {
+    fn open(&self, path: &Path) -> io::Result<BufWriter<File>> {
+        if let Some(parent) = path.parent().filter(|_| self.create_dirs) {
+            fs::create_dir_all(parent).map_err(|e| {
+                io::Error::new(
+                    e.kind(),
+                    format!("error creating directory {}: {}", parent.display(), e),
+                )
+            })?;
+        }
+        let mut options = OpenOptions::new();
+        options
+            .write(true)
+            .append(self.append)
+            .truncate(!self.append)
+            .create(true);
+        #[cfg(unix)]
+        if let Some(mode) = self.mode {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(mode);
+        }
+        let file = options.open(path).map_err(|e| {
+            io::Error::new(e.kind(), format!("error opening {}: {}", path.display(), e))
+        })?;
+        #[cfg(unix)]
+        if let Some(mode) = self.mode {
+            use std::os::unix::fs::PermissionsExt;
+            // The creation mode is masked by the umask and isn't applied to
+            // existing files at all.
+            file.set_permissions(fs::Permissions::from_mode(mode))
+                .map_err(|e| {
+                    io::Error::new(
+                        e.kind(),
+                        format!("error setting permissions of {}: {}", path.display(), e),
+                    )
+                })?;
+        }
+        let mut file = BufWriter::with_capacity(self.buffer_size, file);
+        if self.encoding == Encoding::Utf8Bom && file.get_ref().metadata()?.len() == 0 {
+            file.write_all(b"\xEF\xBB\xBF")?;
+            file.flush()?;
+        }
+        Ok(file)
+    }
 }
 
 impl FileAppender {
@@ -58,6 +410,13 @@ impl FileAppender {
         FileAppenderBuilder {
             encoder: None,
             append: true,
+            encoding: Encoding::Utf8,
+            create_dirs: true,
+            auto_flush: true,
+            flush_level: LevelFilter::Off,
+            buffer_size: DEFAULT_BUFFER_SIZE,
+            mode: None,
+            dated_path: false,
         }
     }
 }
@@ -66,6 +425,14 @@ impl FileAppender {
 pub struct FileAppenderBuilder {
     encoder: Option<Box<dyn Encode>>,
     append: bool,
+    encoding: Encoding,
+    create_dirs: bool,
+    auto_flush: bool,
+    flush_level: LevelFilter,
+    buffer_size: usize,
+    #[cfg_attr(not(unix), allow(dead_code))]
+    mode: Option<u32>,
+    dated_path: bool,
 }
 
 impl FileAppenderBuilder {
@@ -83,30 +450,129 @@ impl FileAppenderBuilder {
         self
     }
 
+    /// Sets the character encoding of the output file.
+    ///
+    /// Defaults to `Encoding::Utf8`.
+    pub fn encoding(mut self, encoding: Encoding) -> FileAppenderBuilder {
+        self.encoding = encoding;
+        self
+    }
+
+    /// Determines if missing parent directories of the output file are
+    /// created.
+    ///
+    /// Defaults to `true`.
+    pub fn create_dirs(mut self, create_dirs: bool) -> FileAppenderBuilder {
+        self.create_dirs = create_dirs;
+        self
+    }
+
+    /// Determines if the output is flushed after every record.
+    ///
+    /// When disabled, records are written once the buffer fills up, when
+    /// `Append::flush` is called, or when the appender is dropped. This is
+    /// considerably faster under load, at the cost of losing the tail of the
+    /// log if the process crashes.
+    ///
+    /// Defaults to `true`.
+    pub fn auto_flush(mut self, auto_flush: bool) -> FileAppenderBuilder {
+        self.auto_flush = auto_flush;
+        self
+    }
+
+    /// Sets the least severe level of record which is flushed as soon as it
+    /// is written, even with auto flushing disabled.
+    ///
+    /// Setting this to `LevelFilter::Error` keeps the throughput of buffered
+    /// writes for routine records while making sure an error reaches the file
+    /// before a crash which may follow it.
+    ///
+    /// Defaults to `LevelFilter::Off`, flushing no records early.
+    pub fn flush_level(mut self, flush_level: LevelFilter) -> FileAppenderBuilder {
+        self.flush_level = flush_level;
+        self
+    }
+
+    /// Sets the capacity of the output buffer, in bytes.
+    ///
+    /// With auto flushing disabled, a larger buffer means fewer writes to the
+    /// file, and so better throughput, but more records lost if the process
+    /// crashes. A size of 0 disables buffering, so every write the encoder
+    /// makes goes straight to the file, whether or not auto flushing is
+    /// enabled.
+    ///
+    /// Defaults to 1024.
+    pub fn buffer_size(mut self, buffer_size: usize) -> FileAppenderBuilder {
+        self.buffer_size = buffer_size;
+        self
+    }
+
+    /// Sets the Unix permission bits of the output file, such as `0o640`.
+    ///
+    /// The mode is applied whether the file is created or already exists.
+    /// Without it, new files get the process's default permissions and
+    /// existing files are left alone. Ignored on other platforms.
+    pub fn mode(mut self, mode: u32) -> FileAppenderBuilder {
+        self.mode = Some(mode);
+        self
+    }
+
+    /// Determines if `chrono` format specifiers in the path are filled in
+    /// with the local time, as described on `FileAppender`.
+    ///
+    /// An invalid specifier then makes `build` fail. When disabled, a `%` in
+    /// the path is just a character.
+    ///
+    /// Defaults to `false`.
+    pub fn dated_path(mut self, dated_path: bool) -> FileAppenderBuilder {
+        self.dated_path = dated_path;
+        self
+    }
+
     /// Consumes the `FileAppenderBuilder`, producing a `FileAppender`.
     /// The path argument can contain environment variables of the form $ENV{name_here},
     /// where 'name_here' will be the name of the environment variable that
     /// will be resolved. Note that if the variable fails to resolve,
     /// $ENV{name_here} will NOT be replaced in the path.
+    /// With `dated_path` enabled, it can also contain `chrono` format
+    /// specifiers such as `%Y`, as described on `FileAppender`.
     pub fn build<P: AsRef<Path>>(self, path: P) -> io::Result<FileAppender> {
         let path_cow = path.as_ref().to_string_lossy();
-        let path: PathBuf = expand_env_vars(path_cow).as_ref().into();
-        if let Some(parent) = path.parent() {
-            fs::create_dir_all(parent)?;
-        }
-        let file = OpenOptions::new()
-            .write(true)
-            .append(self.append)
-            .truncate(!self.append)
-            .create(true)
-            .open(&path)?;
+        let path = expand_env_vars(path_cow);
+        let dated_path = if self.dated_path {
+            Some(DatedPath::new(&path)?)
+        } else {
+            None
+        };
+        let (path, expires) = match dated_path {
+            Some(ref dated_path) => dated_path.format(Local::now()),
+            None => (PathBuf::from(path.as_ref()), None),
+        };
+        let opener = Opener {
+            append: self.append,
+            encoding: self.encoding,
+            create_dirs: self.create_dirs,
+            buffer_size: self.buffer_size,
+            mode: self.mode,
+        };
+        let writer = SimpleWriter(opener.open(&path)?);
+        let generation = REOPEN_GENERATION.load(Ordering::Relaxed);
 
         Ok(FileAppender {
-            path,
-            file: Mutex::new(SimpleWriter(BufWriter::with_capacity(1024, file))),
+            file: Mutex::new(Output {
+                path,
+                writer,
+                generation,
+                expires,
+            }),
+            dated_path,
+            opener,
             encoder: self
                 .encoder
                 .unwrap_or_else(|| Box::<PatternEncoder>::default()),
+            encoding: self.encoding,
+            auto_flush: self.auto_flush,
+            flush_level: self.flush_level,
         })
     }
 }
@@ -125,10 +591,41 @@ impl FileAppenderBuilder {
 /// # $ENV{name_here} will NOT be replaced in the path.
 /// path: log/foo.log
 ///
+/// # Specifies if chrono format specifiers such as %Y in the path are filled
+/// # in with the local time, moving on to a new file when they roll over.
+/// # Defaults to `false`.
+/// dated_path: false
+///
 /// # Specifies if the appender should append to or truncate the log file if it
 /// # already exists. Defaults to `true`.
 /// append: true
 ///
+/// # The character encoding of the log file: `utf-8`, `utf-8-bom` or
+/// # `latin-1`. Defaults to `utf-8`.
+/// encoding: utf-8
+///
+/// # Specifies if missing parent directories of the log file should be
+/// # created. Defaults to `true`.
+/// create_dirs: true
+///
+/// # Specifies if output should be flushed after every record. Disabling this
+/// # improves throughput, but records still buffered when the process crashes
+/// # are lost. Defaults to `true`.
+/// auto_flush: true
+///
+/// # With auto flushing disabled, records at or above this level are still
+/// # flushed as soon as they are written. Defaults to `off`.
+/// flush_level: error
+///
+/// # The capacity of the output buffer, in bytes. 0 disables buffering.
+/// # Defaults to 1024.
+/// buffer_size: 1024
+///
+/// # The Unix permission bits of the log file, in octal. Applied to new and
+/// # existing files alike. Ignored on other platforms. Defaults to leaving
+/// # permissions alone.
+/// mode: "640"
+///
 /// # The encoder to use to format output. Defaults to `kind: pattern`.
 /// encoder:
 ///   kind: pattern
@@ -152,15 +649,45 @@ impl Deserialize for FileAppenderDeserializer {
         if let Some(append) = config.append {
             appender = appender.append(append);
         }
-        if let Some(encoder) = config.encoder {
-            appender = appender.encoder(deserializers.deserialize(&encoder.kind, encoder.config)?);
+        if let Some(auto_flush) = config.auto_flush {
+            appender = appender.auto_flush(auto_flush);
+        }
+        if let Some(flush_level) = config.flush_level {
+            appender = appender.flush_level(flush_level);
+        }
+        if let Some(buffer_size) = config.buffer_size {
+            appender = appender.buffer_size(buffer_size);
+        }
+        if let Some(mode) = config.mode {
+            let digits = mode.strip_prefix("0o").unwrap_or(&mode);
+            let mode = u32::from_str_radix(digits, 8)
+                .map_err(|_| anyhow::anyhow!("invalid file mode `{}`", mode))?;
+            appender = appender.mode(mode);
+        }
+        if let Some(create_dirs) = config.create_dirs {
+            appender = appender.create_dirs(create_dirs);
+        }
+        if let Some(dated_path) = config.dated_path {
+            appender = appender.dated_path(dated_path);
+        }
+        if let Some(encoding) = config.encoding {
+            appender = appender.encoding(encoding.parse()?);
+        }
+        if let Some(encoder) = deserializers.encoder(config.encoder)? {
+            appender = appender.encoder(encoder);
         }
         Ok(Box::new(appender.build(&config.path)?))
     }
+
+    fn supports_encoder_override(&self) -> bool {
+        true
+    }
 }
 
 #[cfg(test)]
 mod test {
+    use log::Level;
+
     use super::*;
 
     #[test]
@@ -172,6 +699,48 @@ mod test {
             .unwrap();
     }
 
+    #[test]
+    fn create_dirs_false() {
+        let tempdir = tempfile::tempdir().unwrap();
+
+        FileAppender::builder()
+            .create_dirs(false)
+            .build(tempdir.path().join("foo").join("bar.log"))
+            .unwrap_err();
+        assert!(!tempdir.path().join("foo").exists());
+    }
+
+    #[test]
+    fn create_dirs_error_names_path() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let blocker = tempdir.path().join("foo");
+        fs::write(&blocker, "").unwrap();
+
+        let err = FileAppender::builder()
+            .build(blocker.join("bar").join("baz.log"))
+            .unwrap_err();
+        let dir = blocker.join("bar");
+        assert!(err.to_string().contains(&*dir.to_string_lossy()), "{}", err);
+    }
+
+    #[test]
+    fn open_error_names_path() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let path = tempdir.path().join("missing").join("foo.log");
+
+        let err = FileAppender::builder()
+            .create_dirs(false)
+            .build(&path)
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+        assert!(
+            err.to_string()
+                .starts_with(&format!("error opening {}: ", path.display())),
+            "{}",
+            err
+        );
+    }
+
     #[test]
     fn append_false() {
         let tempdir = tempfile::tempdir().unwrap();
@@ -180,4 +749,245 @@ mod test {
             .build(tempdir.path().join("foo.log"))
             .unwrap();
     }
+
+    #[test]
+    fn truncates_once_at_open() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let path = tempdir.path().join("foo.log");
+        fs::write(&path, "old\n").unwrap();
+
+        let build = |append| {
+            FileAppender::builder()
+                .encoder(Box::new(PatternEncoder::new("{m}{n}")))
+                .append(append)
+                .build(&path)
+                .unwrap()
+        };
+        let log = |appender: &FileAppender, msg| {
+            appender
+                .append(&Record::builder().args(format_args!("{}", msg)).build())
+                .unwrap()
+        };
+
+        let appender = build(true);
+        log(&appender, "kept");
+        drop(appender);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "old\nkept\n");
+
+        let appender = build(false);
+        log(&appender, "one");
+        log(&appender, "two");
+        assert_eq!(fs::read_to_string(&path).unwrap(), "one\ntwo\n");
+    }
+
+    #[test]
+    fn buffered_until_flush_or_drop() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let path = tempdir.path().join("foo.log");
+        let appender = FileAppender::builder()
+            .encoder(Box::new(PatternEncoder::new("{m}{n}")))
+            .auto_flush(false)
+            .build(&path)
+            .unwrap();
+        let log = |msg| {
+            appender
+                .append(&Record::builder().args(format_args!("{}", msg)).build())
+                .unwrap()
+        };
+
+        log("one");
+        assert_eq!(fs::read_to_string(&path).unwrap(), "");
+        appender.flush();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "one\n");
+
+        log("two");
+        drop(appender);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "one\ntwo\n");
+    }
+
+    #[test]
+    fn flush_level() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let path = tempdir.path().join("foo.log");
+        let appender = FileAppender::builder()
+            .encoder(Box::new(PatternEncoder::new("{m}{n}")))
+            .auto_flush(false)
+            .flush_level(LevelFilter::Warn)
+            .build(&path)
+            .unwrap();
+        let log = |level, msg| {
+            appender
+                .append(
+                    &Record::builder()
+                        .level(level)
+                        .args(format_args!("{}", msg))
+                        .build(),
+                )
+                .unwrap()
+        };
+
+        log(Level::Info, "one");
+        assert_eq!(fs::read_to_string(&path).unwrap(), "");
+        log(Level::Error, "two");
+        assert_eq!(fs::read_to_string(&path).unwrap(), "one\ntwo\n");
+        log(Level::Warn, "three");
+        assert_eq!(fs::read_to_string(&path).unwrap(), "one\ntwo\nthree\n");
+    }
+
+    #[test]
+    fn buffer_size() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let path = tempdir.path().join("small.log");
+        let appender = FileAppender::builder()
+            .encoder(Box::new(PatternEncoder::new("{m}{n}")))
+            .auto_flush(false)
+            .buffer_size(8)
+            .build(&path)
+            .unwrap();
+        for msg in ["a line longer than the buffer", "short"] {
+            appender
+                .append(&Record::builder().args(format_args!("{}", msg)).build())
+                .unwrap();
+        }
+        appender.flush();
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "a line longer than the buffer\nshort\n"
+        );
+
+        let path = tempdir.path().join("unbuffered.log");
+        let appender = FileAppender::builder()
+            .encoder(Box::new(PatternEncoder::new("{m}")))
+            .auto_flush(false)
+            .buffer_size(0)
+            .build(&path)
+            .unwrap();
+        appender
+            .append(&Record::builder().args(format_args!("now")).build())
+            .unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "now");
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn mode() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let tempdir = tempfile::tempdir().unwrap();
+        let path = tempdir.path().join("foo.log");
+        let mode = |path: &Path| fs::metadata(path).unwrap().permissions().mode() & 0o777;
+
+        FileAppender::builder().mode(0o640).build(&path).unwrap();
+        assert_eq!(mode(&path), 0o640);
+
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o604)).unwrap();
+        FileAppender::builder().build(&path).unwrap();
+        assert_eq!(mode(&path), 0o604);
+
+        FileAppender::builder().mode(0o600).build(&path).unwrap();
+        assert_eq!(mode(&path), 0o600);
+    }
+
+    #[test]
+    fn latin1() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let path = tempdir.path().join("foo.log");
+        let appender = FileAppender::builder()
+            .encoder(Box::new(PatternEncoder::new("{m}")))
+            .encoding(Encoding::Latin1)
+            .build(&path)
+            .unwrap();
+        appender
+            .append(&Record::builder().args(format_args!("café €")).build())
+            .unwrap();
+
+        assert_eq!(fs::read(&path).unwrap(), b"caf\xE9 ?");
+    }
+
+    #[test]
+    fn utf8_bom_written_once() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let path = tempdir.path().join("foo.log");
+        for _ in 0..2 {
+            let appender = FileAppender::builder()
+                .encoder(Box::new(PatternEncoder::new("{m}")))
+                .encoding(Encoding::Utf8Bom)
+                .build(&path)
+                .unwrap();
+            appender
+                .append(&Record::builder().args(format_args!("é")).build())
+                .unwrap();
+        }
+
+        assert_eq!(fs::read(&path).unwrap(), "\u{feff}éé".as_bytes());
+    }
+
+    #[test]
+    fn parse_encoding() {
+        assert_eq!("UTF-8".parse::<Encoding>().unwrap(), Encoding::Utf8);
+        assert_eq!("iso-8859-1".parse::<Encoding>().unwrap(), Encoding::Latin1);
+        let err = "ebcdic".parse::<Encoding>().unwrap_err();
+        assert!(err.to_string().contains("`ebcdic`"), "{}", err);
+    }
+
+    #[test]
+    fn dated_path() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let appender = FileAppender::builder()
+            .encoder(Box::new(PatternEncoder::new("{m}\n")))
+            .dated_path(true)
+            .build(tempdir.path().join("%Y").join("%m").join("app.log"))
+            .unwrap();
+        appender
+            .append(&Record::builder().args(format_args!("hello")).build())
+            .unwrap();
+        let (path, expires) = DatedPath::new("%Y/%m/app.log")
+            .unwrap()
+            .format(Local::now());
+        assert_eq!(appender.file.lock().expires, expires);
+        let contents = fs::read_to_string(tempdir.path().join(&path)).unwrap();
+        assert_eq!(contents, "hello\n");
+
+        // Switch to another file as if the month had ended.
+        {
+            let mut file = appender.file.lock();
+            file.path = tempdir.path().join("old.log");
+            file.expires = Some(Local::now());
+        }
+        appender
+            .append(&Record::builder().args(format_args!("again")).build())
+            .unwrap();
+        let contents = fs::read_to_string(tempdir.path().join(path)).unwrap();
+        assert_eq!(contents.lines().collect::<Vec<_>>(), ["hello", "again"]);
+
+        // Without `dated_path`, `%` is kept as is.
+        let path = tempdir.path().join("50%done-%Y.log");
+        FileAppender::builder().build(&path).unwrap();
+        assert!(path.exists());
+
+        let err = FileAppender::builder()
+            .dated_path(true)
+            .build(tempdir.path().join("%Q.log"))
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn dated_path_boundaries() {
+        let unit = |path| DatedPath::new(path).unwrap().unit;
+        assert_eq!(unit("app.log"), Some(Unit::Year));
+        assert_eq!(unit("%Y/%b/%d.log"), Some(Unit::Day));
+        assert_eq!(unit("%Y-%m-%d_%H.log"), Some(Unit::Hour));
+        assert_eq!(unit("%s.log"), Some(Unit::Second));
+        assert_eq!(unit("%Y%f.log"), None);
+
+        let now = Local.with_ymd_and_hms(2023, 12, 31, 13, 5, 7).unwrap();
+        let next = |unit| next_boundary(now, unit).naive_local().to_string();
+        assert_eq!(next(Unit::Second), "2023-12-31 13:05:08");
+        assert_eq!(next(Unit::Minute), "2023-12-31 13:06:00");
+        assert_eq!(next(Unit::Hour), "2023-12-31 14:00:00");
+        assert_eq!(next(Unit::Day), "2024-01-01 00:00:00");
+        assert_eq!(next(Unit::Month), "2024-01-01 00:00:00");
+        assert_eq!(next(Unit::Year), "2024-01-01 00:00:00");
+    }
 }
@@ -0,0 +1,140 @@
+//! The memory appender.
+//!
+//! Requires the `memory_appender` feature.
+
+use derivative::Derivative;
+use log::Record;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+use crate::{
+    append::Append,
+    encode::{pattern::PatternEncoder, writer::simple::SimpleWriter, Encode},
+};
+
+/// An appender which keeps formatted log events in memory.
+///
+/// This is mostly useful in tests. Clones share the same storage, so a clone
+/// can be kept to inspect what was logged after the original has been handed
+/// to a `Config`.
+///
+/// ```
+/// use log::{Level, LevelFilter, Log, Record};
+/// use log4rs::{
+///     append::memory::MemoryAppender,
+///     config::{Appender, Config, Root},
+///     encode::pattern::PatternEncoder,
+/// };
+///
+/// let memory = MemoryAppender::builder()
+///     .encoder(Box::new(PatternEncoder::new("{l} {m}")))
+///     .build();
+/// let config = Config::builder()
+///     .appender(Appender::builder().build("memory", Box::new(memory.clone())))
+///     .build(Root::builder().appender("memory").build(LevelFilter::Info))
+///     .unwrap();
+/// let logger = log4rs::Logger::new(config);
+///
+/// logger.log(&Record::builder().level(Level::Warn).args(format_args!("disk low")).build());
+/// logger.log(&Record::builder().level(Level::Info).args(format_args!("started")).build());
+///
+/// assert_eq!(memory.records(), ["WARN disk low", "INFO started"]);
+/// ```
+#[derive(Clone, Derivative)]
+#[derivative(Debug)]
+pub struct MemoryAppender {
+    #[derivative(Debug = "ignore")]
+    records: Arc<Mutex<Vec<String>>>,
+    encoder: Arc<dyn Encode>,
+}
+
+impl Append for MemoryAppender {
+    fn append(&self, record: &Record) -> anyhow::Result<()> {
+        let mut buf = vec![];
+        self.encoder.encode(&mut SimpleWriter(&mut buf), record)?;
+        self.lock().push(String::from_utf8_lossy(&buf).into_owned());
+        Ok(())
+    }
+}
+
+impl MemoryAppender {
+    /// Creates a new `MemoryAppender` builder.
+    pub fn builder() -> MemoryAppenderBuilder {
+        MemoryAppenderBuilder { encoder: None }
+    }
+
+    /// Returns a copy of the records logged so far, in order.
+    pub fn records(&self) -> Vec<String> {
+        self.lock().clone()
+    }
+
+    /// Discards the records logged so far.
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Vec<String>> {
+        self.records.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// A builder for `MemoryAppender`s.
+pub struct MemoryAppenderBuilder {
+    encoder: Option<Box<dyn Encode>>,
+}
+
+impl MemoryAppenderBuilder {
+    /// Sets the encoder used to format each record.
+    ///
+    /// Defaults to a `PatternEncoder` with the pattern `{m}`.
+    pub fn encoder(mut self, encoder: Box<dyn Encode>) -> MemoryAppenderBuilder {
+        self.encoder = Some(encoder);
+        self
+    }
+
+    /// Consumes the `MemoryAppenderBuilder`, producing a `MemoryAppender`.
+    pub fn build(self) -> MemoryAppender {
+        MemoryAppender {
+            records: Arc::default(),
+            encoder: self
+                .encoder
+                .unwrap_or_else(|| Box::new(PatternEncoder::new("{m}")))
+                .into(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use log::Level;
+
+    use super::*;
+
+    #[test]
+    fn clones_share_records() {
+        let appender = MemoryAppender::builder()
+            .encoder(Box::new(PatternEncoder::new("{l}: {m}")))
+            .build();
+        let handle = appender.clone();
+
+        appender
+            .append(
+                &Record::builder()
+                    .level(Level::Error)
+                    .args(format_args!("one"))
+                    .build(),
+            )
+            .unwrap();
+        appender
+            .append(
+                &Record::builder()
+                    .level(Level::Debug)
+                    .args(format_args!("two"))
+                    .build(),
+            )
+            .unwrap();
+
+        assert_eq!(handle.records(), ["ERROR: one", "DEBUG: two"]);
+        handle.clear();
+        assert!(appender.records().is_empty());
+    }
+}
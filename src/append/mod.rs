@@ -1,5 +1,7 @@
 //! Appenders
 
+#[cfg(feature = "config_parsing")]
+use log::LevelFilter;
 use log::{Log, Record};
 #[cfg(feature = "config_parsing")]
 use serde::{de, Deserialize, Deserializer};
@@ -10,16 +12,37 @@ use std::collections::BTreeMap;
 use std::fmt;
 
 #[cfg(feature = "config_parsing")]
-use crate::config::Deserializable;
+use crate::config::{de_level, Deserializable};
+use crate::encode::Encode;
 #[cfg(feature = "config_parsing")]
 use crate::filter::FilterConfig;
 
+#[cfg(feature = "async_appender")]
+pub mod async_appender;
+#[cfg(feature = "composite_appender")]
+pub mod composite;
 #[cfg(feature = "console_appender")]
 pub mod console;
+#[cfg(feature = "dedup_appender")]
+pub mod dedup;
+#[cfg(feature = "fallback_appender")]
+pub mod fallback;
 #[cfg(feature = "file_appender")]
 pub mod file;
+#[cfg(feature = "memory_appender")]
+pub mod memory;
+#[cfg(feature = "null_appender")]
+pub mod null;
 #[cfg(feature = "rolling_file_appender")]
 pub mod rolling_file;
+#[cfg(feature = "syslog_appender")]
+pub mod syslog;
+#[cfg(feature = "tcp_appender")]
+pub mod tcp;
+#[cfg(feature = "udp_appender")]
+pub mod udp;
+#[cfg(feature = "writer_appender")]
+pub mod writer;
 
 #[cfg(any(feature = "file_appender", feature = "rolling_file_appender"))]
 mod env_util {
@@ -91,8 +114,26 @@ pub trait Append: fmt::Debug + Send + Sync + 'static {
     /// Processes the provided `Record`.
     fn append(&self, record: &Record) -> anyhow::Result<()>;
 
+    /// Processes the provided `Record`, formatting it with `encoder` instead
+    /// of the appender's own encoder.
+    ///
+    /// A logger's `pattern` in a config file is applied through this, so the
+    /// logger shares the appender with everyone else. The console, file and
+    /// rolling file appenders implement it; an appender which does should
+    /// have its deserializer return `true` from
+    /// `Deserialize::supports_encoder_override`. The default implementation
+    /// ignores `encoder` and calls `append`.
+    fn append_with_encoder(&self, record: &Record, encoder: &dyn Encode) -> anyhow::Result<()> {
+        let _ = encoder;
+        self.append(record)
+    }
+
     /// Flushes all in-flight records.
-    fn flush(&self);
+    ///
+    /// Appenders which buffer records, or hand them to a background thread,
+    /// should write them out here. The logger's `flush` and `shutdown` call
+    /// it. The default implementation does nothing.
+    fn flush(&self) {}
 }
 
 #[cfg(feature = "config_parsing")]
@@ -121,10 +162,28 @@ pub struct AppenderConfig {
     pub kind: String,
     /// The filters attached to the appender.
     pub filters: Vec<FilterConfig>,
+    /// The level set by the `level` key, which rejects events below it.
+    pub level: Option<LevelFilter>,
     /// The appender configuration.
     pub config: Value,
 }
 
+#[cfg(all(
+    feature = "config_parsing",
+    any(
+        feature = "async_appender",
+        feature = "composite_appender",
+        feature = "dedup_appender",
+        feature = "fallback_appender"
+    )
+))]
+impl AppenderConfig {
+    /// Returns whether any filter, including a `level`, is attached.
+    pub(crate) fn has_filters(&self) -> bool {
+        !self.filters.is_empty() || self.level.is_some()
+    }
+}
+
 #[cfg(feature = "config_parsing")]
 impl<'de> Deserialize<'de> for AppenderConfig {
     fn deserialize<D>(d: D) -> Result<AppenderConfig, D::Error>
@@ -134,6 +193,8 @@ impl<'de> Deserialize<'de> for AppenderConfig {
         let mut map = BTreeMap::<Value, Value>::deserialize(d)?;
 
         let kind = match map.remove(&Value::String("kind".to_owned())) {
+            // YAML reads an unquoted `kind: null` as a null value
+            Some(Value::Unit) | Some(Value::Option(None)) => "null".to_owned(),
             Some(kind) => kind.deserialize_into().map_err(|e| e.into_error())?,
             None => return Err(de::Error::missing_field("kind")),
         };
@@ -143,14 +204,60 @@ impl<'de> Deserialize<'de> for AppenderConfig {
             None => vec![],
         };
 
+        let level = match map.remove(&Value::String("level".to_owned())) {
+            Some(level) => Some(de_level(level).map_err(|e| e.into_error())?),
+            None => None,
+        };
+
         Ok(AppenderConfig {
             kind,
             filters,
+            level,
             config: Value::Map(map),
         })
     }
 }
 
+/// Appenders used by the tests of the appenders which wrap others.
+#[cfg(all(
+    test,
+    any(
+        feature = "async_appender",
+        feature = "composite_appender",
+        feature = "dedup_appender",
+        feature = "fallback_appender"
+    )
+))]
+pub(crate) mod test_util {
+    use log::Record;
+    use std::sync::{Arc, Mutex};
+
+    use super::Append;
+
+    /// Keeps the message of each record.
+    #[derive(Debug, Default)]
+    pub struct Collect(pub Arc<Mutex<Vec<String>>>);
+
+    impl Append for Collect {
+        fn append(&self, record: &Record) -> anyhow::Result<()> {
+            self.0.lock().unwrap().push(record.args().to_string());
+            Ok(())
+        }
+    }
+
+    /// Fails every record with its message.
+    #[cfg(any(feature = "composite_appender", feature = "fallback_appender"))]
+    #[derive(Debug)]
+    pub struct Fail(pub &'static str);
+
+    #[cfg(any(feature = "composite_appender", feature = "fallback_appender"))]
+    impl Append for Fail {
+        fn append(&self, _: &Record) -> anyhow::Result<()> {
+            Err(anyhow::anyhow!(self.0))
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     #[cfg(any(feature = "file_appender", feature = "rolling_file_appender"))]
@@ -0,0 +1,82 @@
+//! The null appender.
+//!
+//! Requires the `null_appender` feature.
+
+use log::Record;
+
+use crate::append::Append;
+#[cfg(feature = "config_parsing")]
+use crate::config::{Deserialize, Deserializers};
+
+/// The null appender's configuration.
+#[cfg(feature = "config_parsing")]
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Default, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct NullAppenderConfig {
+    #[serde(skip_deserializing)]
+    _p: (),
+}
+
+/// An appender which discards every log event.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub struct NullAppender(());
+
+impl Append for NullAppender {
+    fn append(&self, _: &Record) -> anyhow::Result<()> {
+        Ok(())
+    }
+}
+
+impl NullAppender {
+    /// Returns a new `NullAppender`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+/// A deserializer for the `NullAppender`.
+///
+/// # Configuration
+///
+/// ```yaml
+/// kind: null
+/// ```
+#[cfg(feature = "config_parsing")]
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub struct NullAppenderDeserializer;
+
+#[cfg(feature = "config_parsing")]
+impl Deserialize for NullAppenderDeserializer {
+    type Trait = dyn Append;
+
+    type Config = NullAppenderConfig;
+
+    fn deserialize(
+        &self,
+        _: NullAppenderConfig,
+        _: &Deserializers,
+    ) -> anyhow::Result<Box<dyn Append>> {
+        Ok(Box::<NullAppender>::default())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #[test]
+    #[cfg(all(feature = "config_parsing", feature = "yaml_format"))]
+    fn deserialize() {
+        use crate::config::{Deserializers, RawConfig};
+
+        let config = "
+appenders:
+    bare:
+        kind: null
+    quoted:
+        kind: \"null\"
+";
+        let config = ::serde_yaml::from_str::<RawConfig>(config).unwrap();
+        let (appenders, errors) = config.appenders_lossy(&Deserializers::new());
+        assert!(errors.is_empty(), "{}", errors);
+        assert_eq!(appenders.len(), 2);
+    }
+}
@@ -151,6 +151,10 @@ impl<'a> LogFile<'a> {
 }
 
 /// An appender which archives log files in a configurable strategy.
+///
+/// Writing a record and running the policy happen under a single lock, so a
+/// rollover is atomic with respect to other threads logging to the same
+/// appender: each record is written whole to exactly one file.
 #[derive(Derivative)]
 #[derivative(Debug)]
 pub struct RollingFileAppender {
@@ -164,6 +168,10 @@ pub struct RollingFileAppender {
 
 impl Append for RollingFileAppender {
     fn append(&self, record: &Record) -> anyhow::Result<()> {
+        self.append_with_encoder(record, &*self.encoder)
+    }
+
+    fn append_with_encoder(&self, record: &Record, encoder: &dyn Encode) -> anyhow::Result<()> {
         // TODO(eas): Perhaps this is better as a concurrent queue?
         let mut writer = self.writer.lock();
 
@@ -185,10 +193,10 @@ impl Append for RollingFileAppender {
             self.policy.process(&mut file)?;
 
             let log_writer_new = self.get_writer(&mut writer)?;
-            self.encoder.encode(log_writer_new, record)?;
+            encoder.encode(log_writer_new, record)?;
             log_writer_new.flush()?;
         } else {
-            self.encoder.encode(log_writer, record)?;
+            encoder.encode(log_writer, record)?;
             log_writer.flush()?;
             let len = log_writer.len;
 
@@ -203,8 +211,6 @@ impl Append for RollingFileAppender {
 
         Ok(())
     }
-
-    fn flush(&self) {}
 }
 
 impl RollingFileAppender {
@@ -354,8 +360,7 @@ impl Deserialize for RollingFileAppenderDeserializer {
         if let Some(append) = config.append {
             builder = builder.append(append);
         }
-        if let Some(encoder) = config.encoder {
-            let encoder = deserializers.deserialize(&encoder.kind, encoder.config)?;
+        if let Some(encoder) = deserializers.encoder(config.encoder)? {
             builder = builder.encoder(encoder);
         }
 
@@ -363,6 +368,10 @@ impl Deserialize for RollingFileAppenderDeserializer {
         let appender = builder.build(config.path, policy)?;
         Ok(Box::new(appender))
     }
+
+    fn supports_encoder_override(&self) -> bool {
+        true
+    }
 }
 
 #[cfg(test)]
@@ -474,4 +483,187 @@ appenders:
             .unwrap();
         assert_eq!(contents, b"");
     }
+
+    #[test]
+    #[cfg(all(
+        feature = "compound_policy",
+        feature = "size_trigger",
+        feature = "fixed_window_roller",
+        not(feature = "background_rotation")
+    ))]
+    fn size_rotation() {
+        use crate::append::rolling_file::policy::compound::{
+            roll::fixed_window::FixedWindowRoller, trigger::size::SizeTrigger, CompoundPolicy,
+        };
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("app.log");
+        let roller = FixedWindowRoller::builder()
+            .base(1)
+            .build(&format!("{}/app.log.{{}}", dir.path().display()), 2)
+            .unwrap();
+        let policy = CompoundPolicy::new(Box::new(SizeTrigger::new(10)), Box::new(roller));
+        let appender = RollingFileAppender::builder()
+            .encoder(Box::new(PatternEncoder::new("{m}{n}")))
+            .build(&path, Box::new(policy))
+            .unwrap();
+
+        // A single record larger than the limit is rolled out immediately.
+        for msg in ["a line past the limit", "second", "third", "fourth record"] {
+            appender
+                .append(&Record::builder().args(format_args!("{}", msg)).build())
+                .unwrap();
+        }
+
+        let read = |name: &str| fs::read_to_string(dir.path().join(name)).unwrap();
+        assert!(!path.exists());
+        assert_eq!(read("app.log.1"), "fourth record\n");
+        assert_eq!(read("app.log.2"), "second\nthird\n");
+        assert!(!dir.path().join("app.log.3").exists());
+    }
+
+    #[test]
+    #[cfg(all(
+        feature = "compound_policy",
+        feature = "time_trigger",
+        feature = "fixed_window_roller",
+        not(feature = "background_rotation")
+    ))]
+    fn time_rotation_after_idle() {
+        use std::time::Duration;
+
+        use mock_instant::MockClock;
+
+        use crate::append::rolling_file::policy::compound::{
+            roll::fixed_window::FixedWindowRoller,
+            trigger::time::{TimeTrigger, TimeTriggerConfig, TimeTriggerInterval},
+            CompoundPolicy,
+        };
+
+        let hour = Duration::from_secs(60 * 60);
+        // 1970/1/5 23:00:00
+        MockClock::set_system_time(4 * 24 * hour + 23 * hour);
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("app.log");
+        let trigger = TimeTrigger::new(TimeTriggerConfig {
+            interval: TimeTriggerInterval::Day(1),
+            modulate: false,
+            max_random_delay: 0,
+        });
+        let roller = FixedWindowRoller::builder()
+            .build(&format!("{}/app.log.{{}}", dir.path().display()), 2)
+            .unwrap();
+        let policy = CompoundPolicy::new(Box::new(trigger), Box::new(roller));
+        let appender = RollingFileAppender::builder()
+            .encoder(Box::new(PatternEncoder::new("{m}{n}")))
+            .build(&path, Box::new(policy))
+            .unwrap();
+
+        appender
+            .append(&Record::builder().args(format_args!("day one")).build())
+            .unwrap();
+
+        // Nothing is logged while the day boundary passes; the first record
+        // afterwards must roll the old file before it is written.
+        MockClock::advance_system_time(2 * hour);
+        appender
+            .append(&Record::builder().args(format_args!("day two")).build())
+            .unwrap();
+
+        let read = |name: &str| fs::read_to_string(dir.path().join(name)).unwrap();
+        assert_eq!(read("app.log"), "day two\n");
+        assert_eq!(read("app.log.0"), "day one\n");
+    }
+
+    /// Rolls the log over to numbered files once it reaches a size.
+    #[derive(Debug)]
+    struct NumberedPolicy {
+        limit: u64,
+        rolls: std::sync::atomic::AtomicUsize,
+    }
+
+    impl Policy for NumberedPolicy {
+        fn process(&self, log: &mut LogFile) -> anyhow::Result<()> {
+            if log.len_estimate() >= self.limit {
+                log.roll();
+                let n = self
+                    .rolls
+                    .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                fs::rename(log.path(), log.path().with_extension(n.to_string()))?;
+            }
+            Ok(())
+        }
+        fn is_pre_process(&self) -> bool {
+            false
+        }
+    }
+
+    #[test]
+    fn concurrent_rollover() {
+        use crate::encode::pattern::PatternEncoder;
+        use std::{sync::Arc, thread};
+
+        const THREADS: usize = 8;
+        const LINES: usize = 500;
+
+        let dir = tempfile::tempdir().unwrap();
+        let appender = Arc::new(
+            RollingFileAppender::builder()
+                .encoder(Box::new(PatternEncoder::new("{m}\n")))
+                .build(
+                    dir.path().join("stress.log"),
+                    Box::new(NumberedPolicy {
+                        limit: 4096,
+                        rolls: Default::default(),
+                    }),
+                )
+                .unwrap(),
+        );
+
+        let threads = (0..THREADS)
+            .map(|t| {
+                let appender = appender.clone();
+                thread::spawn(move || {
+                    for i in 0..LINES {
+                        appender
+                            .append(
+                                &Record::builder()
+                                    .args(format_args!(
+                                        "thread {} line {} {}",
+                                        t,
+                                        i,
+                                        "x".repeat(40)
+                                    ))
+                                    .build(),
+                            )
+                            .unwrap();
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+        drop(appender);
+
+        let mut lines = vec![];
+        let mut files = 0;
+        for entry in fs::read_dir(dir.path()).unwrap() {
+            let contents = fs::read_to_string(entry.unwrap().path()).unwrap();
+            assert!(contents.is_empty() || contents.ends_with('\n'));
+            lines.extend(contents.lines().map(str::to_owned));
+            files += 1;
+        }
+        assert!(files > 10, "only {} files", files);
+
+        let mut expected = (0..THREADS)
+            .flat_map(|t| {
+                (0..LINES).map(move |i| format!("thread {} line {} {}", t, i, "x".repeat(40)))
+            })
+            .collect::<Vec<_>>();
+        expected.sort();
+        lines.sort();
+        assert_eq!(lines, expected);
+    }
 }
@@ -0,0 +1,256 @@
+//! The dated roller.
+//!
+//! Requires the `dated_roller` feature.
+
+use anyhow::bail;
+use chrono::{
+    format::{Item, StrftimeItems},
+    DateTime, Local,
+};
+use std::{
+    ffi::OsString,
+    fs::{self, File},
+    io,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use crate::append::{env_util::expand_env_vars, rolling_file::policy::compound::roll::Roll};
+#[cfg(feature = "config_parsing")]
+use crate::config::{Deserialize, Deserializers};
+
+const DEFAULT_DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// Configuration for the dated roller.
+#[cfg(feature = "config_parsing")]
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Default, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DatedRollerConfig {
+    pattern: String,
+    date_format: Option<String>,
+}
+
+/// A roller which archives the log file under a name containing the date it
+/// was last written to, so the active log file keeps a fixed name.
+///
+/// Archived log files are named by substituting all instances of `{}` in the
+/// filename pattern with the file's last modification time, formatted with a
+/// [`chrono` format string][chrono]. For example, if the filename pattern is
+/// `archive/app.{}.log` and the date format is the default `%Y-%m-%d`, the log
+/// file last written to on the 1st of March 2024 is archived as
+/// `archive/app.2024-03-01.log`. If that archive already exists, `.1`, `.2`
+/// and so on are appended to the name until it is unique.
+///
+/// Where the filesystem supports hard links, the archive is linked to the log
+/// file and an empty file is then renamed over the log file's path, so that
+/// path never goes missing, even briefly. Otherwise the log file is moved to
+/// the archive and recreated empty.
+///
+/// Archived files are never deleted by this roller.
+///
+/// [chrono]: https://docs.rs/chrono/latest/chrono/format/strftime/index.html
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub struct DatedRoller {
+    pattern: String,
+    date_format: String,
+}
+
+impl DatedRoller {
+    /// Returns a new builder for the `DatedRoller`.
+    pub fn builder() -> DatedRollerBuilder {
+        DatedRollerBuilder {
+            date_format: DEFAULT_DATE_FORMAT.to_owned(),
+        }
+    }
+
+    fn archive_path(&self, modified: SystemTime) -> PathBuf {
+        let date = DateTime::<Local>::from(modified)
+            .format(&self.date_format)
+            .to_string();
+        PathBuf::from(expand_env_vars(self.pattern.replace("{}", &date)).into_owned())
+    }
+}
+
+impl Roll for DatedRoller {
+    fn roll(&self, file: &Path) -> anyhow::Result<()> {
+        let modified = fs::metadata(file)?
+            .modified()
+            .unwrap_or_else(|_| SystemTime::now());
+        let archive = self.archive_path(modified);
+        if let Some(parent) = archive.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let mut n = 0;
+        loop {
+            let dst = with_suffix(&archive, n);
+            match fs::hard_link(file, &dst) {
+                Ok(()) => return replace_with_empty(file),
+                Err(ref e) if e.kind() == io::ErrorKind::AlreadyExists => n += 1,
+                Err(_) => break,
+            }
+        }
+
+        // hard links aren't supported here, so fall back to a move
+        let dst = (n..)
+            .map(|n| with_suffix(&archive, n))
+            .find(|dst| !dst.exists())
+            .expect("ran out of archive names");
+        if fs::rename(file, &dst).is_err() {
+            // src and dst may be on different mounts
+            fs::copy(file, &dst)?;
+            fs::remove_file(file)?;
+        }
+        File::create(file)?;
+        Ok(())
+    }
+}
+
+fn with_suffix(path: &Path, n: u32) -> PathBuf {
+    if n == 0 {
+        return path.to_owned();
+    }
+    let mut path = OsString::from(path);
+    path.push(format!(".{}", n));
+    path.into()
+}
+
+/// Atomically replaces `file` with an empty one.
+fn replace_with_empty(file: &Path) -> anyhow::Result<()> {
+    let mut name = OsString::from(".");
+    name.push(file.file_name().unwrap_or_default());
+    name.push(".new");
+    let temp = file.with_file_name(name);
+
+    File::create(&temp)?;
+    if let Err(e) = fs::rename(&temp, file) {
+        let _ = fs::remove_file(&temp);
+        // the archive holds the contents, so the log file can go
+        fs::remove_file(file).map_err(|_| e)?;
+    }
+    Ok(())
+}
+
+/// A builder for the `DatedRoller`.
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub struct DatedRollerBuilder {
+    date_format: String,
+}
+
+impl DatedRollerBuilder {
+    /// Sets the `chrono` format string used to render dates in archive names.
+    ///
+    /// Defaults to `%Y-%m-%d`.
+    pub fn date_format(mut self, date_format: &str) -> DatedRollerBuilder {
+        self.date_format = date_format.to_owned();
+        self
+    }
+
+    /// Constructs a new `DatedRoller`.
+    ///
+    /// `pattern` is either an absolute path or lacking a leading `/`, relative
+    /// to the `cwd` of your application. The pattern must contain at least one
+    /// instance of `{}`, all of which will be replaced with the archived log
+    /// file's date.
+    pub fn build(self, pattern: &str) -> anyhow::Result<DatedRoller> {
+        if !pattern.contains("{}") {
+            // Hide {} in this error message from the formatting machinery in bail macro
+            let msg = "pattern does not contain `{}`";
+            bail!(msg);
+        }
+
+        if StrftimeItems::new(&self.date_format).any(|item| item == Item::Error) {
+            bail!("invalid date format `{}`", self.date_format);
+        }
+
+        Ok(DatedRoller {
+            pattern: pattern.to_owned(),
+            date_format: self.date_format,
+        })
+    }
+}
+
+/// A deserializer for the `DatedRoller`.
+///
+/// # Configuration
+///
+/// ```yaml
+/// kind: dated
+///
+/// # The filename pattern for archived logs. This is either an absolute path or if lacking a leading `/`,
+/// # relative to the `cwd` of your application. The pattern must contain at least one
+/// # instance of `{}`, all of which will be replaced with the date the log file was last written to.
+/// # Required.
+/// pattern: archive/app.{}.log
+///
+/// # The chrono format string used to render the date. Defaults to `%Y-%m-%d`.
+/// date_format: "%Y-%m-%d_%H"
+/// ```
+#[cfg(feature = "config_parsing")]
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub struct DatedRollerDeserializer;
+
+#[cfg(feature = "config_parsing")]
+impl Deserialize for DatedRollerDeserializer {
+    type Trait = dyn Roll;
+
+    type Config = DatedRollerConfig;
+
+    fn deserialize(
+        &self,
+        config: DatedRollerConfig,
+        _: &Deserializers,
+    ) -> anyhow::Result<Box<dyn Roll>> {
+        let mut builder = DatedRoller::builder();
+        if let Some(date_format) = config.date_format {
+            builder = builder.date_format(&date_format);
+        }
+
+        Ok(Box::new(builder.build(&config.pattern)?))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Write;
+
+    use super::*;
+
+    #[test]
+    fn rotation() {
+        let dir = tempfile::tempdir().unwrap();
+        let roller = DatedRoller::builder()
+            .date_format("%Y")
+            .build(&format!("{}/archive/app.{{}}.log", dir.path().display()))
+            .unwrap();
+        let year = Local::now().format("%Y").to_string();
+
+        let file = dir.path().join("app.log");
+        for contents in ["one", "two"] {
+            File::create(&file)
+                .unwrap()
+                .write_all(contents.as_bytes())
+                .unwrap();
+            roller.roll(&file).unwrap();
+            assert_eq!(fs::read_to_string(&file).unwrap(), "");
+        }
+
+        let archive = dir.path().join("archive");
+        let read = |name: String| fs::read_to_string(archive.join(name)).unwrap();
+        assert_eq!(read(format!("app.{}.log", year)), "one");
+        assert_eq!(read(format!("app.{}.log.1", year)), "two");
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 2);
+    }
+
+    #[test]
+    fn invalid() {
+        let err = DatedRoller::builder().build("app.log").unwrap_err();
+        assert_eq!(err.to_string(), "pattern does not contain `{}`");
+
+        let err = DatedRoller::builder()
+            .date_format("%Q")
+            .build("app.{}.log")
+            .unwrap_err();
+        assert_eq!(err.to_string(), "invalid date format `%Q`");
+    }
+}
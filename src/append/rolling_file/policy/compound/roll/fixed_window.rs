@@ -451,7 +451,7 @@ mod test {
         wait_for_roller(&roller);
         assert!(!file.exists());
         contents.clear();
-        File::open(dir.path().join(&format!("{}.{}", fname, base + 1)))
+        File::open(dir.path().join(format!("{}.{}", fname, base + 1)))
             .unwrap()
             .read_to_end(&mut contents)
             .unwrap();
@@ -560,6 +560,40 @@ mod test {
         assert_eq!(contents, actual);
     }
 
+    #[test]
+    #[cfg(feature = "gzip")]
+    fn gzip_pruning() {
+        use flate2::read::GzDecoder;
+
+        let dir = tempfile::tempdir().unwrap();
+        let roller = FixedWindowRoller::builder()
+            .build(&format!("{}/foo.log.{{}}.gz", dir.path().display()), 2)
+            .unwrap();
+
+        let file = dir.path().join("foo.log");
+        for contents in ["file1", "file2", "file3"] {
+            File::create(&file)
+                .unwrap()
+                .write_all(contents.as_bytes())
+                .unwrap();
+            roller.roll(&file).unwrap();
+            wait_for_roller(&roller);
+        }
+
+        let read = |name: &str| {
+            let mut contents = String::new();
+            GzDecoder::new(File::open(dir.path().join(name)).unwrap())
+                .read_to_string(&mut contents)
+                .unwrap();
+            contents
+        };
+        assert!(!file.exists());
+        assert_eq!(read("foo.log.0.gz"), "file3");
+        assert_eq!(read("foo.log.1.gz"), "file2");
+        assert!(!dir.path().join("foo.log.2.gz").exists());
+        assert!(!dir.path().join("foo.log.0").exists());
+    }
+
     #[test]
     fn roll_with_env_var() {
         std::env::set_var("LOG_DIR", "test_log_dir");
@@ -5,6 +5,8 @@ use std::{fmt, path::Path};
 #[cfg(feature = "config_parsing")]
 use crate::config::Deserializable;
 
+#[cfg(feature = "dated_roller")]
+pub mod dated;
 #[cfg(feature = "delete_roller")]
 pub mod delete;
 #[cfg(feature = "fixed_window_roller")]
@@ -18,7 +20,7 @@ pub trait Roll: fmt::Debug + Send + Sync + 'static {
     /// been closed.
     ///
     /// If this method returns successfully, there *must* no longer be a file
-    /// at the specified location.
+    /// at the specified location, or it must be empty.
     fn roll(&self, file: &Path) -> anyhow::Result<()>;
 }
 
@@ -23,20 +23,26 @@ use crate::config::{Deserialize, Deserializers};
 #[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Default, serde::Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct TimeTriggerConfig {
-    interval: TimeTriggerInterval,
+    /// The length of each period.
+    pub interval: TimeTriggerInterval,
+    /// Align each roll to the start of the interval unit.
     #[serde(default)]
-    modulate: bool,
+    pub modulate: bool,
+    /// An upper bound, in seconds, on a random delay added to each roll.
     #[serde(default)]
-    max_random_delay: u64,
+    pub max_random_delay: u64,
 }
 
 #[cfg(not(feature = "config_parsing"))]
 /// Configuration for the time trigger.
 #[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Default)]
 pub struct TimeTriggerConfig {
-    interval: TimeTriggerInterval,
-    modulate: bool,
-    max_random_delay: u64,
+    /// The length of each period.
+    pub interval: TimeTriggerInterval,
+    /// Align each roll to the start of the interval unit.
+    pub modulate: bool,
+    /// An upper bound, in seconds, on a random delay added to each roll.
+    pub max_random_delay: u64,
 }
 
 /// A trigger which rolls the log once it has passed a certain time.
@@ -177,6 +183,7 @@ impl TimeTrigger {
     /// specified time.
     pub fn new(config: TimeTriggerConfig) -> TimeTrigger {
         #[cfg(test)]
+        #[allow(deprecated)]
         let current = {
             let now: std::time::Duration = SystemTime::now()
                 .duration_since(UNIX_EPOCH)
@@ -279,6 +286,7 @@ impl TimeTrigger {
 impl Trigger for TimeTrigger {
     fn trigger(&self, _file: &LogFile) -> anyhow::Result<bool> {
         #[cfg(test)]
+        #[allow(deprecated)]
         let current = {
             let now = SystemTime::now()
                 .duration_since(UNIX_EPOCH)
@@ -338,6 +346,7 @@ impl Deserialize for TimeTriggerDeserializer {
 }
 
 #[cfg(test)]
+#[allow(clippy::useless_vec, clippy::needless_borrow, clippy::useless_format)]
 mod test {
     use super::*;
     use mock_instant::MockClock;
@@ -0,0 +1,631 @@
+//! The syslog appender.
+//!
+//! Requires the `syslog_appender` feature.
+
+use chrono::Local;
+use derivative::Derivative;
+use log::{Level, Record};
+use parking_lot::Mutex;
+#[cfg(unix)]
+use std::os::unix::net::UnixDatagram;
+use std::{
+    io::{self, Write},
+    net::{Ipv4Addr, Ipv6Addr, SocketAddr, ToSocketAddrs, UdpSocket},
+    process,
+};
+
+#[cfg(feature = "config_parsing")]
+use crate::config::{Deserialize, Deserializers};
+#[cfg(feature = "config_parsing")]
+use crate::encode::EncoderConfig;
+
+use crate::{
+    append::Append,
+    encode::{pattern::PatternEncoder, writer::simple::SimpleWriter, Encode},
+};
+
+/// The syslog appender's configuration.
+#[cfg(feature = "config_parsing")]
+#[derive(Clone, Eq, PartialEq, Hash, Debug, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SyslogAppenderConfig {
+    address: String,
+    protocol: Option<Protocol>,
+    facility: Option<Facility>,
+    format: Option<Format>,
+    hostname: Option<String>,
+    app_name: Option<String>,
+    structured_data: Option<StructuredDataConfig>,
+    encoder: Option<EncoderConfig>,
+}
+
+/// The configuration of an RFC 5424 structured data element.
+#[cfg(feature = "config_parsing")]
+#[derive(Clone, Eq, PartialEq, Hash, Debug, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct StructuredDataConfig {
+    id: String,
+    keys: Vec<String>,
+}
+
+/// A syslog facility.
+#[allow(missing_docs)]
+#[cfg_attr(feature = "config_parsing", derive(serde::Deserialize))]
+#[cfg_attr(feature = "config_parsing", serde(rename_all = "lowercase"))]
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum Facility {
+    Kern = 0,
+    User = 1,
+    Mail = 2,
+    Daemon = 3,
+    Auth = 4,
+    Syslog = 5,
+    Lpr = 6,
+    News = 7,
+    Uucp = 8,
+    Cron = 9,
+    AuthPriv = 10,
+    Ftp = 11,
+    Local0 = 16,
+    Local1 = 17,
+    Local2 = 18,
+    Local3 = 19,
+    Local4 = 20,
+    Local5 = 21,
+    Local6 = 22,
+    Local7 = 23,
+}
+
+/// The syslog message format.
+#[cfg_attr(feature = "config_parsing", derive(serde::Deserialize))]
+#[cfg_attr(feature = "config_parsing", serde(rename_all = "lowercase"))]
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum Format {
+    /// The BSD syslog format described in RFC 3164.
+    Rfc3164,
+    /// The format described in RFC 5424.
+    Rfc5424,
+}
+
+/// The transport used to reach the syslog daemon.
+#[cfg_attr(feature = "config_parsing", derive(serde::Deserialize))]
+#[cfg_attr(feature = "config_parsing", serde(rename_all = "lowercase"))]
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum Protocol {
+    /// Datagrams sent to a `host:port` address.
+    Udp,
+    /// Datagrams sent to a local socket such as `/dev/log`.
+    #[cfg(unix)]
+    Unix,
+}
+
+enum Socket {
+    Udp(UdpSocket),
+    #[cfg(unix)]
+    Unix(UnixDatagram),
+}
+
+impl Socket {
+    fn connect(protocol: Protocol, address: &str) -> io::Result<Socket> {
+        match protocol {
+            Protocol::Udp => {
+                let remote = address.to_socket_addrs()?.next().ok_or_else(|| {
+                    io::Error::new(io::ErrorKind::InvalidInput, "address resolved to nothing")
+                })?;
+                let local: SocketAddr = match remote {
+                    SocketAddr::V4(_) => (Ipv4Addr::UNSPECIFIED, 0).into(),
+                    SocketAddr::V6(_) => (Ipv6Addr::UNSPECIFIED, 0).into(),
+                };
+                let socket = UdpSocket::bind(local)?;
+                socket.connect(remote)?;
+                Ok(Socket::Udp(socket))
+            }
+            #[cfg(unix)]
+            Protocol::Unix => {
+                let socket = UnixDatagram::unbound()?;
+                socket.connect(address)?;
+                Ok(Socket::Unix(socket))
+            }
+        }
+    }
+
+    fn send(&self, buf: &[u8]) -> io::Result<usize> {
+        match *self {
+            Socket::Udp(ref socket) => socket.send(buf),
+            #[cfg(unix)]
+            Socket::Unix(ref socket) => socket.send(buf),
+        }
+    }
+}
+
+/// An RFC 5424 structured data element filled in from MDC values.
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+struct StructuredData {
+    id: String,
+    keys: Vec<String>,
+}
+
+impl StructuredData {
+    /// Writes the element, or the nil value `-` if none of the keys are set.
+    fn write(&self, buf: &mut Vec<u8>) -> io::Result<()> {
+        let mut params = vec![];
+        for key in &self.keys {
+            log_mdc::get(key, |value| {
+                if let Some(value) = value {
+                    params.push((key, escape_param_value(value)));
+                }
+            });
+        }
+        if params.is_empty() {
+            return buf.write_all(b"-");
+        }
+        write!(buf, "[{}", self.id)?;
+        for (key, value) in params {
+            write!(buf, " {}=\"{}\"", key, value)?;
+        }
+        buf.write_all(b"]")
+    }
+}
+
+/// Escapes the characters RFC 5424 requires escaping in a parameter value.
+fn escape_param_value(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if let '"' | '\\' | ']' = c {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Returns whether `name` is valid as an SD-ID or parameter name: 1 to 32
+/// printable ASCII characters other than `=`, space, `]` and `"`.
+fn valid_sd_name(name: &str) -> bool {
+    (1..=32).contains(&name.len())
+        && name
+            .bytes()
+            .all(|b| b.is_ascii_graphic() && !matches!(b, b'=' | b']' | b'"'))
+}
+
+fn severity(level: Level) -> u8 {
+    match level {
+        Level::Error => 3,
+        Level::Warn => 4,
+        Level::Info => 6,
+        Level::Debug | Level::Trace => 7,
+    }
+}
+
+/// An appender which sends log events to a syslog daemon.
+///
+/// Each record is sent as a single datagram. If sending fails, the appender
+/// reconnects once and retries before reporting the error.
+#[derive(Derivative)]
+#[derivative(Debug)]
+pub struct SyslogAppender {
+    address: String,
+    protocol: Protocol,
+    facility: Facility,
+    format: Format,
+    hostname: Option<String>,
+    app_name: String,
+    structured_data: Option<StructuredData>,
+    #[derivative(Debug = "ignore")]
+    socket: Mutex<Option<Socket>>,
+    encoder: Box<dyn Encode>,
+}
+
+impl Append for SyslogAppender {
+    fn append(&self, record: &Record) -> anyhow::Result<()> {
+        let message = self.message(record)?;
+
+        let mut socket = self.socket.lock();
+        if let Some(ref s) = *socket {
+            if s.send(&message).is_ok() {
+                return Ok(());
+            }
+        }
+        *socket = None;
+        let s = Socket::connect(self.protocol, &self.address)?;
+        s.send(&message)?;
+        *socket = Some(s);
+        Ok(())
+    }
+}
+
+impl SyslogAppender {
+    /// Creates a new `SyslogAppender` builder.
+    pub fn builder() -> SyslogAppenderBuilder {
+        SyslogAppenderBuilder {
+            protocol: Protocol::Udp,
+            facility: Facility::User,
+            format: Format::Rfc3164,
+            hostname: None,
+            app_name: None,
+            structured_data: None,
+            encoder: None,
+        }
+    }
+
+    fn message(&self, record: &Record) -> anyhow::Result<Vec<u8>> {
+        let pri = (self.facility as u8) * 8 + severity(record.level());
+        let mut buf = vec![];
+        match self.format {
+            Format::Rfc3164 => {
+                write!(buf, "<{}>{} ", pri, Local::now().format("%b %e %H:%M:%S"))?;
+                if let Some(ref hostname) = self.hostname {
+                    write!(buf, "{} ", hostname)?;
+                }
+                write!(buf, "{}[{}]: ", self.app_name, process::id())?;
+            }
+            Format::Rfc5424 => {
+                write!(
+                    buf,
+                    "<{}>1 {} {} {} {} - ",
+                    pri,
+                    Local::now().format("%Y-%m-%dT%H:%M:%S%.6f%:z"),
+                    self.hostname.as_deref().unwrap_or("-"),
+                    self.app_name,
+                    process::id()
+                )?;
+                match self.structured_data {
+                    Some(ref structured_data) => structured_data.write(&mut buf)?,
+                    None => buf.write_all(b"-")?,
+                }
+                buf.write_all(b" ")?;
+            }
+        }
+        self.encoder.encode(&mut SimpleWriter(&mut buf), record)?;
+        Ok(buf)
+    }
+}
+
+/// A builder for `SyslogAppender`s.
+pub struct SyslogAppenderBuilder {
+    protocol: Protocol,
+    facility: Facility,
+    format: Format,
+    hostname: Option<String>,
+    app_name: Option<String>,
+    structured_data: Option<StructuredData>,
+    encoder: Option<Box<dyn Encode>>,
+}
+
+impl SyslogAppenderBuilder {
+    /// Sets the transport used to reach the daemon.
+    ///
+    /// Defaults to `Protocol::Udp`.
+    pub fn protocol(mut self, protocol: Protocol) -> SyslogAppenderBuilder {
+        self.protocol = protocol;
+        self
+    }
+
+    /// Sets the facility messages are logged under.
+    ///
+    /// Defaults to `Facility::User`.
+    pub fn facility(mut self, facility: Facility) -> SyslogAppenderBuilder {
+        self.facility = facility;
+        self
+    }
+
+    /// Sets the message format.
+    ///
+    /// Defaults to `Format::Rfc3164`.
+    pub fn format(mut self, format: Format) -> SyslogAppenderBuilder {
+        self.format = format;
+        self
+    }
+
+    /// Sets the hostname reported in each message.
+    ///
+    /// If unset, RFC 3164 messages omit the hostname and RFC 5424 messages use
+    /// the nil value `-`, leaving it to the daemon to fill in.
+    pub fn hostname(mut self, hostname: &str) -> SyslogAppenderBuilder {
+        self.hostname = Some(hostname.to_owned());
+        self
+    }
+
+    /// Sets the application name reported in each message.
+    ///
+    /// Defaults to the file name of the current executable.
+    pub fn app_name(mut self, app_name: &str) -> SyslogAppenderBuilder {
+        self.app_name = Some(app_name.to_owned());
+        self
+    }
+
+    /// Adds an RFC 5424 structured data element with the SD-ID `id`, such as
+    /// `request@32473`, holding the MDC values of `keys`.
+    ///
+    /// Keys without an MDC value on the logging thread are left out, and if
+    /// none have one, the message has no structured data. The element is
+    /// only written in the `Format::Rfc5424` format.
+    pub fn structured_data(mut self, id: &str, keys: &[&str]) -> SyslogAppenderBuilder {
+        self.structured_data = Some(StructuredData {
+            id: id.to_owned(),
+            keys: keys.iter().map(|&key| key.to_owned()).collect(),
+        });
+        self
+    }
+
+    /// Sets the encoder used to format the message part of each record.
+    ///
+    /// Defaults to a `PatternEncoder` with the pattern `{m}`.
+    pub fn encoder(mut self, encoder: Box<dyn Encode>) -> SyslogAppenderBuilder {
+        self.encoder = Some(encoder);
+        self
+    }
+
+    /// Consumes the `SyslogAppenderBuilder`, producing a `SyslogAppender`
+    /// connected to `address`.
+    ///
+    /// The address is a `host:port` pair for `Protocol::Udp` and a socket path
+    /// for `Protocol::Unix`. It is an error for the structured data's SD-ID
+    /// or keys not to be valid RFC 5424 names.
+    pub fn build(self, address: &str) -> io::Result<SyslogAppender> {
+        if let Some(ref structured_data) = self.structured_data {
+            let names = Some(&structured_data.id).into_iter();
+            if let Some(name) = names
+                .chain(&structured_data.keys)
+                .find(|name| !valid_sd_name(name))
+            {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("invalid structured data name `{}`", name),
+                ));
+            }
+        }
+        let socket = Socket::connect(self.protocol, address)?;
+        let app_name = self.app_name.unwrap_or_else(|| {
+            std::env::current_exe()
+                .ok()
+                .and_then(|p| p.file_name().map(|n| n.to_string_lossy().into_owned()))
+                .unwrap_or_else(|| "-".to_owned())
+        });
+
+        Ok(SyslogAppender {
+            address: address.to_owned(),
+            protocol: self.protocol,
+            facility: self.facility,
+            format: self.format,
+            hostname: self.hostname,
+            app_name,
+            structured_data: self.structured_data,
+            socket: Mutex::new(Some(socket)),
+            encoder: self
+                .encoder
+                .unwrap_or_else(|| Box::new(PatternEncoder::new("{m}"))),
+        })
+    }
+}
+
+/// A deserializer for the `SyslogAppender`.
+///
+/// # Configuration
+///
+/// ```yaml
+/// kind: syslog
+///
+/// # The address of the syslog daemon. Required.
+/// address: 127.0.0.1:514
+///
+/// # The transport, either `udp` for a `host:port` address or `unix` for a
+/// # local socket path like `/dev/log`. Defaults to `udp`.
+/// protocol: udp
+///
+/// # The facility to log under: `kern`, `user`, `mail`, `daemon`, `auth`,
+/// # `syslog`, `lpr`, `news`, `uucp`, `cron`, `authpriv`, `ftp`, or `local0`
+/// # through `local7`. Defaults to `user`.
+/// facility: user
+///
+/// # The message format, either `rfc3164` or `rfc5424`. Defaults to `rfc3164`.
+/// format: rfc3164
+///
+/// # The hostname to report. Defaults to letting the daemon fill it in.
+/// hostname: my-host
+///
+/// # The application name to report. Defaults to the executable's file name.
+/// app_name: my-app
+///
+/// # An RFC 5424 structured data element holding the MDC values of `keys`,
+/// # which are left out when unset; with none set, no element is written.
+/// # Only used by the `rfc5424` format. Defaults to none.
+/// structured_data:
+///   id: request@32473
+///   keys: [request_id, user]
+///
+/// # The encoder used to format the message part. Defaults to `kind: pattern`
+/// # with the pattern `{m}`.
+/// encoder:
+///   kind: pattern
+///   pattern: "{m}"
+/// ```
+#[cfg(feature = "config_parsing")]
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub struct SyslogAppenderDeserializer;
+
+#[cfg(feature = "config_parsing")]
+impl Deserialize for SyslogAppenderDeserializer {
+    type Trait = dyn Append;
+
+    type Config = SyslogAppenderConfig;
+
+    fn deserialize(
+        &self,
+        config: SyslogAppenderConfig,
+        deserializers: &Deserializers,
+    ) -> anyhow::Result<Box<Self::Trait>> {
+        let mut appender = SyslogAppender::builder();
+        if let Some(protocol) = config.protocol {
+            appender = appender.protocol(protocol);
+        }
+        if let Some(facility) = config.facility {
+            appender = appender.facility(facility);
+        }
+        if let Some(format) = config.format {
+            appender = appender.format(format);
+        }
+        if let Some(hostname) = config.hostname {
+            appender = appender.hostname(&hostname);
+        }
+        if let Some(app_name) = config.app_name {
+            appender = appender.app_name(&app_name);
+        }
+        if let Some(structured_data) = config.structured_data {
+            let keys = structured_data
+                .keys
+                .iter()
+                .map(String::as_str)
+                .collect::<Vec<_>>();
+            appender = appender.structured_data(&structured_data.id, &keys);
+        }
+        // not `Deserializers::encoder`, since the default pattern would repeat
+        // the header's fields in the message
+        if let Some(encoder) = config.encoder {
+            appender = appender.encoder(deserializers.deserialize(&encoder.kind, encoder.config)?);
+        }
+        Ok(Box::new(appender.build(&config.address)?))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn record(level: Level) -> Record<'static> {
+        Record::builder()
+            .level(level)
+            .args(format_args!("hello"))
+            .build()
+    }
+
+    #[test]
+    fn rfc5424_over_udp() {
+        let server = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let appender = SyslogAppender::builder()
+            .facility(Facility::Local0)
+            .format(Format::Rfc5424)
+            .hostname("host")
+            .app_name("app")
+            .build(&server.local_addr().unwrap().to_string())
+            .unwrap();
+
+        appender.append(&record(Level::Warn)).unwrap();
+
+        let mut buf = [0; 1024];
+        let len = server.recv(&mut buf).unwrap();
+        let msg = String::from_utf8_lossy(&buf[..len]);
+        assert!(msg.starts_with("<132>1 "), "{}", msg);
+        assert!(
+            msg.ends_with(&format!(" host app {} - - hello", process::id())),
+            "{}",
+            msg
+        );
+    }
+
+    #[test]
+    fn structured_data() {
+        let server = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let address = server.local_addr().unwrap().to_string();
+        let appender = SyslogAppender::builder()
+            .format(Format::Rfc5424)
+            .app_name("app")
+            .structured_data("request@32473", &["request_id", "user"])
+            .build(&address)
+            .unwrap();
+        let mut buf = [0; 1024];
+        let mut recv = || {
+            appender.append(&record(Level::Info)).unwrap();
+            let len = server.recv(&mut buf).unwrap();
+            String::from_utf8_lossy(&buf[..len]).into_owned()
+        };
+
+        let msg = recv();
+        assert!(
+            msg.ends_with(&format!(" app {} - - hello", process::id())),
+            "{}",
+            msg
+        );
+
+        log_mdc::insert("request_id", "5f2c");
+        let msg = recv();
+        assert!(
+            msg.ends_with(" - [request@32473 request_id=\"5f2c\"] hello"),
+            "{}",
+            msg
+        );
+
+        log_mdc::insert("user", "a \"b\" [c]");
+        let msg = recv();
+        assert!(
+            msg.ends_with(r#" - [request@32473 request_id="5f2c" user="a \"b\" [c\]"] hello"#),
+            "{}",
+            msg
+        );
+        log_mdc::clear();
+
+        let err = SyslogAppender::builder()
+            .structured_data("request id", &[])
+            .build(&address)
+            .unwrap_err();
+        assert_eq!(err.to_string(), "invalid structured data name `request id`");
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn rfc3164_reconnects() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("log.sock");
+        let server = UnixDatagram::bind(&path).unwrap();
+        let appender = SyslogAppender::builder()
+            .protocol(Protocol::Unix)
+            .app_name("app")
+            .build(path.to_str().unwrap())
+            .unwrap();
+
+        appender.append(&record(Level::Error)).unwrap();
+        let mut buf = [0; 1024];
+        let len = server.recv(&mut buf).unwrap();
+        let msg = String::from_utf8_lossy(&buf[..len]);
+        assert!(msg.starts_with("<11>"), "{}", msg);
+        assert!(
+            msg.ends_with(&format!(" app[{}]: hello", process::id())),
+            "{}",
+            msg
+        );
+
+        // Simulate the daemon restarting on the same path.
+        drop(server);
+        std::fs::remove_file(&path).unwrap();
+        let server = UnixDatagram::bind(&path).unwrap();
+
+        appender.append(&record(Level::Info)).unwrap();
+        let len = server.recv(&mut buf).unwrap();
+        assert!(buf[..len].starts_with(b"<14>"));
+    }
+
+    #[test]
+    #[cfg(all(feature = "config_parsing", feature = "yaml_format"))]
+    fn deserialize() {
+        use crate::config::RawConfig;
+
+        let server = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let config = format!(
+            "
+appenders:
+    syslog:
+        kind: syslog
+        address: {}
+        facility: local7
+        format: rfc5424
+        encoder:
+            pattern: '{{t}}: {{m}}'
+",
+            server.local_addr().unwrap()
+        );
+
+        let config = ::serde_yaml::from_str::<RawConfig>(&config).unwrap();
+        let errors = config.appenders_lossy(&Deserializers::new()).1;
+        assert!(errors.is_empty(), "{:?}", errors);
+    }
+}
@@ -0,0 +1,362 @@
+//! The TCP appender.
+//!
+//! Requires the `tcp_appender` feature.
+
+use derivative::Derivative;
+use log::Record;
+use parking_lot::Mutex;
+use std::{
+    cmp,
+    io::{self, Write},
+    net::{SocketAddr, TcpStream, ToSocketAddrs},
+    time::{Duration, Instant},
+};
+
+#[cfg(feature = "config_parsing")]
+use crate::config::{de_duration, Deserialize, Deserializers};
+#[cfg(feature = "config_parsing")]
+use crate::encode::EncoderConfig;
+
+use crate::{
+    append::Append,
+    encode::{pattern::PatternEncoder, writer::simple::SimpleWriter, Encode},
+};
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+const DEFAULT_MAX_BACKOFF: Duration = Duration::from_secs(30);
+const INITIAL_BACKOFF: Duration = Duration::from_millis(100);
+
+/// The TCP appender's configuration.
+#[cfg(feature = "config_parsing")]
+#[derive(Clone, Eq, PartialEq, Hash, Debug, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TcpAppenderConfig {
+    address: String,
+    #[serde(deserialize_with = "de_duration", default)]
+    timeout: Option<Duration>,
+    #[serde(deserialize_with = "de_duration", default)]
+    max_backoff: Option<Duration>,
+    encoder: Option<EncoderConfig>,
+}
+
+struct Connection {
+    stream: Option<TcpStream>,
+    backoff: Duration,
+    retry_at: Option<Instant>,
+}
+
+/// An appender which writes log events to a TCP connection.
+///
+/// If the connection drops, the appender reconnects once before giving up on
+/// the record. After a failed reconnect, records are rejected without
+/// touching the network until an exponentially growing backoff has elapsed,
+/// so a dead peer costs the logging thread at most one timeout per retry.
+///
+/// Delivery is at least once. A record whose write fails partway through is
+/// sent again in full on the new connection, so the peer may see the start
+/// of it on the old connection followed by the whole record on the new one.
+#[derive(Derivative)]
+#[derivative(Debug)]
+pub struct TcpAppender {
+    address: String,
+    timeout: Duration,
+    max_backoff: Duration,
+    #[derivative(Debug = "ignore")]
+    connection: Mutex<Connection>,
+    encoder: Box<dyn Encode>,
+}
+
+impl Append for TcpAppender {
+    fn append(&self, record: &Record) -> anyhow::Result<()> {
+        let mut buf = vec![];
+        self.encoder.encode(&mut SimpleWriter(&mut buf), record)?;
+
+        let mut conn = self.connection.lock();
+        if let Some(ref mut stream) = conn.stream {
+            if stream.write_all(&buf).is_ok() {
+                return Ok(());
+            }
+            conn.stream = None;
+        } else if let Some(retry_at) = conn.retry_at {
+            if Instant::now() < retry_at {
+                anyhow::bail!("not connected to {}", self.address);
+            }
+        }
+
+        match self
+            .connect()
+            .and_then(|mut s| s.write_all(&buf).map(|()| s))
+        {
+            Ok(stream) => {
+                conn.stream = Some(stream);
+                conn.backoff = INITIAL_BACKOFF;
+                conn.retry_at = None;
+                Ok(())
+            }
+            Err(e) => {
+                conn.retry_at = Some(Instant::now() + conn.backoff);
+                conn.backoff = cmp::min(conn.backoff * 2, self.max_backoff);
+                Err(anyhow::anyhow!("error writing to {}: {}", self.address, e))
+            }
+        }
+    }
+
+    fn flush(&self) {
+        if let Some(ref mut stream) = self.connection.lock().stream {
+            let _ = stream.flush();
+        }
+    }
+}
+
+impl TcpAppender {
+    /// Creates a new `TcpAppender` builder.
+    pub fn builder() -> TcpAppenderBuilder {
+        TcpAppenderBuilder {
+            timeout: DEFAULT_TIMEOUT,
+            max_backoff: DEFAULT_MAX_BACKOFF,
+            encoder: None,
+        }
+    }
+
+    fn connect(&self) -> io::Result<TcpStream> {
+        connect(&self.address, self.timeout)
+    }
+}
+
+fn connect(address: &str, timeout: Duration) -> io::Result<TcpStream> {
+    let mut last_err = None;
+    for addr in address.to_socket_addrs()? {
+        match connect_addr(&addr, timeout) {
+            Ok(stream) => return Ok(stream),
+            Err(e) => last_err = Some(e),
+        }
+    }
+    Err(last_err.unwrap_or_else(|| {
+        io::Error::new(io::ErrorKind::InvalidInput, "address resolved to nothing")
+    }))
+}
+
+fn connect_addr(addr: &SocketAddr, timeout: Duration) -> io::Result<TcpStream> {
+    let stream = TcpStream::connect_timeout(addr, timeout)?;
+    stream.set_write_timeout(Some(timeout))?;
+    stream.set_nodelay(true)?;
+    Ok(stream)
+}
+
+/// A builder for `TcpAppender`s.
+pub struct TcpAppenderBuilder {
+    timeout: Duration,
+    max_backoff: Duration,
+    encoder: Option<Box<dyn Encode>>,
+}
+
+impl TcpAppenderBuilder {
+    /// Sets the timeout for connecting and for writing a single record.
+    ///
+    /// Defaults to 5 seconds. A zero timeout, which sockets don't accept,
+    /// makes `build` fail.
+    pub fn timeout(mut self, timeout: Duration) -> TcpAppenderBuilder {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Sets the longest the appender waits between reconnect attempts.
+    ///
+    /// Defaults to 30 seconds.
+    pub fn max_backoff(mut self, max_backoff: Duration) -> TcpAppenderBuilder {
+        self.max_backoff = max_backoff;
+        self
+    }
+
+    /// Sets the output encoder for the `TcpAppender`.
+    pub fn encoder(mut self, encoder: Box<dyn Encode>) -> TcpAppenderBuilder {
+        self.encoder = Some(encoder);
+        self
+    }
+
+    /// Consumes the `TcpAppenderBuilder`, producing a `TcpAppender` for the
+    /// `host:port` address.
+    ///
+    /// The initial connection is made lazily, so an unreachable peer does not
+    /// prevent the appender from being built.
+    pub fn build(self, address: &str) -> io::Result<TcpAppender> {
+        if self.timeout == Duration::ZERO {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "timeout must not be zero",
+            ));
+        }
+        Ok(TcpAppender {
+            address: address.to_owned(),
+            timeout: self.timeout,
+            max_backoff: self.max_backoff,
+            connection: Mutex::new(Connection {
+                stream: None,
+                backoff: INITIAL_BACKOFF,
+                retry_at: None,
+            }),
+            encoder: self
+                .encoder
+                .unwrap_or_else(|| Box::<PatternEncoder>::default()),
+        })
+    }
+}
+
+/// A deserializer for the `TcpAppender`.
+///
+/// # Configuration
+///
+/// ```yaml
+/// kind: tcp
+///
+/// # The host and port to connect to. Required.
+/// address: 127.0.0.1:5000
+///
+/// # The timeout for connecting and for writing a single record. Must not be
+/// # zero. Defaults to 5 seconds.
+/// timeout: 5 seconds
+///
+/// # The longest wait between reconnect attempts while the peer is
+/// # unreachable. Defaults to 30 seconds.
+/// max_backoff: 30 seconds
+///
+/// # The encoder to use to format output. Defaults to `kind: pattern`.
+/// encoder:
+///   kind: pattern
+/// ```
+#[cfg(feature = "config_parsing")]
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub struct TcpAppenderDeserializer;
+
+#[cfg(feature = "config_parsing")]
+impl Deserialize for TcpAppenderDeserializer {
+    type Trait = dyn Append;
+
+    type Config = TcpAppenderConfig;
+
+    fn deserialize(
+        &self,
+        config: TcpAppenderConfig,
+        deserializers: &Deserializers,
+    ) -> anyhow::Result<Box<Self::Trait>> {
+        let mut appender = TcpAppender::builder();
+        if let Some(timeout) = config.timeout {
+            appender = appender.timeout(timeout);
+        }
+        if let Some(max_backoff) = config.max_backoff {
+            appender = appender.max_backoff(max_backoff);
+        }
+        if let Some(encoder) = deserializers.encoder(config.encoder)? {
+            appender = appender.encoder(encoder);
+        }
+        Ok(Box::new(appender.build(&config.address)?))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        io::{BufRead, BufReader},
+        net::TcpListener,
+    };
+
+    use super::*;
+
+    #[test]
+    fn writes_and_reconnects() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let appender = TcpAppender::builder()
+            .encoder(Box::new(PatternEncoder::new("{m}{n}")))
+            .build(&address)
+            .unwrap();
+        let append =
+            |msg: &str| appender.append(&Record::builder().args(format_args!("{}", msg)).build());
+
+        append("first").unwrap();
+        let (stream, _) = listener.accept().unwrap();
+        let mut lines = BufReader::new(stream).lines();
+        assert_eq!(lines.next().unwrap().unwrap(), "first");
+
+        // Once the peer hangs up, the next successful write goes to a new
+        // connection. The first write after the hang-up may still be accepted
+        // by the kernel, so keep writing until a reconnect happens.
+        drop(lines);
+        let mut reconnected = None;
+        for _ in 0..100 {
+            append("second").unwrap();
+            listener.set_nonblocking(true).unwrap();
+            if let Ok((stream, _)) = listener.accept() {
+                reconnected = Some(stream);
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        let stream = reconnected.expect("appender never reconnected");
+        stream.set_nonblocking(false).unwrap();
+        let mut lines = BufReader::new(stream).lines();
+        assert_eq!(lines.next().unwrap().unwrap(), "second");
+    }
+
+    #[test]
+    fn backs_off_when_unreachable() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        drop(listener);
+
+        let appender = TcpAppender::builder()
+            .timeout(Duration::from_millis(100))
+            .build(&address)
+            .unwrap();
+        let record = Record::builder().args(format_args!("lost")).build();
+
+        let err = appender.append(&record).unwrap_err();
+        assert!(err.to_string().starts_with("error writing to"), "{}", err);
+        // Within the backoff window the network isn't touched at all.
+        let err = appender.append(&record).unwrap_err();
+        assert_eq!(err.to_string(), format!("not connected to {}", address));
+    }
+
+    #[test]
+    #[cfg(all(feature = "config_parsing", feature = "yaml_format"))]
+    fn deserialize() {
+        use crate::config::RawConfig;
+
+        let config = "
+appenders:
+    tcp:
+        kind: tcp
+        address: 127.0.0.1:5000
+        timeout: 500ms
+        max_backoff: 1 minute
+";
+        let config = ::serde_yaml::from_str::<RawConfig>(config).unwrap();
+        let errors = config.appenders_lossy(&Deserializers::new()).1;
+        assert!(errors.is_empty(), "{:?}", errors);
+
+        let config = "
+appenders:
+    tcp:
+        kind: tcp
+        address: 127.0.0.1:5000
+        timeout: 0 seconds
+";
+        let config = ::serde_yaml::from_str::<RawConfig>(config).unwrap();
+        let errors = config.appenders_lossy(&Deserializers::new()).1;
+        assert!(
+            errors.to_string().contains("timeout must not be zero"),
+            "{}",
+            errors
+        );
+    }
+
+    #[test]
+    fn zero_timeout() {
+        let err = TcpAppender::builder()
+            .timeout(Duration::ZERO)
+            .build("127.0.0.1:5000")
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        assert_eq!(err.to_string(), "timeout must not be zero");
+    }
+}
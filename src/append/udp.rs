@@ -0,0 +1,226 @@
+//! The UDP appender.
+//!
+//! Requires the `udp_appender` feature.
+
+use derivative::Derivative;
+use log::Record;
+use std::{
+    io,
+    net::{Ipv4Addr, Ipv6Addr, SocketAddr, ToSocketAddrs, UdpSocket},
+};
+
+#[cfg(feature = "config_parsing")]
+use crate::config::{Deserialize, Deserializers};
+#[cfg(feature = "config_parsing")]
+use crate::encode::EncoderConfig;
+
+use crate::{
+    append::Append,
+    encode::{pattern::PatternEncoder, writer::simple::SimpleWriter, Encode},
+};
+
+/// The largest payload of a UDP datagram over IPv4.
+const DEFAULT_MAX_SIZE: usize = 65_507;
+
+/// The UDP appender's configuration.
+#[cfg(feature = "config_parsing")]
+#[derive(Clone, Eq, PartialEq, Hash, Debug, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct UdpAppenderConfig {
+    address: String,
+    max_size: Option<usize>,
+    encoder: Option<EncoderConfig>,
+}
+
+/// An appender which sends each log event as a single UDP datagram.
+///
+/// Records longer than the maximum datagram size are truncated, backing off
+/// to the start of a UTF-8 character so the datagram stays valid text. Delivery
+/// is not confirmed. A failed send is reported to the error handler, and the
+/// appender keeps sending later records.
+#[derive(Derivative)]
+#[derivative(Debug)]
+pub struct UdpAppender {
+    #[derivative(Debug = "ignore")]
+    socket: UdpSocket,
+    remote: SocketAddr,
+    max_size: usize,
+    encoder: Box<dyn Encode>,
+}
+
+impl Append for UdpAppender {
+    fn append(&self, record: &Record) -> anyhow::Result<()> {
+        let mut buf = vec![];
+        self.encoder.encode(&mut SimpleWriter(&mut buf), record)?;
+        buf.truncate(truncation_point(&buf, self.max_size));
+        self.socket.send_to(&buf, self.remote)?;
+        Ok(())
+    }
+}
+
+impl UdpAppender {
+    /// Creates a new `UdpAppender` builder.
+    pub fn builder() -> UdpAppenderBuilder {
+        UdpAppenderBuilder {
+            max_size: DEFAULT_MAX_SIZE,
+            encoder: None,
+        }
+    }
+}
+
+/// Returns the length `buf` should be cut to so it fits in `max_size` bytes
+/// without splitting a UTF-8 character.
+fn truncation_point(buf: &[u8], max_size: usize) -> usize {
+    if buf.len() <= max_size {
+        return buf.len();
+    }
+    let mut end = max_size;
+    while end > 0 && buf[end] & 0xC0 == 0x80 {
+        end -= 1;
+    }
+    end
+}
+
+/// A builder for `UdpAppender`s.
+pub struct UdpAppenderBuilder {
+    max_size: usize,
+    encoder: Option<Box<dyn Encode>>,
+}
+
+impl UdpAppenderBuilder {
+    /// Sets the largest datagram sent, in bytes. Longer records are truncated.
+    ///
+    /// Defaults to 65507, the largest UDP payload over IPv4.
+    pub fn max_size(mut self, max_size: usize) -> UdpAppenderBuilder {
+        self.max_size = max_size;
+        self
+    }
+
+    /// Sets the output encoder for the `UdpAppender`.
+    pub fn encoder(mut self, encoder: Box<dyn Encode>) -> UdpAppenderBuilder {
+        self.encoder = Some(encoder);
+        self
+    }
+
+    /// Consumes the `UdpAppenderBuilder`, producing a `UdpAppender` which
+    /// sends to the `host:port` address.
+    pub fn build(self, address: &str) -> io::Result<UdpAppender> {
+        let remote = address.to_socket_addrs()?.next().ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, "address resolved to nothing")
+        })?;
+        let local: SocketAddr = match remote {
+            SocketAddr::V4(_) => (Ipv4Addr::UNSPECIFIED, 0).into(),
+            SocketAddr::V6(_) => (Ipv6Addr::UNSPECIFIED, 0).into(),
+        };
+
+        Ok(UdpAppender {
+            socket: UdpSocket::bind(local)?,
+            remote,
+            max_size: self.max_size,
+            encoder: self
+                .encoder
+                .unwrap_or_else(|| Box::<PatternEncoder>::default()),
+        })
+    }
+}
+
+/// A deserializer for the `UdpAppender`.
+///
+/// # Configuration
+///
+/// ```yaml
+/// kind: udp
+///
+/// # The host and port to send datagrams to. Required.
+/// address: 127.0.0.1:5000
+///
+/// # The largest datagram sent, in bytes. Longer records are truncated.
+/// # Defaults to 65507.
+/// max_size: 1400
+///
+/// # The encoder to use to format output. Defaults to `kind: pattern`.
+/// encoder:
+///   kind: pattern
+/// ```
+#[cfg(feature = "config_parsing")]
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub struct UdpAppenderDeserializer;
+
+#[cfg(feature = "config_parsing")]
+impl Deserialize for UdpAppenderDeserializer {
+    type Trait = dyn Append;
+
+    type Config = UdpAppenderConfig;
+
+    fn deserialize(
+        &self,
+        config: UdpAppenderConfig,
+        deserializers: &Deserializers,
+    ) -> anyhow::Result<Box<Self::Trait>> {
+        let mut appender = UdpAppender::builder();
+        if let Some(max_size) = config.max_size {
+            appender = appender.max_size(max_size);
+        }
+        if let Some(encoder) = deserializers.encoder(config.encoder)? {
+            appender = appender.encoder(encoder);
+        }
+        Ok(Box::new(appender.build(&config.address)?))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn sends_datagrams() {
+        let server = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let appender = UdpAppender::builder()
+            .encoder(Box::new(PatternEncoder::new("{l} {m}")))
+            .build(&server.local_addr().unwrap().to_string())
+            .unwrap();
+
+        appender
+            .append(&Record::builder().args(format_args!("hello")).build())
+            .unwrap();
+
+        let mut buf = [0; 64];
+        let len = server.recv(&mut buf).unwrap();
+        assert_eq!(&buf[..len], b"INFO hello");
+    }
+
+    #[test]
+    fn truncates_oversized_records() {
+        let server = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let appender = UdpAppender::builder()
+            .encoder(Box::new(PatternEncoder::new("{m}")))
+            .max_size(5)
+            .build(&server.local_addr().unwrap().to_string())
+            .unwrap();
+
+        appender
+            .append(&Record::builder().args(format_args!("abcdéf")).build())
+            .unwrap();
+
+        let mut buf = [0; 64];
+        let len = server.recv(&mut buf).unwrap();
+        assert_eq!(&buf[..len], b"abcd");
+    }
+
+    #[test]
+    #[cfg(all(feature = "config_parsing", feature = "yaml_format"))]
+    fn deserialize() {
+        use crate::config::RawConfig;
+
+        let config = "
+appenders:
+    udp:
+        kind: udp
+        address: 127.0.0.1:5000
+        max_size: 1400
+";
+        let config = ::serde_yaml::from_str::<RawConfig>(config).unwrap();
+        let errors = config.appenders_lossy(&Deserializers::new()).1;
+        assert!(errors.is_empty(), "{:?}", errors);
+    }
+}
@@ -0,0 +1,141 @@
+//! The writer appender.
+//!
+//! Requires the `writer_appender` feature.
+
+use derivative::Derivative;
+use log::Record;
+use std::{
+    io::{self, Write},
+    sync::{Mutex, PoisonError},
+};
+
+use crate::{
+    append::Append,
+    encode::{pattern::PatternEncoder, writer::simple::SimpleWriter, Encode},
+};
+
+/// An appender which logs to any `io::Write`r, such as a pipe into an
+/// embedding application's own console.
+///
+/// The writer is not flushed after each record, so one which buffers output
+/// only writes it out when the appender is flushed or dropped.
+///
+/// ```
+/// use log4rs::{append::writer::WriterAppender, encode::pattern::PatternEncoder};
+///
+/// let appender = WriterAppender::builder()
+///     .encoder(Box::new(PatternEncoder::new("{l} - {m}{n}")))
+///     .build(Box::new(std::io::sink()));
+/// ```
+#[derive(Derivative)]
+#[derivative(Debug)]
+pub struct WriterAppender {
+    #[derivative(Debug = "ignore")]
+    writer: Mutex<SimpleWriter<Box<dyn Write + Send>>>,
+    encoder: Box<dyn Encode>,
+}
+
+impl Append for WriterAppender {
+    fn append(&self, record: &Record) -> anyhow::Result<()> {
+        let mut writer = self.writer.lock().unwrap_or_else(PoisonError::into_inner);
+        self.encoder.encode(&mut *writer, record)?;
+        Ok(())
+    }
+
+    fn flush(&self) {
+        if let Err(e) = self.flush_writer() {
+            crate::handle_error(&e.into());
+        }
+    }
+}
+
+impl Drop for WriterAppender {
+    fn drop(&mut self) {
+        self.flush();
+    }
+}
+
+impl WriterAppender {
+    /// Creates a new `WriterAppender` builder.
+    pub fn builder() -> WriterAppenderBuilder {
+        WriterAppenderBuilder { encoder: None }
+    }
+
+    fn flush_writer(&self) -> io::Result<()> {
+        self.writer
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .flush()
+    }
+}
+
+/// A builder for `WriterAppender`s.
+pub struct WriterAppenderBuilder {
+    encoder: Option<Box<dyn Encode>>,
+}
+
+impl WriterAppenderBuilder {
+    /// Sets the output encoder for the `WriterAppender`.
+    pub fn encoder(mut self, encoder: Box<dyn Encode>) -> WriterAppenderBuilder {
+        self.encoder = Some(encoder);
+        self
+    }
+
+    /// Consumes the `WriterAppenderBuilder`, producing a `WriterAppender`
+    /// which logs to `writer`.
+    pub fn build(self, writer: Box<dyn Write + Send>) -> WriterAppender {
+        WriterAppender {
+            writer: Mutex::new(SimpleWriter(writer)),
+            encoder: self
+                .encoder
+                .unwrap_or_else(|| Box::<PatternEncoder>::default()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use super::*;
+
+    /// Keeps what was written apart from what was flushed.
+    #[derive(Clone, Default)]
+    struct Sink {
+        pending: Arc<Mutex<Vec<u8>>>,
+        flushed: Arc<Mutex<Vec<u8>>>,
+    }
+
+    impl Write for Sink {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.pending.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            let pending = std::mem::take(&mut *self.pending.lock().unwrap());
+            self.flushed.lock().unwrap().extend(pending);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn flushes_on_drop() {
+        let sink = Sink::default();
+        let appender = WriterAppender::builder()
+            .encoder(Box::new(PatternEncoder::new("{l} {m};")))
+            .build(Box::new(sink.clone()));
+
+        appender
+            .append(&Record::builder().args(format_args!("one")).build())
+            .unwrap();
+        appender.flush();
+        appender
+            .append(&Record::builder().args(format_args!("two")).build())
+            .unwrap();
+        assert_eq!(*sink.flushed.lock().unwrap(), b"INFO one;");
+
+        drop(appender);
+        assert_eq!(*sink.flushed.lock().unwrap(), b"INFO one;INFO two;");
+    }
+}
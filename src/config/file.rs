@@ -1,13 +1,14 @@
 use std::{
-    fs,
+    env, error, fmt, fs,
     path::{Path, PathBuf},
     thread,
     time::{Duration, SystemTime},
 };
 
+use serde::de::DeserializeOwned;
 use thiserror::Error;
 
-use super::{init_config, Config, Deserializers, Handle, RawConfig};
+use super::{init_config, raw::RawConfigSections, Config, Deserializers, Handle, RawConfig};
 use crate::handle_error;
 
 /// Initializes the global logger as a log4rs logger configured via a file.
@@ -28,23 +29,24 @@ where
     let source = read_config(&path)?;
     // An Err here could come because mtime isn't available, so don't bail
     let modified = fs::metadata(&path).and_then(|m| m.modified()).ok();
-    let config = format.parse(&source)?;
+    let (config, includes) = parse_tracking_includes(&format, &path, &source)?;
 
     let refresh_rate = config.refresh_rate();
-    let config = deserialize(&config, &deserializers);
+    let config = deserialize(config, &deserializers);
 
     match init_config(config) {
         Ok(handle) => {
             if let Some(refresh_rate) = refresh_rate {
-                ConfigReloader::start(
+                ConfigReloader {
                     path,
                     format,
-                    refresh_rate,
                     source,
                     modified,
+                    includes,
                     deserializers,
                     handle,
-                );
+                }
+                .start(refresh_rate);
             }
             Ok(())
         }
@@ -52,6 +54,41 @@ where
     }
 }
 
+/// Initializes the global logger as a log4rs logger configured via a string,
+/// such as one embedded in the binary with `include_str!`.
+///
+/// `format` names the format of `source` as a file extension would: `yaml`,
+/// `yml`, `json` or `toml`. Each requires its Cargo feature. Files named in an
+/// `include` are resolved relative to the current directory.
+///
+/// A string can't change, so a `refresh_rate` in the configuration is ignored,
+/// with a warning. Any nonfatal errors encountered when processing the
+/// configuration are reported to the error handler, as with `init_file`.
+///
+/// ```no_run
+/// # #[cfg(feature = "yaml_format")]
+/// # fn f() {
+/// let config = "
+/// appenders:
+///   stdout:
+///     kind: console
+/// root:
+///   level: info
+///   appenders: [stdout]
+/// ";
+/// log4rs::config::init_string(config, "yaml", Default::default()).unwrap();
+/// # }
+/// ```
+pub fn init_string(source: &str, format: &str, deserializers: Deserializers) -> anyhow::Result<()> {
+    let format = Format::from_extension(Some(format))?;
+    let config = parse_with_includes(&format, Path::new(""), source)?;
+    if config.refresh_rate().is_some() {
+        deserializers.warn("refresh_rate is ignored for a configuration read from a string");
+    }
+    init_config(deserialize(config, &deserializers))?;
+    Ok(())
+}
+
 /// Loads a log4rs logger configuration from a file.
 ///
 /// Unlike `init_file`, this function does not initialize the logger; it only
@@ -63,9 +100,30 @@ where
     let path = path.as_ref();
     let format = Format::from_path(path)?;
     let source = read_config(path)?;
-    let config = format.parse(&source)?;
+    let config = parse_with_includes(&format, path, &source)?;
 
-    Ok(deserialize(&config, &deserializers))
+    Ok(deserialize(config, &deserializers))
+}
+
+/// Loads a log4rs logger configuration layered from several files.
+///
+/// The files are merged in order with `RawConfig::merge`, so a later file
+/// replaces the appenders and loggers of the same name in earlier ones, and
+/// its root if it has one. Each file's format is determined by its
+/// extension, so formats may be mixed.
+pub fn load_config_files<P>(paths: &[P], deserializers: Deserializers) -> anyhow::Result<Config>
+where
+    P: AsRef<Path>,
+{
+    let mut merged = RawConfig::default();
+    for path in paths {
+        let path = path.as_ref();
+        let format = Format::from_path(path)?;
+        let source = read_config(path)?;
+        merged.merge(parse_with_includes(&format, path, &source)?);
+    }
+
+    Ok(deserialize(merged, &deserializers))
 }
 
 /// The various types of formatting errors that can be generated.
@@ -92,6 +150,49 @@ pub enum FormatError {
     UnknownFormat,
 }
 
+/// The errors of a configuration which can't be parsed, when there is more
+/// than one.
+///
+/// Displays as the first error, which has the position of the first problem,
+/// followed by every problem found in the root, appenders and loggers,
+/// separated by semicolons.
+#[derive(Debug)]
+pub struct ParseErrors {
+    first: anyhow::Error,
+    errors: Vec<anyhow::Error>,
+}
+
+impl ParseErrors {
+    /// Returns the error with the position of the first problem.
+    pub fn first(&self) -> &anyhow::Error {
+        &self.first
+    }
+
+    /// Returns every problem found in the root, appenders and loggers.
+    pub fn errors(&self) -> &[anyhow::Error] {
+        &self.errors
+    }
+}
+
+impl fmt::Display for ParseErrors {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "{}; all errors: ", self.first)?;
+        for (i, error) in self.errors.iter().enumerate() {
+            if i > 0 {
+                fmt.write_str("; ")?;
+            }
+            write!(fmt, "{}", error)?;
+        }
+        Ok(())
+    }
+}
+
+impl error::Error for ParseErrors {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        Some(self.first.as_ref())
+    }
+}
+
 #[derive(Debug)]
 enum Format {
     #[cfg(feature = "yaml_format")]
@@ -104,7 +205,11 @@ enum Format {
 
 impl Format {
     fn from_path(path: &Path) -> anyhow::Result<Format> {
-        match path.extension().and_then(|s| s.to_str()) {
+        Format::from_extension(path.extension().and_then(|s| s.to_str()))
+    }
+
+    fn from_extension(extension: Option<&str>) -> anyhow::Result<Format> {
+        match extension {
             #[cfg(feature = "yaml_format")]
             Some("yaml") | Some("yml") => Ok(Format::Yaml),
             #[cfg(not(feature = "yaml_format"))]
@@ -125,8 +230,33 @@ impl Format {
         }
     }
 
-    #[allow(unused_variables)]
+    /// Parses a configuration.
+    ///
+    /// The error of a configuration which can't be parsed has the position
+    /// of the first problem. If the root, appenders or loggers have more than
+    /// one, it is a `ParseErrors` listing them all.
     fn parse(&self, source: &str) -> anyhow::Result<RawConfig> {
+        self.parse_as::<RawConfig>(source).map_err(|e| {
+            match self.parse_as::<RawConfigSections>(source) {
+                Ok(sections) => {
+                    let errors = sections.errors();
+                    if errors.len() > 1 {
+                        ParseErrors {
+                            first: e,
+                            errors: errors.into_iter().map(anyhow::Error::msg).collect(),
+                        }
+                        .into()
+                    } else {
+                        e
+                    }
+                }
+                Err(_) => e,
+            }
+        })
+    }
+
+    #[allow(unused_variables)]
+    fn parse_as<T: DeserializeOwned>(&self, source: &str) -> anyhow::Result<T> {
         match *self {
             #[cfg(feature = "yaml_format")]
             Format::Yaml => ::serde_yaml::from_str(source).map_err(Into::into),
@@ -143,7 +273,69 @@ fn read_config(path: &Path) -> anyhow::Result<String> {
     Ok(s)
 }
 
-fn deserialize(config: &RawConfig, deserializers: &Deserializers) -> Config {
+/// Parses the configuration read from `path`, merged on top of the files it
+/// includes.
+fn parse_with_includes(format: &Format, path: &Path, source: &str) -> anyhow::Result<RawConfig> {
+    parse_included(format, path, source, &mut vec![], &mut vec![])
+}
+
+/// Like `parse_with_includes`, but also returns the files that were included,
+/// so that they can be watched for changes.
+fn parse_tracking_includes(
+    format: &Format,
+    path: &Path,
+    source: &str,
+) -> anyhow::Result<(RawConfig, Vec<IncludedFile>)> {
+    let mut includes = vec![];
+    let config = parse_included(format, path, source, &mut vec![], &mut includes)?;
+    Ok((config, includes))
+}
+
+fn parse_included(
+    format: &Format,
+    path: &Path,
+    source: &str,
+    stack: &mut Vec<PathBuf>,
+    includes: &mut Vec<IncludedFile>,
+) -> anyhow::Result<RawConfig> {
+    let canonical = fs::canonicalize(path).unwrap_or_else(|_| path.to_owned());
+    if let Some(start) = stack.iter().position(|p| *p == canonical) {
+        let cycle = stack[start..]
+            .iter()
+            .chain(Some(&canonical))
+            .map(|p| p.display().to_string())
+            .collect::<Vec<_>>();
+        anyhow::bail!("cyclic include: {}", cycle.join(" -> "));
+    }
+    stack.push(canonical);
+
+    let mut config = format.parse(source)?;
+    let mut merged = RawConfig::default();
+    for include in config.take_includes() {
+        let include = path.parent().unwrap_or_else(|| Path::new("")).join(include);
+        let format = Format::from_path(&include)?;
+        let modified = fs::metadata(&include).and_then(|m| m.modified()).ok();
+        let source = read_config(&include).map_err(|e| {
+            anyhow::anyhow!("error reading included file {}: {}", include.display(), e)
+        })?;
+        merged.merge(parse_included(&format, &include, &source, stack, includes)?);
+        includes.push(IncludedFile {
+            path: include,
+            modified,
+            source: Some(source),
+        });
+    }
+    merged.merge(config);
+
+    stack.pop();
+    Ok(merged)
+}
+
+fn deserialize(mut config: RawConfig, deserializers: &Deserializers) -> Config {
+    if let Some(directives) = deserializers.level_env().and_then(|var| env::var(var).ok()) {
+        config.override_levels(&directives);
+    }
+    config.handle_unattached_appenders(deserializers);
     let (appenders, mut errors) = config.appenders_lossy(deserializers);
     errors.handle();
 
@@ -157,37 +349,46 @@ fn deserialize(config: &RawConfig, deserializers: &Deserializers) -> Config {
     config
 }
 
+/// A file pulled in by an `include`, as it was when last read.
+struct IncludedFile {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    source: Option<String>,
+}
+
+impl IncludedFile {
+    /// Checks the file again, returning whether it has changed.
+    fn refresh(&mut self) -> bool {
+        let modified = fs::metadata(&self.path).and_then(|m| m.modified()).ok();
+        if modified.is_some() && modified == self.modified {
+            return false;
+        }
+        self.modified = modified;
+
+        let source = read_config(&self.path).ok();
+        if source == self.source {
+            return false;
+        }
+        self.source = source;
+        true
+    }
+}
+
 struct ConfigReloader {
     path: PathBuf,
     format: Format,
     source: String,
     modified: Option<SystemTime>,
+    includes: Vec<IncludedFile>,
     deserializers: Deserializers,
     handle: Handle,
 }
 
 impl ConfigReloader {
-    fn start(
-        path: PathBuf,
-        format: Format,
-        rate: Duration,
-        source: String,
-        modified: Option<SystemTime>,
-        deserializers: Deserializers,
-        handle: Handle,
-    ) {
-        let mut reloader = ConfigReloader {
-            path,
-            format,
-            source,
-            modified,
-            deserializers,
-            handle,
-        };
-
+    fn start(mut self, rate: Duration) {
         thread::Builder::new()
             .name("log4rs refresh".to_owned())
-            .spawn(move || reloader.run(rate))
+            .spawn(move || self.run(rate))
             .unwrap();
     }
 
@@ -204,9 +405,15 @@ impl ConfigReloader {
     }
 
     fn run_once(&mut self, rate: Duration) -> anyhow::Result<Option<Duration>> {
+        // every included file is refreshed, not just up to the first change
+        let mut includes_changed = false;
+        for include in &mut self.includes {
+            includes_changed |= include.refresh();
+        }
+
         if let Some(last_modified) = self.modified {
             let modified = fs::metadata(&self.path).and_then(|m| m.modified())?;
-            if last_modified == modified {
+            if last_modified == modified && !includes_changed {
                 return Ok(Some(rate));
             }
 
@@ -215,18 +422,381 @@ impl ConfigReloader {
 
         let source = read_config(&self.path)?;
 
-        if source == self.source {
+        if source == self.source && !includes_changed {
             return Ok(Some(rate));
         }
 
         self.source = source;
 
-        let config = self.format.parse(&self.source)?;
+        let (config, includes) = parse_tracking_includes(&self.format, &self.path, &self.source)?;
+        self.includes = includes;
         let rate = config.refresh_rate();
-        let config = deserialize(&config, &self.deserializers);
+        let config = deserialize(config, &self.deserializers);
 
         self.handle.set_config(config);
 
         Ok(rate)
     }
 }
+
+#[cfg(all(
+    test,
+    any(
+        feature = "yaml_format",
+        feature = "json_format",
+        feature = "toml_format"
+    )
+))]
+mod test {
+    use super::*;
+
+    #[test]
+    #[cfg(all(feature = "yaml_format", feature = "file_appender"))]
+    fn merge_files() {
+        use log::LevelFilter;
+
+        let dir = tempfile::tempdir().unwrap();
+        let base = dir.path().join("base.yaml");
+        fs::write(
+            &base,
+            format!(
+                "
+appenders:
+    main:
+        kind: file
+        path: {0}/base.log
+    audit:
+        kind: file
+        path: {0}/audit.log
+root:
+    level: info
+    appenders: [main]
+loggers:
+    app::db:
+        level: warn
+    app::auth:
+        level: info
+        appenders: [audit]
+",
+                dir.path().display()
+            ),
+        )
+        .unwrap();
+        let service = dir.path().join("service.yaml");
+        fs::write(
+            &service,
+            format!(
+                "
+appenders:
+    main:
+        kind: file
+        path: {0}/service.log
+loggers:
+    app::db:
+        level: debug
+    app::http:
+        level: error
+",
+                dir.path().display()
+            ),
+        )
+        .unwrap();
+
+        let config = load_config_files(&[base, service], Deserializers::default()).unwrap();
+
+        let mut appenders = config
+            .appenders()
+            .iter()
+            .map(|a| a.name())
+            .collect::<Vec<_>>();
+        appenders.sort_unstable();
+        assert_eq!(appenders, ["audit", "main"]);
+        assert!(dir.path().join("service.log").exists());
+        assert!(!dir.path().join("base.log").exists());
+
+        assert_eq!(config.root().level(), LevelFilter::Info);
+        assert_eq!(config.root().appenders(), ["main"]);
+
+        let level = |name| {
+            config
+                .loggers()
+                .iter()
+                .find(|logger| logger.name() == name)
+                .map(|logger| logger.level())
+        };
+        assert_eq!(level("app::db"), Some(LevelFilter::Debug));
+        assert_eq!(level("app::auth"), Some(LevelFilter::Info));
+        assert_eq!(level("app::http"), Some(LevelFilter::Error));
+    }
+
+    #[test]
+    #[cfg(feature = "yaml_format")]
+    fn includes() {
+        use log::LevelFilter;
+
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("shared")).unwrap();
+        fs::write(
+            dir.path().join("shared").join("loggers.yaml"),
+            "
+root:
+    level: warn
+loggers:
+    app::db:
+        level: warn
+    app::auth:
+        level: info
+",
+        )
+        .unwrap();
+        let main = dir.path().join("main.yaml");
+        fs::write(
+            &main,
+            "
+include: [shared/loggers.yaml]
+loggers:
+    app::db:
+        level: trace
+",
+        )
+        .unwrap();
+
+        let config = load_config_file(&main, Deserializers::default()).unwrap();
+        assert_eq!(config.root().level(), LevelFilter::Warn);
+        let levels = config
+            .loggers()
+            .iter()
+            .map(|logger| (logger.name(), logger.level()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            levels,
+            [
+                ("app::auth", LevelFilter::Info),
+                ("app::db", LevelFilter::Trace),
+            ]
+        );
+
+        fs::write(
+            dir.path().join("shared").join("loggers.yaml"),
+            "include: [../main.yaml]",
+        )
+        .unwrap();
+        let err = load_config_file(&main, Deserializers::default()).unwrap_err();
+        let err = err.to_string();
+        assert!(err.starts_with("cyclic include: "), "{}", err);
+        assert!(err.ends_with("main.yaml"), "{}", err);
+        assert!(err.contains("loggers.yaml"), "{}", err);
+    }
+
+    #[test]
+    #[cfg(feature = "yaml_format")]
+    fn custom_kind() {
+        use crate::{append::Append, config::Deserialize};
+
+        #[derive(Debug)]
+        struct Custom;
+
+        impl Append for Custom {
+            fn append(&self, _: &log::Record) -> anyhow::Result<()> {
+                Ok(())
+            }
+
+            fn flush(&self) {}
+        }
+
+        #[derive(serde::Deserialize)]
+        struct CustomConfig {}
+
+        struct CustomDeserializer;
+
+        impl Deserialize for CustomDeserializer {
+            type Trait = dyn Append;
+
+            type Config = CustomConfig;
+
+            fn deserialize(
+                &self,
+                _: CustomConfig,
+                _: &Deserializers,
+            ) -> anyhow::Result<Box<dyn Append>> {
+                Ok(Box::new(Custom))
+            }
+        }
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("log4rs.yaml");
+        fs::write(
+            &path,
+            "appenders:\n  custom:\n    kind: custom\nroot:\n  appenders: [custom]\n",
+        )
+        .unwrap();
+
+        let mut deserializers = Deserializers::default();
+        deserializers.insert("custom", CustomDeserializer);
+        let config = load_config_file(&path, deserializers).unwrap();
+        assert_eq!(config.appenders()[0].name(), "custom");
+        assert_eq!(config.root().appenders(), ["custom"]);
+    }
+
+    #[test]
+    #[cfg(feature = "yaml_format")]
+    fn reload_keeps_config_on_error() {
+        use log::LevelFilter;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("log4rs.yaml");
+        let source = "root:\n  level: info\n".to_owned();
+        fs::write(&path, &source).unwrap();
+
+        let logger = crate::Logger::new(load_config_file(&path, Deserializers::new()).unwrap());
+        let mut reloader = ConfigReloader {
+            path: path.clone(),
+            format: Format::Yaml,
+            source,
+            modified: None,
+            includes: vec![],
+            deserializers: Deserializers::new(),
+            handle: Handle {
+                shared: logger.0.clone(),
+            },
+        };
+        let rate = Duration::from_secs(30);
+
+        fs::write(&path, "root: [not, a, map").unwrap();
+        assert!(reloader.run_once(rate).is_err());
+        assert_eq!(logger.max_log_level(), LevelFilter::Info);
+
+        fs::write(&path, "refresh_rate: 1 minute\nroot:\n  level: debug\n").unwrap();
+        let rate = reloader.run_once(rate).unwrap();
+        assert_eq!(rate, Some(Duration::from_secs(60)));
+        assert_eq!(logger.max_log_level(), LevelFilter::Debug);
+    }
+
+    #[test]
+    #[cfg(feature = "yaml_format")]
+    fn reload_watches_includes() {
+        use log::LevelFilter;
+
+        let dir = tempfile::tempdir().unwrap();
+        let shared = dir.path().join("shared.yaml");
+        fs::write(&shared, "root:\n  level: info\n").unwrap();
+        let path = dir.path().join("log4rs.yaml");
+        let source = "include: [shared.yaml]\n".to_owned();
+        fs::write(&path, &source).unwrap();
+
+        let (config, includes) = parse_tracking_includes(&Format::Yaml, &path, &source).unwrap();
+        let logger = crate::Logger::new(deserialize(config, &Deserializers::new()));
+        let mut reloader = ConfigReloader {
+            path,
+            format: Format::Yaml,
+            source,
+            modified: None,
+            includes,
+            deserializers: Deserializers::new(),
+            handle: Handle {
+                shared: logger.0.clone(),
+            },
+        };
+        let rate = Duration::from_secs(30);
+
+        assert_eq!(reloader.run_once(rate).unwrap(), Some(rate));
+        assert_eq!(logger.max_log_level(), LevelFilter::Info);
+
+        // the rewrite may land within the mtime's resolution
+        reloader.includes[0].modified = None;
+        fs::write(&shared, "root:\n  level: debug\n").unwrap();
+        assert_eq!(reloader.run_once(rate).unwrap(), None);
+        assert_eq!(logger.max_log_level(), LevelFilter::Debug);
+    }
+
+    fn assert_position(format: Format, source: &str, position: &str) {
+        let err = format.parse(source).unwrap_err().to_string();
+        assert!(err.contains(position), "{}", err);
+    }
+
+    #[test]
+    #[cfg(feature = "yaml_format")]
+    fn yaml_error_position() {
+        assert_position(
+            Format::Yaml,
+            "root:\n  level: info\n  appenders: [stdout\n",
+            "line 4",
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "yaml_format")]
+    fn yaml_section_error_position() {
+        assert_position(
+            Format::Yaml,
+            "root:\n  level: info\nappenders:\n  file:\n    kind: file\n    level: [warn]\n",
+            "line 4",
+        );
+        let err = Format::Yaml.parse("appenders:\n  stdout:\n    target: stderr\n");
+        let err = err.unwrap_err().to_string();
+        assert_eq!(err, "appenders: missing field `kind` at line 2 column 3");
+    }
+
+    #[test]
+    #[cfg(feature = "yaml_format")]
+    fn errors_accumulate() {
+        let cfg = "
+root:
+    level: loud
+appenders:
+    stdout:
+        target: stderr
+    file:
+        kind: file
+        level: [warn]
+loggers:
+    app:
+        additive: maybe
+levels:
+    app::db: verbose
+";
+        let err = Format::Yaml.parse(cfg).unwrap_err();
+        let err = err.downcast_ref::<ParseErrors>().unwrap();
+        let first = err.first().to_string();
+        assert!(
+            first.starts_with("root.level: unknown level `loud`"),
+            "{}",
+            err
+        );
+        assert!(first.ends_with("at line 3 column 12"), "{}", err);
+
+        let errors = err
+            .errors()
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        assert_eq!(errors.len(), 5, "{}", err);
+        assert!(errors[0].starts_with("root: "), "{}", err);
+        assert!(errors[0].contains("unknown level `loud`"), "{}", err);
+        assert!(errors[1].starts_with("appender `file`: "), "{}", err);
+        assert_eq!(errors[2], "appender `stdout`: Missing field kind");
+        assert!(errors[3].starts_with("logger `app`: "), "{}", err);
+        assert!(
+            errors[4].starts_with("level of logger `app::db`: "),
+            "{}",
+            err
+        );
+        assert!(errors[4].contains("unknown level `verbose`"), "{}", err);
+    }
+
+    #[test]
+    #[cfg(feature = "json_format")]
+    fn json_error_position() {
+        assert_position(
+            Format::Json,
+            "{\n  \"root\": {\n    \"level\": info\n  }\n}",
+            "line 3 column",
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "toml_format")]
+    fn toml_error_position() {
+        assert_position(Format::Toml, "[root]\nlevel = info\n", "line 2, column");
+    }
+}
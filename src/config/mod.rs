@@ -1,7 +1,9 @@
 //! All things pertaining to log4rs config.
 #![doc = include_str!("../../docs/Configuration.md")]
 
-use log::SetLoggerError;
+use std::sync::{Arc, Mutex};
+
+use log::{LevelFilter, SetLoggerError};
 use thiserror::Error;
 
 use crate::Handle;
@@ -13,24 +15,61 @@ mod file;
 #[cfg(feature = "config_parsing")]
 mod raw;
 
-pub use runtime::{Appender, Config, Logger, Root};
+pub use runtime::{Appender, Config, ConfigView, Logger, Root};
 
 #[cfg(feature = "config_parsing")]
-pub use self::file::{init_file, load_config_file, FormatError};
+pub use self::file::{
+    init_file, init_string, load_config_file, load_config_files, FormatError, ParseErrors,
+};
+#[cfg(all(
+    feature = "config_parsing",
+    any(feature = "dedup_appender", feature = "tcp_appender")
+))]
+pub(crate) use self::raw::de_duration;
+#[cfg(feature = "config_parsing")]
+pub(crate) use self::raw::de_level;
 #[cfg(feature = "config_parsing")]
-pub use self::raw::{Deserializable, Deserialize, Deserializers, RawConfig};
+pub use self::raw::{
+    Deserializable, Deserialize, DeserializeError, Deserializers, RawConfig, UnattachedAppenders,
+    Warning,
+};
 
 /// Initializes the global logger as a log4rs logger with the provided config.
 ///
 /// A `Handle` object is returned which can be used to adjust the logging
 /// configuration.
 pub fn init_config(config: runtime::Config) -> Result<crate::Handle, SetLoggerError> {
-    let logger = crate::Logger::new(config);
-    log::set_max_level(logger.max_log_level());
+    install(crate::Logger::new(config))
+}
+
+/// Initializes the global logger as a log4rs logger which buffers records
+/// until a configuration is installed.
+///
+/// Records at or below `level` are held in memory, up to `capacity` of them;
+/// once the buffer is full the oldest records are dropped. This lets records
+/// logged early in startup, possibly by libraries, survive until the
+/// application has loaded its configuration.
+///
+/// A later call to `init_config`, `init_file` or another `init_*` function
+/// installs its configuration into this logger instead of failing, and the
+/// buffered records are then logged through it, as is any configuration set
+/// through the returned `Handle`. The number of records dropped, if any, is
+/// reported to the error handler.
+pub fn init_buffered(level: LevelFilter, capacity: usize) -> Result<Handle, SetLoggerError> {
+    let config = runtime::Config::builder()
+        .build(Root::builder().build(level))
+        .expect("an empty config is valid");
+    let mut shared = crate::SharedLogger::new(config);
+    shared.early = Some(crate::EarlyBuffer {
+        state: Mutex::default(),
+        capacity,
+    });
+    let logger = crate::Logger(Arc::new(arc_swap::ArcSwap::new(Arc::new(shared))));
+    log::set_max_level(level);
     let handle = Handle {
         shared: logger.0.clone(),
     };
-    log::set_boxed_logger(Box::new(logger)).map(|()| handle)
+    log::set_boxed_logger(Box::new(logger)).map(|()| register(handle))
 }
 
 /// Initializes the global logger as a log4rs logger with the provided config and error handler.
@@ -41,23 +80,20 @@ pub fn init_config_with_err_handler(
     config: runtime::Config,
     err_handler: Box<dyn Send + Sync + Fn(&anyhow::Error)>,
 ) -> Result<crate::Handle, SetLoggerError> {
-    let logger = crate::Logger::new_with_err_handler(config, err_handler);
-    log::set_max_level(logger.max_log_level());
-    let handle = Handle {
-        shared: logger.0.clone(),
-    };
-    log::set_boxed_logger(Box::new(logger)).map(|()| handle)
+    install(crate::Logger::new_with_err_handler(config, err_handler))
 }
 
 /// Create a log4rs logger using the provided raw config.
 ///
 /// This will return errors if the appenders configuration is malformed.
 #[cfg(feature = "config_parsing")]
-pub fn create_raw_config(config: RawConfig) -> Result<crate::Logger, InitError> {
-    let (appenders, errors) = config.appenders_lossy(&Deserializers::default());
+pub fn create_raw_config(mut config: RawConfig) -> Result<crate::Logger, InitError> {
+    let deserializers = Deserializers::default();
+    let (appenders, errors) = config.appenders_lossy(&deserializers);
     if !errors.is_empty() {
         return Err(InitError::Deserializing(errors));
     }
+    config.handle_unattached_appenders(&deserializers);
     let config = Config::builder()
         .appenders(appenders)
         .loggers(config.loggers())
@@ -71,12 +107,30 @@ pub fn create_raw_config(config: RawConfig) -> Result<crate::Logger, InitError>
 /// This will return errors if the appenders configuration is malformed or if we fail to set the global logger.
 #[cfg(feature = "config_parsing")]
 pub fn init_raw_config(config: RawConfig) -> Result<(), InitError> {
-    let logger = create_raw_config(config)?;
-    log::set_max_level(logger.max_log_level());
-    log::set_boxed_logger(Box::new(logger))?;
+    install(create_raw_config(config)?)?;
     Ok(())
 }
 
+/// Installs `logger` as the global logger, or into the logger installed by
+/// `init_buffered` if it is still buffering.
+fn install(logger: crate::Logger) -> Result<Handle, SetLoggerError> {
+    if let Some(handle) = crate::HANDLE.get().filter(|handle| handle.is_buffering()) {
+        handle.install(logger.0.load_full());
+        return Ok(handle.clone());
+    }
+    log::set_max_level(logger.max_log_level());
+    let handle = Handle {
+        shared: logger.0.clone(),
+    };
+    log::set_boxed_logger(Box::new(logger)).map(|()| register(handle))
+}
+
+/// Records the handle of the installed global logger for `crate::shutdown`.
+fn register(handle: Handle) -> Handle {
+    let _ = crate::HANDLE.set(handle.clone());
+    handle
+}
+
 /// Errors found when initializing.
 #[derive(Debug, Error)]
 pub enum InitError {
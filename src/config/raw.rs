@@ -37,6 +37,10 @@
 //!         # filter's builder, and will vary based on the kind of filter.
 //!         level: error
 //!
+//!     # An optional level. Events below it are rejected by this appender, as
+//!     # with a threshold filter.
+//!     level: info
+//!
 //!     # The remainder of the configuration is passed along to the appender's
 //!     # builder, and will vary based on the kind of appender.
 //!     # Appenders will commonly be associated with an encoder.
@@ -86,12 +90,59 @@
 //!     # parent will also be attached to this logger.
 //!     # Default: true
 //!     additive: false
+//!
+//!     # A pattern for this logger's events. If set, the logger's events are
+//!     # written to each of its appenders with a pattern encoder with this
+//!     # pattern, through an appender named `<appender>@<logger>` which shares
+//!     # the output of the original. The root and other loggers keep the
+//!     # original encoder. Only appenders whose deserializer reports
+//!     # `supports_encoder_override`, such as console, file and rolling_file,
+//!     # support this.
+//!     #
+//!     # Default: none
+//!     pattern: "{d} {l} {M} - {m}{n}"
+//!
+//! # The "levels" map is a shorthand for loggers which only set a level. Each
+//! # entry adds a logger with no appenders of its own. A name may not appear
+//! # in both "levels" and "loggers".
+//! levels:
+//!   hyper: warn
+//!   app::db: debug
+//!
+//! # The "patterns" map defines named patterns. A pattern encoder's or a
+//! # logger's pattern of the form `@name` refers to one of them, and an
+//! # unknown name is an error. A pattern which really starts with `@` is
+//! # written with `@@` instead.
+//! patterns:
+//!   detailed: "{d} {l} [{T}] {M}:{L} - {m}{n}"
+//! ```
+//!
+//! YAML anchors and aliases may be used to share blocks between components;
+//! an alias is expanded to a copy of the anchored value before the
+//! configuration is deserialized. Merge keys (`<<`) are not supported.
+//!
+//! ```yaml
+//! appenders:
+//!   stdout:
+//!     kind: console
+//!     encoder: &encoder
+//!       pattern: "{d} [{t}] {m}{n}"
+//!   requests:
+//!     kind: file
+//!     path: log/requests.log
+//!     encoder: *encoder
 //! ```
 #![allow(deprecated)]
 
-use std::{collections::HashMap, fmt, marker::PhantomData, sync::Arc, time::Duration};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    convert::TryFrom,
+    error, fmt,
+    marker::PhantomData,
+    sync::Arc,
+    time::Duration,
+};
 
-use anyhow::anyhow;
 use derivative::Derivative;
 use log::LevelFilter;
 use serde::de::{self, Deserialize as SerdeDeserialize, DeserializeOwned};
@@ -99,16 +150,13 @@ use serde_value::Value;
 use thiserror::Error;
 use typemap_ors::{Key, ShareCloneMap};
 
-use crate::{append::AppenderConfig, config};
+use crate::{
+    append::AppenderConfig,
+    config,
+    filter::{self, Filter},
+};
 
-#[allow(unused_imports)]
-use crate::append;
-
-#[cfg(any(feature = "json_encoder", feature = "pattern_encoder"))]
-use crate::encode;
-
-#[cfg(feature = "threshold_filter")]
-use crate::filter;
+use crate::{append, encode};
 
 /// A trait implemented by traits which are deserializable.
 pub trait Deserializable: 'static {
@@ -132,6 +180,14 @@ pub trait Deserialize: Send + Sync + 'static {
         config: Self::Config,
         deserializers: &Deserializers,
     ) -> anyhow::Result<Box<Self::Trait>>;
+
+    /// Returns whether the appenders created by this deserializer implement
+    /// `Append::append_with_encoder`, and so support a logger's `pattern`.
+    ///
+    /// Only appender deserializers are asked. Defaults to `false`.
+    fn supports_encoder_override(&self) -> bool {
+        false
+    }
 }
 
 trait ErasedDeserialize: Send + Sync + 'static {
@@ -142,6 +198,8 @@ trait ErasedDeserialize: Send + Sync + 'static {
         config: Value,
         deserializers: &Deserializers,
     ) -> anyhow::Result<Box<Self::Trait>>;
+
+    fn supports_encoder_override(&self) -> bool;
 }
 
 struct DeserializeEraser<T>(T);
@@ -157,9 +215,15 @@ where
         config: Value,
         deserializers: &Deserializers,
     ) -> anyhow::Result<Box<Self::Trait>> {
-        let config = config.deserialize_into()?;
+        let config = config
+            .deserialize_into()
+            .map_err(DeserializeError::InvalidConfig)?;
         self.0.deserialize(config, deserializers)
     }
+
+    fn supports_encoder_override(&self) -> bool {
+        self.0.supports_encoder_override()
+    }
 }
 
 struct KeyAdaptor<T: ?Sized>(PhantomData<T>);
@@ -170,7 +234,161 @@ impl<T: ?Sized + 'static> Key for KeyAdaptor<T> {
 
 /// A container of `Deserialize`rs.
 #[derive(Clone)]
-pub struct Deserializers(ShareCloneMap);
+pub struct Deserializers {
+    map: ShareCloneMap,
+    default_pattern: Option<String>,
+    level_env: Option<String>,
+    unattached_appenders: UnattachedAppenders,
+}
+
+/// The component name, kind, Cargo feature and whether that feature is
+/// enabled for each of the default deserializers.
+const BUILTIN_KINDS: &[(&str, &str, &str, bool)] = &[
+    (
+        "appender",
+        "console",
+        "console_appender",
+        cfg!(feature = "console_appender"),
+    ),
+    (
+        "appender",
+        "file",
+        "file_appender",
+        cfg!(feature = "file_appender"),
+    ),
+    (
+        "appender",
+        "rolling_file",
+        "rolling_file_appender",
+        cfg!(feature = "rolling_file_appender"),
+    ),
+    (
+        "appender",
+        "async",
+        "async_appender",
+        cfg!(feature = "async_appender"),
+    ),
+    (
+        "appender",
+        "composite",
+        "composite_appender",
+        cfg!(feature = "composite_appender"),
+    ),
+    (
+        "appender",
+        "dedup",
+        "dedup_appender",
+        cfg!(feature = "dedup_appender"),
+    ),
+    (
+        "appender",
+        "fallback",
+        "fallback_appender",
+        cfg!(feature = "fallback_appender"),
+    ),
+    (
+        "appender",
+        "null",
+        "null_appender",
+        cfg!(feature = "null_appender"),
+    ),
+    (
+        "appender",
+        "syslog",
+        "syslog_appender",
+        cfg!(feature = "syslog_appender"),
+    ),
+    (
+        "appender",
+        "tcp",
+        "tcp_appender",
+        cfg!(feature = "tcp_appender"),
+    ),
+    (
+        "appender",
+        "udp",
+        "udp_appender",
+        cfg!(feature = "udp_appender"),
+    ),
+    (
+        "policy",
+        "compound",
+        "compound_policy",
+        cfg!(feature = "compound_policy"),
+    ),
+    (
+        "roller",
+        "dated",
+        "dated_roller",
+        cfg!(feature = "dated_roller"),
+    ),
+    (
+        "roller",
+        "delete",
+        "delete_roller",
+        cfg!(feature = "delete_roller"),
+    ),
+    (
+        "roller",
+        "fixed_window",
+        "fixed_window_roller",
+        cfg!(feature = "fixed_window_roller"),
+    ),
+    (
+        "trigger",
+        "size",
+        "size_trigger",
+        cfg!(feature = "size_trigger"),
+    ),
+    (
+        "trigger",
+        "time",
+        "time_trigger",
+        cfg!(feature = "time_trigger"),
+    ),
+    (
+        "trigger",
+        "onstartup",
+        "onstartup_trigger",
+        cfg!(feature = "onstartup_trigger"),
+    ),
+    (
+        "encoder",
+        "json",
+        "json_encoder",
+        cfg!(feature = "json_encoder"),
+    ),
+    (
+        "encoder",
+        "pattern",
+        "pattern_encoder",
+        cfg!(feature = "pattern_encoder"),
+    ),
+    (
+        "filter",
+        "level_range",
+        "level_range_filter",
+        cfg!(feature = "level_range_filter"),
+    ),
+    (
+        "filter",
+        "message",
+        "message_filter",
+        cfg!(feature = "message_filter"),
+    ),
+    (
+        "filter",
+        "sample",
+        "sample_filter",
+        cfg!(feature = "sample_filter"),
+    ),
+    (
+        "filter",
+        "threshold",
+        "threshold_filter",
+        cfg!(feature = "threshold_filter"),
+    ),
+];
 
 impl Default for Deserializers {
     fn default() -> Deserializers {
@@ -189,12 +407,45 @@ impl Default for Deserializers {
             append::rolling_file::RollingFileAppenderDeserializer,
         );
 
+        #[cfg(feature = "async_appender")]
+        d.insert("async", append::async_appender::AsyncAppenderDeserializer);
+
+        #[cfg(feature = "composite_appender")]
+        d.insert(
+            "composite",
+            append::composite::CompositeAppenderDeserializer,
+        );
+
+        #[cfg(feature = "dedup_appender")]
+        d.insert("dedup", append::dedup::DedupAppenderDeserializer);
+
+        #[cfg(feature = "fallback_appender")]
+        d.insert("fallback", append::fallback::FallbackAppenderDeserializer);
+
+        #[cfg(feature = "null_appender")]
+        d.insert("null", append::null::NullAppenderDeserializer);
+
+        #[cfg(feature = "syslog_appender")]
+        d.insert("syslog", append::syslog::SyslogAppenderDeserializer);
+
+        #[cfg(feature = "tcp_appender")]
+        d.insert("tcp", append::tcp::TcpAppenderDeserializer);
+
+        #[cfg(feature = "udp_appender")]
+        d.insert("udp", append::udp::UdpAppenderDeserializer);
+
         #[cfg(feature = "compound_policy")]
         d.insert(
             "compound",
             append::rolling_file::policy::compound::CompoundPolicyDeserializer,
         );
 
+        #[cfg(feature = "dated_roller")]
+        d.insert(
+            "dated",
+            append::rolling_file::policy::compound::roll::dated::DatedRollerDeserializer,
+        );
+
         #[cfg(feature = "delete_roller")]
         d.insert(
             "delete",
@@ -231,6 +482,18 @@ impl Default for Deserializers {
         #[cfg(feature = "pattern_encoder")]
         d.insert("pattern", encode::pattern::PatternEncoderDeserializer);
 
+        #[cfg(feature = "level_range_filter")]
+        d.insert(
+            "level_range",
+            filter::level_range::LevelRangeFilterDeserializer,
+        );
+
+        #[cfg(feature = "message_filter")]
+        d.insert("message", filter::message::MessageFilterDeserializer);
+
+        #[cfg(feature = "sample_filter")]
+        d.insert("sample", filter::sample::SampleFilterDeserializer);
+
         #[cfg(feature = "threshold_filter")]
         d.insert("threshold", filter::threshold::ThresholdFilterDeserializer);
 
@@ -250,6 +513,22 @@ impl Deserializers {
     ///         * Requires the `file_appender` feature.
     ///     * "rolling_file" -> `RollingFileAppenderDeserializer`
     ///         * Requires the `rolling_file_appender` feature.
+    ///     * "async" -> `AsyncAppenderDeserializer`
+    ///         * Requires the `async_appender` feature.
+    ///     * "composite" -> `CompositeAppenderDeserializer`
+    ///         * Requires the `composite_appender` feature.
+    ///     * "dedup" -> `DedupAppenderDeserializer`
+    ///         * Requires the `dedup_appender` feature.
+    ///     * "fallback" -> `FallbackAppenderDeserializer`
+    ///         * Requires the `fallback_appender` feature.
+    ///     * "null" -> `NullAppenderDeserializer`
+    ///         * Requires the `null_appender` feature.
+    ///     * "syslog" -> `SyslogAppenderDeserializer`
+    ///         * Requires the `syslog_appender` feature.
+    ///     * "tcp" -> `TcpAppenderDeserializer`
+    ///         * Requires the `tcp_appender` feature.
+    ///     * "udp" -> `UdpAppenderDeserializer`
+    ///         * Requires the `udp_appender` feature.
     /// * Encoders
     ///     * "pattern" -> `PatternEncoderDeserializer`
     ///         * Requires the `pattern_encoder` feature.
@@ -258,10 +537,18 @@ impl Deserializers {
     /// * Filters
     ///     * "threshold" -> `ThresholdFilterDeserializer`
     ///         * Requires the `threshold_filter` feature.
+    ///     * "message" -> `MessageFilterDeserializer`
+    ///         * Requires the `message_filter` feature.
+    ///     * "level_range" -> `LevelRangeFilterDeserializer`
+    ///         * Requires the `level_range_filter` feature.
+    ///     * "sample" -> `SampleFilterDeserializer`
+    ///         * Requires the `sample_filter` feature.
     /// * Policies
     ///     *  "compound" -> `CompoundPolicyDeserializer`
     ///         * Requires the `compound_policy` feature.
     /// * Rollers
+    ///     * "dated" -> `DatedRollerDeserializer`
+    ///         * Requires the `dated_roller` feature.
     ///     * "delete" -> `DeleteRollerDeserializer`
     ///         * Requires the `delete_roller` feature.
     ///     * "fixed_window" -> `FixedWindowRollerDeserializer`
@@ -279,7 +566,12 @@ impl Deserializers {
 
     /// Creates a new `Deserializers` with no mappings.
     pub fn empty() -> Deserializers {
-        Deserializers(ShareCloneMap::custom())
+        Deserializers {
+            map: ShareCloneMap::custom(),
+            default_pattern: None,
+            level_env: None,
+            unattached_appenders: UnattachedAppenders::default(),
+        }
     }
 
     /// Adds a mapping from the specified `kind` to a deserializer.
@@ -287,28 +579,281 @@ impl Deserializers {
     where
         T: Deserialize,
     {
-        self.0
+        self.map
             .entry::<KeyAdaptor<T::Trait>>()
             .or_insert_with(HashMap::new)
             .insert(kind.to_owned(), Arc::new(DeserializeEraser(deserializer)));
     }
 
+    /// Adds `alias` as another kind for the deserializer currently registered
+    /// for `kind`.
+    ///
+    /// This lets configurations written for other logging frameworks use
+    /// their own names for a component. The alias refers to the deserializer
+    /// registered when it is added, so replacing `kind` later leaves the
+    /// alias unchanged. Returns `false`, adding nothing, if no deserializer is
+    /// registered for `kind`.
+    pub fn insert_alias<T>(&mut self, alias: &str, kind: &str) -> bool
+    where
+        T: Deserializable + ?Sized,
+    {
+        let map = match self.map.get_mut::<KeyAdaptor<T>>() {
+            Some(map) => map,
+            None => return false,
+        };
+        match map.get(kind).cloned() {
+            Some(deserializer) => {
+                map.insert(alias.to_owned(), deserializer);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Adds `alias` as another kind for the appender deserializer currently
+    /// registered for `kind`, as with `insert_alias`.
+    ///
+    /// ```
+    /// # use log4rs::config::Deserializers;
+    /// let mut deserializers = Deserializers::new();
+    /// for alias in ["RollingFile", "RollingFileAppender", "rolling"] {
+    ///     assert!(deserializers.add_appender_alias(alias, "rolling_file"));
+    /// }
+    /// ```
+    pub fn add_appender_alias(&mut self, alias: &str, kind: &str) -> bool {
+        self.insert_alias::<dyn crate::append::Append>(alias, kind)
+    }
+
     /// Deserializes a value of a specific type and kind.
     pub fn deserialize<T>(&self, kind: &str, config: Value) -> anyhow::Result<Box<T>>
     where
         T: Deserializable + ?Sized,
     {
-        match self.0.get::<KeyAdaptor<T>>().and_then(|m| m.get(kind)) {
+        match self.map.get::<KeyAdaptor<T>>().and_then(|m| m.get(kind)) {
             Some(b) => b.deserialize(config, self),
-            None => Err(anyhow!(
-                "no {} deserializer for kind `{}` registered",
-                T::name(),
-                kind
-            )),
+            None => match BUILTIN_KINDS
+                .iter()
+                .find(|&&(name, k, _, enabled)| name == T::name() && k == kind && !enabled)
+            {
+                Some(&(_, _, feature, _)) => Err(DeserializeError::MissingFeature {
+                    name: T::name(),
+                    kind: kind.to_owned(),
+                    feature,
+                }
+                .into()),
+                None => Err(DeserializeError::UnknownKind {
+                    name: T::name(),
+                    kind: kind.to_owned(),
+                    known: self.kinds::<T>(),
+                }
+                .into()),
+            },
+        }
+    }
+
+    /// Returns whether the appender deserializer registered for `kind`, or
+    /// the one it is an alias of, supports a logger's `pattern`.
+    fn supports_encoder_override(&self, kind: &str) -> bool {
+        self.map
+            .get::<KeyAdaptor<dyn append::Append>>()
+            .and_then(|m| m.get(kind))
+            .map_or(false, |d| d.supports_encoder_override())
+    }
+
+    /// Returns the registered kinds of a specific type, sorted.
+    fn kinds<T>(&self) -> Vec<String>
+    where
+        T: Deserializable + ?Sized,
+    {
+        let mut kinds = self
+            .map
+            .get::<KeyAdaptor<T>>()
+            .map(|m| m.keys().cloned().collect::<Vec<_>>())
+            .unwrap_or_default();
+        kinds.sort();
+        kinds
+    }
+
+    /// Sets the pattern of the encoder used by the console, file, rolling
+    /// file, TCP and UDP appenders when their configuration has no `encoder`.
+    ///
+    /// An appender's own encoder always takes precedence. Without a default
+    /// pattern, those appenders use `PatternEncoder`'s default. The syslog
+    /// appender keeps its own default of `{m}`, as the syslog header already
+    /// holds the time, level and source.
+    pub fn set_default_pattern(&mut self, pattern: &str) {
+        self.default_pattern = Some(pattern.to_owned());
+    }
+
+    /// Returns the pattern set by `set_default_pattern`, if any.
+    pub fn default_pattern(&self) -> Option<&str> {
+        self.default_pattern.as_deref()
+    }
+
+    /// Sets the environment variable holding logger level overrides applied
+    /// when a configuration file is loaded or reloaded.
+    ///
+    /// The variable holds comma-separated directives in the style of
+    /// `env_logger`: a bare level sets the root's level and `target=level`
+    /// sets the level of the logger for `target`, adding it if the
+    /// configuration doesn't have it. For example, `warn,app::db=trace`.
+    /// Malformed directives are reported to the error handler and skipped.
+    ///
+    /// No variable is read unless one is set.
+    pub fn set_level_env(&mut self, var: &str) {
+        self.level_env = Some(var.to_owned());
+    }
+
+    /// Returns the variable set by `set_level_env`, if any.
+    pub fn level_env(&self) -> Option<&str> {
+        self.level_env.as_deref()
+    }
+
+    /// Sets what happens to appenders which neither the root nor any logger
+    /// is attached to when a configuration file is loaded or reloaded.
+    ///
+    /// Defaults to `UnattachedAppenders::Warn`.
+    pub fn set_unattached_appenders(&mut self, unattached: UnattachedAppenders) {
+        self.unattached_appenders = unattached;
+    }
+
+    /// Returns the setting made by `set_unattached_appenders`.
+    pub fn unattached_appenders(&self) -> UnattachedAppenders {
+        self.unattached_appenders
+    }
+
+    /// Reports a suspicious but not fatal problem with a component's
+    /// configuration, such as a pattern which never writes the message.
+    ///
+    /// Deserializers call this while building a component which is still
+    /// usable. The message is passed to the error handler as a `Warning`.
+    pub fn warn(&self, message: &str) {
+        crate::handle_error(&Warning(message.to_owned()).into());
+    }
+
+    /// Deserializes an appender's encoder, falling back to the default
+    /// pattern when it has none.
+    ///
+    /// Returns `None` if the appender should use its own default.
+    #[cfg(any(
+        feature = "console_appender",
+        feature = "file_appender",
+        feature = "rolling_file_appender",
+        feature = "tcp_appender",
+        feature = "udp_appender"
+    ))]
+    pub(crate) fn encoder(
+        &self,
+        config: Option<encode::EncoderConfig>,
+    ) -> anyhow::Result<Option<Box<dyn encode::Encode>>> {
+        match (config, &self.default_pattern) {
+            (Some(encoder), _) => self.deserialize(&encoder.kind, encoder.config).map(Some),
+            (None, Some(pattern)) => Ok(Some(Box::new(encode::pattern::PatternEncoder::try_new(
+                pattern,
+            )?))),
+            (None, None) => Ok(None),
         }
     }
 }
 
+/// What happens to appenders which neither the root nor any logger is
+/// attached to, and which so never receive events.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub enum UnattachedAppenders {
+    /// Each one is reported to the error handler as a `Warning`.
+    #[default]
+    Warn,
+    /// Nothing is reported.
+    Ignore,
+    /// If the root has no appenders, every appender is attached to it.
+    /// Appenders left unattached otherwise are reported as with `Warn`.
+    AttachToRoot,
+}
+
+/// A suspicious but not fatal problem found while deserializing a component
+/// or loading a configuration, such as an appender nothing is attached to.
+///
+/// Warnings reach the error handler set by `log4rs::set_error_handler` as
+/// errors which can be downcast to this type to tell them apart from real
+/// errors.
+///
+/// ```
+/// log4rs::set_error_handler(Box::new(|e| {
+///     if e.downcast_ref::<log4rs::config::Warning>().is_some() {
+///         eprintln!("{}", e);
+///     } else {
+///         panic!("{}", e);
+///     }
+/// }));
+/// ```
+#[derive(Debug, Error)]
+#[error("warning: {0}")]
+pub struct Warning(pub String);
+
+/// An error common to all components, found before their deserializer runs.
+///
+/// The errors returned by `Deserializers::deserialize` can be downcast to this
+/// type to tell these apart. Any other error comes from the deserializer
+/// itself, for example when a file appender fails to open its file.
+///
+/// ```
+/// use log4rs::{
+///     append::Append,
+///     config::{DeserializeError, Deserializers},
+/// };
+///
+/// let err = Deserializers::new()
+///     .deserialize::<dyn Append>("carrier_pigeon", serde_value::Value::Unit)
+///     .unwrap_err();
+/// match err.downcast_ref::<DeserializeError>() {
+///     Some(DeserializeError::UnknownKind { kind, .. }) => assert_eq!(kind, "carrier_pigeon"),
+///     _ => panic!("{}", err),
+/// }
+/// ```
+#[derive(Debug, Error)]
+pub enum DeserializeError {
+    /// No deserializer is registered for the kind.
+    #[error("no {name} deserializer for kind `{kind}` registered{}", known_kinds(.known))]
+    UnknownKind {
+        /// The name of the kind of component, such as "appender".
+        name: &'static str,
+        /// The kind which was looked up.
+        kind: String,
+        /// The kinds which are registered, sorted.
+        known: Vec<String>,
+    },
+
+    /// The kind is one of log4rs's own, but the Cargo feature providing it
+    /// is disabled.
+    #[error(
+        "no {name} deserializer for kind `{kind}` registered, it requires the `{feature}` feature"
+    )]
+    MissingFeature {
+        /// The name of the kind of component, such as "appender".
+        name: &'static str,
+        /// The kind which was looked up.
+        kind: String,
+        /// The Cargo feature which provides the kind.
+        feature: &'static str,
+    },
+
+    /// The configuration doesn't match what the deserializer expects, for
+    /// example because a field is missing, unknown, or of the wrong type.
+    #[error(transparent)]
+    InvalidConfig(serde_value::DeserializerError),
+}
+
+fn known_kinds(known: &[String]) -> String {
+    if known.is_empty() {
+        return String::new();
+    }
+    let known = known
+        .iter()
+        .map(|kind| format!("`{}`", kind))
+        .collect::<Vec<_>>();
+    format!(", known kinds are {}", known.join(", "))
+}
+
 #[derive(Debug, Error)]
 pub enum DeserializingConfigError {
     #[error("error deserializing appender {0}: {1}")]
@@ -319,25 +864,284 @@ pub enum DeserializingConfigError {
 
 /// A raw deserializable log4rs configuration.
 #[derive(Clone, Debug, Default, serde::Deserialize)]
-#[serde(deny_unknown_fields)]
+#[serde(try_from = "RawConfigSource")]
 pub struct RawConfig {
+    refresh_rate: Option<Duration>,
+    root: Option<Root>,
+    appenders: BTreeMap<String, AppenderConfig>,
+    loggers: BTreeMap<String, Logger>,
+    include: Vec<String>,
+}
+
+/// A `RawConfig` as written, before its `levels` table is expanded into
+/// loggers and references to its `patterns` are resolved.
+///
+/// `RawConfig` is deserialized through this with typed sections, so that an
+/// error carries the format's position. `RawConfigSections` keeps the
+/// sections as values instead, to find the errors of all of them.
+#[derive(serde::Deserialize)]
+#[serde(
+    deny_unknown_fields,
+    bound(deserialize = "R: SerdeDeserialize<'de>, A: SerdeDeserialize<'de>, \
+                         L: SerdeDeserialize<'de>, V: SerdeDeserialize<'de>")
+)]
+struct RawConfigSource<R = Root, A = AppenderConfig, L = Logger, V = RawLevel> {
     #[serde(deserialize_with = "de_duration", default)]
     refresh_rate: Option<Duration>,
 
     #[serde(default)]
-    root: Root,
+    root: Option<R>,
 
-    #[serde(default)]
-    appenders: HashMap<String, AppenderConfig>,
+    #[serde(deserialize_with = "de_unique_names", default)]
+    appenders: BTreeMap<String, A>,
+
+    #[serde(deserialize_with = "de_unique_names", default)]
+    loggers: BTreeMap<String, L>,
+
+    #[serde(deserialize_with = "de_unique_names", default)]
+    levels: BTreeMap<String, V>,
+
+    #[serde(deserialize_with = "de_unique_names", default)]
+    patterns: BTreeMap<String, String>,
 
     #[serde(default)]
-    loggers: HashMap<String, Logger>,
+    include: Vec<String>,
 }
 
-#[derive(Debug, Error)]
-#[error("errors deserializing appenders {0:#?}")]
+/// A `RawConfigSource` whose root, appenders, loggers and levels are not yet
+/// deserialized.
+#[derive(serde::Deserialize)]
+#[serde(transparent)]
+pub(crate) struct RawConfigSections(RawConfigSource<Value, Value, Value, Value>);
+
+#[derive(serde::Deserialize)]
+#[serde(transparent)]
+struct RawLevel(#[serde(deserialize_with = "de_level")] LevelFilter);
+
+impl TryFrom<RawConfigSource> for RawConfig {
+    type Error = String;
+
+    fn try_from(source: RawConfigSource) -> Result<RawConfig, String> {
+        source.resolve().map_err(|errors| errors.join("; "))
+    }
+}
+
+impl RawConfigSource {
+    fn resolve(self) -> Result<RawConfig, Vec<String>> {
+        let patterns = &self.patterns;
+        let mut errors = vec![];
+
+        let mut appenders = self.appenders;
+        for (name, appender) in &mut appenders {
+            if let Err(e) = resolve_encoder_patterns(&mut appender.config, patterns) {
+                errors.push(format!("appender `{}`: {}", name, e));
+            }
+        }
+
+        let mut loggers = self.loggers;
+        for (name, logger) in &mut loggers {
+            if let Some(ref mut pattern) = logger.pattern {
+                match resolve_pattern(pattern, patterns) {
+                    Ok(Some(resolved)) => *pattern = resolved,
+                    Ok(None) => {}
+                    Err(e) => errors.push(format!("logger `{}`: {}", name, e)),
+                }
+            }
+        }
+
+        for (name, RawLevel(level)) in self.levels {
+            if loggers.contains_key(&name) {
+                errors.push(format!(
+                    "logger `{}` is configured in both `levels` and `loggers`",
+                    name
+                ));
+                continue;
+            }
+            loggers.insert(
+                name,
+                Logger {
+                    level,
+                    appenders: vec![],
+                    additive: logger_additive_default(),
+                    pattern: None,
+                },
+            );
+        }
+
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+
+        Ok(RawConfig {
+            refresh_rate: self.refresh_rate,
+            root: self.root,
+            appenders,
+            loggers,
+            include: self.include,
+        })
+    }
+}
+
+impl RawConfigSections {
+    /// Deserializes the sections one at a time, and returns the errors of
+    /// every one of them, along with those found once they are deserialized.
+    pub(crate) fn errors(self) -> Vec<String> {
+        let RawConfigSections(source) = self;
+        let mut errors = vec![];
+
+        let root = match source.root.map(Value::deserialize_into) {
+            Some(Ok(root)) => Some(root),
+            Some(Err(e)) => {
+                errors.push(format!("root: {}", e));
+                None
+            }
+            None => None,
+        };
+        let appenders = sections(source.appenders, "appender", &mut errors, |appender| {
+            appender.deserialize_into().map_err(|e| e.to_string())
+        });
+        let loggers = sections(source.loggers, "logger", &mut errors, |logger| {
+            logger.deserialize_into().map_err(|e| e.to_string())
+        });
+        let levels = sections(source.levels, "level of logger", &mut errors, |level| {
+            de_level(level).map(RawLevel).map_err(|e| e.to_string())
+        });
+
+        let source = RawConfigSource {
+            refresh_rate: source.refresh_rate,
+            root,
+            appenders,
+            loggers,
+            levels,
+            patterns: source.patterns,
+            include: source.include,
+        };
+        if let Err(resolve) = source.resolve() {
+            errors.extend(resolve);
+        }
+        errors
+    }
+}
+
+/// Deserializes each section of a map, recording the errors of those which
+/// fail and leaving them out.
+fn sections<T, F>(
+    sections: BTreeMap<String, Value>,
+    section: &str,
+    errors: &mut Vec<String>,
+    mut f: F,
+) -> BTreeMap<String, T>
+where
+    F: FnMut(Value) -> Result<T, String>,
+{
+    let mut deserialized = BTreeMap::new();
+    for (name, value) in sections {
+        match f(value) {
+            Ok(value) => {
+                deserialized.insert(name, value);
+            }
+            Err(e) => errors.push(format!("{} `{}`: {}", section, name, e)),
+        }
+    }
+    deserialized
+}
+
+/// Replaces the `@name` references in the pattern encoders found anywhere in
+/// a component's configuration, including those of nested appenders.
+fn resolve_encoder_patterns(
+    config: &mut Value,
+    patterns: &BTreeMap<String, String>,
+) -> Result<(), String> {
+    match *config {
+        Value::Map(ref mut map) => {
+            if let Some(Value::Map(encoder)) = map.get_mut(&Value::String("encoder".to_owned())) {
+                let is_pattern = match encoder.get(&Value::String("kind".to_owned())) {
+                    Some(Value::String(kind)) => kind == "pattern",
+                    Some(_) => false,
+                    None => true,
+                };
+                if is_pattern {
+                    if let Some(Value::String(pattern)) =
+                        encoder.get_mut(&Value::String("pattern".to_owned()))
+                    {
+                        if let Some(resolved) = resolve_pattern(pattern, patterns)? {
+                            *pattern = resolved;
+                        }
+                    }
+                }
+            }
+            for value in map.values_mut() {
+                resolve_encoder_patterns(value, patterns)?;
+            }
+        }
+        Value::Seq(ref mut seq) => {
+            for value in seq {
+                resolve_encoder_patterns(value, patterns)?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Resolves a pattern of the form `@name` to the named pattern, or `@@...`
+/// to the literal `@...`. Returns `None` for any other pattern.
+fn resolve_pattern(
+    pattern: &str,
+    patterns: &BTreeMap<String, String>,
+) -> Result<Option<String>, String> {
+    let name = match pattern.strip_prefix('@') {
+        Some(name) => name,
+        None => return Ok(None),
+    };
+    if name.starts_with('@') {
+        return Ok(Some(name.to_owned()));
+    }
+    match patterns.get(name) {
+        Some(pattern) => Ok(Some(pattern.clone())),
+        None if patterns.is_empty() => Err(format!(
+            "unknown pattern `{}`, no patterns are defined",
+            name
+        )),
+        None => {
+            let known = patterns
+                .keys()
+                .map(|name| format!("`{}`", name))
+                .collect::<Vec<_>>();
+            Err(format!(
+                "unknown pattern `{}`, known patterns are {}",
+                name,
+                known.join(", ")
+            ))
+        }
+    }
+}
+
+#[derive(Debug)]
 pub struct AppenderErrors(Vec<DeserializingConfigError>);
 
+impl fmt::Display for AppenderErrors {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.write_str("errors deserializing appenders: ")?;
+        for (i, error) in self.0.iter().enumerate() {
+            if i > 0 {
+                fmt.write_str("; ")?;
+            }
+            write!(fmt, "{}", error)?;
+        }
+        Ok(())
+    }
+}
+
+impl error::Error for AppenderErrors {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self.0 {
+            [ref error] => Some(error),
+            _ => None,
+        }
+    }
+}
+
 impl AppenderErrors {
     pub fn is_empty(&self) -> bool {
         self.0.is_empty()
@@ -352,9 +1156,119 @@ impl AppenderErrors {
 impl RawConfig {
     /// Returns the root.
     pub fn root(&self) -> config::Root {
+        let root = self.root.clone().unwrap_or_default();
         config::Root::builder()
-            .appenders(self.root.appenders.clone())
-            .build(self.root.level)
+            .appenders(root.appenders)
+            .build(root.level)
+    }
+
+    /// Merges a later configuration fragment into this one.
+    ///
+    /// Appenders and loggers of `other` are added, replacing whole entries of
+    /// the same name here rather than merging their fields. Its root and
+    /// refresh rate replace those here if it sets them. This lets a shared
+    /// base configuration be layered with per-service overrides.
+    pub fn merge(&mut self, other: RawConfig) {
+        if other.refresh_rate.is_some() {
+            self.refresh_rate = other.refresh_rate;
+        }
+        if other.root.is_some() {
+            self.root = other.root;
+        }
+        self.appenders.extend(other.appenders);
+        self.loggers.extend(other.loggers);
+    }
+
+    /// Removes and returns the paths of the files this configuration
+    /// includes.
+    pub(crate) fn take_includes(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.include)
+    }
+
+    /// Returns the names of the appenders which neither the root nor any
+    /// logger is attached to, in sorted order.
+    ///
+    /// Such appenders never receive events, which is usually a mistake, such
+    /// as forgetting to list the appenders of the root.
+    pub fn unattached_appenders(&self) -> Vec<&str> {
+        let root = self.root.iter().flat_map(|root| &root.appenders);
+        let loggers = self.loggers.values().flat_map(|logger| &logger.appenders);
+        let attached = root.chain(loggers).collect::<HashSet<_>>();
+        self.appenders
+            .keys()
+            .filter(|name| !attached.contains(name))
+            .map(|name| &**name)
+            .collect()
+    }
+
+    /// Overrides logger levels according to `env_logger`-style directives,
+    /// as described by `Deserializers::set_level_env`.
+    ///
+    /// Malformed directives are reported through the error handler as
+    /// `Warning`s.
+    pub(crate) fn override_levels(&mut self, directives: &str) {
+        for directive in directives.split(',').map(str::trim) {
+            let (target, level) = match directive.split_once('=') {
+                Some((target, level)) => (Some(target.trim()), level.trim()),
+                None => (None, directive),
+            };
+            let level = match parse_level(level) {
+                Some(level) if target != Some("") => level,
+                _ if directive.is_empty() => continue,
+                _ => {
+                    crate::handle_error(
+                        &Warning(format!("ignoring malformed level override `{}`", directive))
+                            .into(),
+                    );
+                    continue;
+                }
+            };
+            match target {
+                Some(target) => {
+                    self.loggers
+                        .entry(target.to_owned())
+                        .or_insert_with(|| Logger {
+                            level,
+                            appenders: vec![],
+                            additive: logger_additive_default(),
+                            pattern: None,
+                        })
+                        .level = level
+                }
+                None => self.root.get_or_insert_with(Root::default).level = level,
+            }
+        }
+    }
+
+    /// Deals with the unattached appenders as set by
+    /// `Deserializers::set_unattached_appenders`.
+    pub(crate) fn handle_unattached_appenders(&mut self, deserializers: &Deserializers) {
+        match deserializers.unattached_appenders() {
+            UnattachedAppenders::Warn => {}
+            UnattachedAppenders::Ignore => return,
+            UnattachedAppenders::AttachToRoot => {
+                let root = self.root.get_or_insert_with(Root::default);
+                if root.appenders.is_empty() {
+                    root.appenders = self.appenders.keys().cloned().collect();
+                }
+            }
+        }
+        self.warn_unattached_appenders();
+    }
+
+    /// Reports each unattached appender through the error handler as a
+    /// `Warning`.
+    fn warn_unattached_appenders(&self) {
+        for name in self.unattached_appenders() {
+            crate::handle_error(
+                &Warning(format!(
+                    "appender `{}` is not attached to the root or any logger, so it receives no \
+                     events",
+                    name
+                ))
+                .into(),
+            );
+        }
     }
 
     /// Returns the loggers.
@@ -362,8 +1276,14 @@ impl RawConfig {
         self.loggers
             .iter()
             .map(|(name, logger)| {
+                let appenders = logger.appenders.iter().map(|appender| {
+                    match (&logger.pattern, self.appenders.get(appender)) {
+                        (Some(_), Some(_)) => derived_name(appender, name),
+                        _ => appender.clone(),
+                    }
+                });
                 config::Logger::builder()
-                    .appenders(logger.appenders.clone())
+                    .appenders(appenders)
                     .additive(logger.additive)
                     .build(name.clone(), logger.level)
             })
@@ -373,37 +1293,123 @@ impl RawConfig {
     /// Returns the appenders.
     ///
     /// Any components which fail to be deserialized will be ignored.
+    ///
+    /// Appenders are built, and their errors reported, in order of name, so
+    /// the result is the same on every run. The appenders derived for loggers
+    /// with a `pattern` follow, in order of their own names.
     pub fn appenders_lossy(
         &self,
         deserializers: &Deserializers,
     ) -> (Vec<config::Appender>, AppenderErrors) {
         let mut appenders = vec![];
+        let mut derived_appenders = vec![];
         let mut errors = vec![];
 
+        let derived = self.derived_appenders();
         for (name, appender) in &self.appenders {
-            let mut builder = config::Appender::builder();
-            for filter in &appender.filters {
-                match deserializers.deserialize(&filter.kind, filter.config.clone()) {
-                    Ok(filter) => builder = builder.filter(filter),
-                    Err(e) => errors.push(DeserializingConfigError::Filter(name.clone(), e)),
+            let builder = appender_builder(name, appender, deserializers, &mut errors);
+            let base = match deserializers.deserialize(&appender.kind, appender.config.clone()) {
+                Ok(base) => base,
+                Err(e) => {
+                    errors.push(DeserializingConfigError::Appender(name.clone(), e));
+                    continue;
+                }
+            };
+            let derived = match derived.get(name.as_str()) {
+                Some(derived) => derived,
+                None => {
+                    appenders.push(builder.build(name.clone(), base));
+                    continue;
+                }
+            };
+
+            let base = Arc::<dyn append::Append>::from(base);
+            appenders.push(builder.build(name.clone(), Box::new(SharedAppender(base.clone()))));
+            let supported = deserializers.supports_encoder_override(&appender.kind);
+            for (derived_name, pattern) in derived {
+                let builder = appender_builder(derived_name, appender, deserializers, &mut errors);
+                if !supported {
+                    // the logger keeps its appender, without its pattern
+                    errors.push(DeserializingConfigError::Appender(
+                        derived_name.clone(),
+                        anyhow::anyhow!(
+                            "logger patterns are not supported by appenders of kind `{}`",
+                            appender.kind
+                        ),
+                    ));
+                    derived_appenders.push(
+                        builder.build(derived_name.clone(), Box::new(SharedAppender(base.clone()))),
+                    );
+                    continue;
+                }
+                let mut config = BTreeMap::new();
+                config.insert(
+                    Value::String("pattern".to_owned()),
+                    Value::String((*pattern).to_owned()),
+                );
+                match deserializers.deserialize("pattern", Value::Map(config)) {
+                    Ok(encoder) => derived_appenders.push(builder.build(
+                        derived_name.clone(),
+                        Box::new(PatternAppender {
+                            base: base.clone(),
+                            encoder,
+                        }),
+                    )),
+                    Err(e) => {
+                        errors.push(DeserializingConfigError::Appender(derived_name.clone(), e))
+                    }
                 }
-            }
-            match deserializers.deserialize(&appender.kind, appender.config.clone()) {
-                Ok(appender) => appenders.push(builder.build(name.clone(), appender)),
-                Err(e) => errors.push(DeserializingConfigError::Appender(name.clone(), e)),
             }
         }
+        derived_appenders.sort_by(|a, b| a.name().cmp(b.name()));
+        appenders.extend(derived_appenders);
 
         (appenders, AppenderErrors(errors))
     }
 
+    /// Returns the names and patterns of the appenders derived for loggers
+    /// with a `pattern`, by the name of the appender they share.
+    fn derived_appenders(&self) -> BTreeMap<&str, Vec<(String, &str)>> {
+        let mut derived = BTreeMap::<_, Vec<_>>::new();
+        for (logger_name, logger) in &self.loggers {
+            let pattern = match logger.pattern {
+                Some(ref pattern) => pattern,
+                None => continue,
+            };
+            for name in &logger.appenders {
+                if !self.appenders.contains_key(name) {
+                    continue;
+                }
+                derived
+                    .entry(name.as_str())
+                    .or_default()
+                    .push((derived_name(name, logger_name), pattern.as_str()));
+            }
+        }
+        derived
+    }
+
     /// Returns the requested refresh rate.
     pub fn refresh_rate(&self) -> Option<Duration> {
         self.refresh_rate
     }
 }
 
-fn de_duration<'de, D>(d: D) -> Result<Option<Duration>, D::Error>
+/// The filter set by an appender's `level` key.
+#[derive(Debug)]
+struct AppenderLevel(LevelFilter);
+
+impl Filter for AppenderLevel {
+    fn filter(&self, record: &log::Record) -> filter::Response {
+        if record.level() > self.0 {
+            filter::Response::Reject
+        } else {
+            filter::Response::Neutral
+        }
+    }
+}
+
+pub(crate) fn de_duration<'de, D>(d: D) -> Result<Option<Duration>, D::Error>
 where
     D: de::Deserializer<'de>,
 {
@@ -429,6 +1435,24 @@ where
                 {
                     humantime::parse_duration(v).map(S).map_err(E::custom)
                 }
+
+                // A bare number is a count of seconds.
+                fn visit_u64<E>(self, v: u64) -> Result<S, E>
+                where
+                    E: de::Error,
+                {
+                    Ok(S(Duration::from_secs(v)))
+                }
+
+                fn visit_i64<E>(self, v: i64) -> Result<S, E>
+                where
+                    E: de::Error,
+                {
+                    if v < 0 {
+                        return Err(E::custom(format!("negative duration `{}`", v)));
+                    }
+                    self.visit_u64(v as u64)
+                }
             }
 
             d.deserialize_any(V)
@@ -438,11 +1462,92 @@ where
     Option::<S>::deserialize(d).map(|r| r.map(|s| s.0))
 }
 
+/// Parses a level name, ignoring case and accepting the spellings common in
+/// other logging frameworks.
+fn parse_level(s: &str) -> Option<LevelFilter> {
+    let level = match &*s.to_ascii_lowercase() {
+        "off" | "none" => LevelFilter::Off,
+        "error" | "err" | "fatal" | "critical" => LevelFilter::Error,
+        "warn" | "warning" => LevelFilter::Warn,
+        "info" | "information" => LevelFilter::Info,
+        "debug" => LevelFilter::Debug,
+        "trace" | "all" => LevelFilter::Trace,
+        _ => return None,
+    };
+    Some(level)
+}
+
+pub(crate) fn de_level<'de, D>(d: D) -> Result<LevelFilter, D::Error>
+where
+    D: de::Deserializer<'de>,
+{
+    struct V;
+
+    impl<'de> de::Visitor<'de> for V {
+        type Value = LevelFilter;
+
+        fn expecting(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+            fmt.write_str("a log level")
+        }
+
+        fn visit_str<E>(self, v: &str) -> Result<LevelFilter, E>
+        where
+            E: de::Error,
+        {
+            parse_level(v).ok_or_else(|| {
+                E::custom(format!(
+                    "unknown level `{}`, expected one of `off`, `error`, `warn`, `info`, \
+                     `debug` or `trace`",
+                    v
+                ))
+            })
+        }
+    }
+
+    d.deserialize_str(V)
+}
+
+fn de_unique_names<'de, D, T>(d: D) -> Result<BTreeMap<String, T>, D::Error>
+where
+    D: de::Deserializer<'de>,
+    T: SerdeDeserialize<'de>,
+{
+    struct V<T>(PhantomData<T>);
+
+    impl<'de, T> de::Visitor<'de> for V<T>
+    where
+        T: SerdeDeserialize<'de>,
+    {
+        type Value = BTreeMap<String, T>;
+
+        fn expecting(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+            fmt.write_str("a map")
+        }
+
+        fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+        where
+            A: de::MapAccess<'de>,
+        {
+            let mut out = BTreeMap::new();
+            while let Some(name) = map.next_key::<String>()? {
+                if out.contains_key(&name) {
+                    return Err(de::Error::custom(format!("duplicate name `{}`", name)));
+                }
+                let value = map.next_value()?;
+                out.insert(name, value);
+            }
+            Ok(out)
+        }
+    }
+
+    d.deserialize_map(V(PhantomData))
+}
+
 #[derive(Clone, Debug, Derivative, serde::Deserialize)]
 #[derivative(Default)]
 #[serde(deny_unknown_fields)]
 struct Root {
-    #[serde(default = "root_level_default")]
+    #[serde(deserialize_with = "de_level", default = "root_level_default")]
     #[derivative(Default(value = "root_level_default()"))]
     level: LevelFilter,
     #[serde(default)]
@@ -456,11 +1561,84 @@ fn root_level_default() -> LevelFilter {
 #[derive(serde::Deserialize, Debug, Clone)]
 #[serde(deny_unknown_fields)]
 struct Logger {
+    #[serde(deserialize_with = "de_level")]
     level: LevelFilter,
     #[serde(default)]
     appenders: Vec<String>,
     #[serde(default = "logger_additive_default")]
     additive: bool,
+    #[serde(default)]
+    pattern: Option<String>,
+}
+
+fn derived_name(appender: &str, logger: &str) -> String {
+    format!("{}@{}", appender, logger)
+}
+
+/// Returns a builder for an appender with the filters of `config`.
+fn appender_builder(
+    name: &str,
+    config: &AppenderConfig,
+    deserializers: &Deserializers,
+    errors: &mut Vec<DeserializingConfigError>,
+) -> config::runtime::AppenderBuilder {
+    let mut builder = config::Appender::builder();
+    if let Some(level) = config.level {
+        builder = builder.filter(Box::new(AppenderLevel(level)));
+    }
+    for filter in &config.filters {
+        match deserializers.deserialize(&filter.kind, filter.config.clone()) {
+            Ok(filter) => builder = builder.filter(filter),
+            Err(e) => errors.push(DeserializingConfigError::Filter(name.to_owned(), e)),
+        }
+    }
+    builder
+}
+
+/// An appender shared with the appenders derived for loggers with a
+/// `pattern`.
+struct SharedAppender(Arc<dyn append::Append>);
+
+impl fmt::Debug for SharedAppender {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        self.0.fmt(fmt)
+    }
+}
+
+impl append::Append for SharedAppender {
+    fn append(&self, record: &log::Record) -> anyhow::Result<()> {
+        self.0.append(record)
+    }
+
+    fn append_with_encoder(
+        &self,
+        record: &log::Record,
+        encoder: &dyn encode::Encode,
+    ) -> anyhow::Result<()> {
+        self.0.append_with_encoder(record, encoder)
+    }
+
+    fn flush(&self) {
+        self.0.flush()
+    }
+}
+
+/// The appender derived for a logger with a `pattern`, which writes to the
+/// shared appender with the logger's pattern.
+#[derive(Debug)]
+struct PatternAppender {
+    base: Arc<dyn append::Append>,
+    encoder: Box<dyn encode::Encode>,
+}
+
+impl append::Append for PatternAppender {
+    fn append(&self, record: &log::Record) -> anyhow::Result<()> {
+        self.base.append_with_encoder(record, &*self.encoder)
+    }
+
+    fn flush(&self) {
+        self.base.flush()
+    }
 }
 
 fn logger_additive_default() -> bool {
@@ -521,7 +1699,7 @@ loggers:
     const LINE_ENDING: &'static str = "\r\n";
     #[cfg(not(windows))]
     #[allow(dead_code)]
-    const LINE_ENDING: &'static str = "\n";
+    const LINE_ENDING: &str = "\n";
 
     #[test]
     #[cfg(feature = "yaml_format")]
@@ -541,4 +1719,809 @@ loggers:
         assert!(config.is_ok());
         assert!(config::create_raw_config(config.unwrap()).is_ok());
     }
+
+    #[test]
+    #[cfg(all(feature = "yaml_format", feature = "file_appender"))]
+    fn yaml_aliases() {
+        let dir = tempfile::tempdir().unwrap();
+        let cfg = format!(
+            r#"
+appenders:
+    foo:
+        kind: file
+        path: {0}/foo.log
+        encoder: &encoder
+            pattern: "{{m}}"
+    bar:
+        kind: file
+        path: {0}/bar.log
+        encoder: *encoder
+"#,
+            dir.path().display()
+        );
+        let config = ::serde_yaml::from_str::<RawConfig>(&cfg).unwrap();
+        let encoder = |name: &str| match config.appenders[name].config {
+            Value::Map(ref map) => map[&Value::String("encoder".to_owned())].clone(),
+            ref other => panic!("unexpected config {:?}", other),
+        };
+        assert_eq!(encoder("foo"), encoder("bar"));
+
+        let (appenders, errors) = config.appenders_lossy(&Deserializers::new());
+        assert!(errors.is_empty(), "{:?}", errors);
+        assert_eq!(appenders.len(), 2);
+    }
+
+    #[test]
+    #[cfg(feature = "yaml_format")]
+    fn duplicate_appender_names() {
+        let cfg = "
+appenders:
+    foo:
+        kind: console
+    foo:
+        kind: console
+";
+        let err = ::serde_yaml::from_str::<RawConfig>(cfg).unwrap_err();
+        assert!(err.to_string().contains("duplicate name `foo`"), "{}", err);
+    }
+
+    #[test]
+    #[cfg(feature = "yaml_format")]
+    fn appender_errors_display() {
+        use std::error::Error;
+
+        let cfg = "
+appenders:
+    foo:
+        kind: nonexistent
+";
+        let config = ::serde_yaml::from_str::<RawConfig>(cfg).unwrap();
+        let errors = config.appenders_lossy(&Deserializers::empty()).1;
+        assert_eq!(
+            errors.to_string(),
+            "errors deserializing appenders: error deserializing appender foo: \
+             no appender deserializer for kind `nonexistent` registered"
+        );
+        assert!(errors.source().is_some());
+    }
+
+    #[test]
+    #[cfg(feature = "console_appender")]
+    fn kind_aliases() {
+        let mut deserializers = Deserializers::empty();
+        deserializers.insert("console", append::console::ConsoleAppenderDeserializer);
+        assert!(deserializers.add_appender_alias("ConsoleAppender", "console"));
+        assert!(!deserializers.add_appender_alias("Nope", "nonexistent"));
+
+        let config = Value::Map(Default::default());
+        assert!(deserializers
+            .deserialize::<dyn crate::append::Append>("ConsoleAppender", config.clone())
+            .is_ok());
+        let err = deserializers
+            .deserialize::<dyn crate::append::Append>("Nope", config)
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "no appender deserializer for kind `Nope` registered, \
+             known kinds are `ConsoleAppender`, `console`"
+        );
+    }
+
+    #[test]
+    #[cfg(all(feature = "console_appender", feature = "file_appender"))]
+    fn unknown_kind_lists_known_kinds() {
+        let err = Deserializers::new()
+            .deserialize::<dyn crate::append::Append>("consol", Value::Unit)
+            .unwrap_err()
+            .to_string();
+        assert!(
+            err.starts_with(
+                "no appender deserializer for kind `consol` registered, known kinds are "
+            ),
+            "{}",
+            err
+        );
+        assert!(
+            err.contains("`console`") && err.contains("`file`"),
+            "{}",
+            err
+        );
+        assert!(!err.contains("`pattern`"), "{}", err);
+    }
+
+    #[test]
+    fn level_aliases() {
+        let levels = [
+            ("off", LevelFilter::Off),
+            ("None", LevelFilter::Off),
+            ("ERROR", LevelFilter::Error),
+            ("err", LevelFilter::Error),
+            ("Fatal", LevelFilter::Error),
+            ("critical", LevelFilter::Error),
+            ("WARN", LevelFilter::Warn),
+            ("Warning", LevelFilter::Warn),
+            ("info", LevelFilter::Info),
+            ("Information", LevelFilter::Info),
+            ("Debug", LevelFilter::Debug),
+            ("trace", LevelFilter::Trace),
+            ("ALL", LevelFilter::Trace),
+        ];
+        for (name, level) in levels {
+            assert_eq!(parse_level(name), Some(level), "{}", name);
+        }
+        assert_eq!(parse_level("verbose"), None);
+    }
+
+    #[test]
+    #[cfg(feature = "yaml_format")]
+    fn unknown_level() {
+        let cfg = "
+root:
+    level: Warning
+loggers:
+    app:
+        level: verbose
+";
+        let err = ::serde_yaml::from_str::<RawConfig>(cfg).unwrap_err();
+        assert!(
+            err.to_string().contains("unknown level `verbose`"),
+            "{}",
+            err
+        );
+
+        let config = ::serde_yaml::from_str::<RawConfig>("root:\n    level: Warning").unwrap();
+        assert_eq!(config.root().level(), LevelFilter::Warn);
+    }
+
+    #[test]
+    #[cfg(all(
+        feature = "yaml_format",
+        feature = "console_appender",
+        feature = "file_appender"
+    ))]
+    fn unknown_appender_keys() {
+        let dir = tempfile::tempdir().unwrap();
+        let cfg = format!(
+            "
+appenders:
+    console:
+        kind: console
+        taget: stderr
+    file:
+        kind: file
+        path: {}/foo.log
+        encoder:
+            patern: '{{m}}'
+",
+            dir.path().display()
+        );
+        let config = ::serde_yaml::from_str::<RawConfig>(&cfg).unwrap();
+        let (appenders, errors) = config.appenders_lossy(&Deserializers::new());
+        assert!(appenders.is_empty());
+        let errors = errors.to_string();
+        assert!(errors.contains("Unknown field taget"), "{}", errors);
+        assert!(errors.contains("Unknown field patern"), "{}", errors);
+    }
+
+    #[test]
+    #[cfg(all(feature = "yaml_format", feature = "file_appender"))]
+    fn appender_level() {
+        use log::{Level, Log, Record};
+
+        let dir = tempfile::tempdir().unwrap();
+        let cfg = format!(
+            "
+appenders:
+    alerts:
+        kind: file
+        path: {0}/alerts.log
+        level: warn
+        encoder:
+            pattern: '{{m}};'
+root:
+    level: trace
+    appenders: [alerts]
+",
+            dir.path().display()
+        );
+        let config = ::serde_yaml::from_str::<RawConfig>(&cfg).unwrap();
+        let (appenders, errors) = config.appenders_lossy(&Deserializers::new());
+        assert!(errors.is_empty(), "{}", errors);
+        let config = config::Config::builder()
+            .appenders(appenders)
+            .build(config.root())
+            .unwrap();
+        let logger = crate::Logger::new(config);
+        for (level, message) in [(Level::Info, "started"), (Level::Error, "disk full")] {
+            logger.log(
+                &Record::builder()
+                    .level(level)
+                    .args(format_args!("{}", message))
+                    .build(),
+            );
+        }
+        logger.flush();
+
+        let alerts = fs::read_to_string(dir.path().join("alerts.log")).unwrap();
+        assert_eq!(alerts, "disk full;");
+
+        let cfg = "
+appenders:
+    alerts:
+        kind: console
+        level: loud
+";
+        let err = ::serde_yaml::from_str::<RawConfig>(cfg).unwrap_err();
+        assert!(err.to_string().contains("unknown level `loud`"), "{}", err);
+    }
+
+    #[test]
+    #[cfg(all(feature = "yaml_format", feature = "console_appender"))]
+    fn invalid_config_error() {
+        let config = ::serde_yaml::from_str::<Value>("taget: stderr").unwrap();
+        let err = Deserializers::new()
+            .deserialize::<dyn crate::append::Append>("console", config)
+            .unwrap_err();
+        match err.downcast_ref::<DeserializeError>() {
+            Some(DeserializeError::InvalidConfig(_)) => {}
+            _ => panic!("{}", err),
+        }
+    }
+
+    #[test]
+    fn missing_feature() {
+        let deserializers = Deserializers::default();
+        for &(name, kind, feature, enabled) in BUILTIN_KINDS {
+            let err = match name {
+                "appender" => deserializers
+                    .deserialize::<dyn crate::append::Append>(kind, Value::Unit)
+                    .err(),
+                "encoder" => deserializers
+                    .deserialize::<dyn crate::encode::Encode>(kind, Value::Unit)
+                    .err(),
+                "filter" => deserializers
+                    .deserialize::<dyn Filter>(kind, Value::Unit)
+                    .err(),
+                _ => continue,
+            };
+            match err
+                .as_ref()
+                .and_then(|e| e.downcast_ref::<DeserializeError>())
+            {
+                Some(DeserializeError::MissingFeature { feature: f, .. }) => {
+                    assert!(!enabled, "{} is enabled", feature);
+                    assert_eq!(*f, feature);
+                }
+                Some(DeserializeError::UnknownKind { .. }) => {
+                    panic!("{} {} should name its feature", name, kind)
+                }
+                _ => assert!(enabled, "{} {} is registered", name, kind),
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "yaml_format")]
+    fn refresh_rate_formats() {
+        let refresh_rate = |source: &str| {
+            ::serde_yaml::from_str::<RawConfig>(source).map(|config| config.refresh_rate())
+        };
+
+        assert_eq!(
+            refresh_rate("refresh_rate: 500ms").unwrap(),
+            Some(Duration::from_millis(500))
+        );
+        assert_eq!(
+            refresh_rate("refresh_rate: 5m").unwrap(),
+            Some(Duration::from_secs(300))
+        );
+        assert_eq!(
+            refresh_rate("refresh_rate: 1h 30s").unwrap(),
+            Some(Duration::from_secs(3630))
+        );
+        assert_eq!(
+            refresh_rate("refresh_rate: 30").unwrap(),
+            Some(Duration::from_secs(30))
+        );
+        assert!(refresh_rate("refresh_rate: -30").is_err());
+        assert!(refresh_rate("refresh_rate: 30 parsecs").is_err());
+    }
+
+    #[test]
+    #[cfg(all(feature = "yaml_format", feature = "file_appender"))]
+    fn default_pattern() {
+        use log::{Level, Log, Record};
+
+        let dir = tempfile::tempdir().unwrap();
+        let cfg = format!(
+            "
+appenders:
+    plain:
+        kind: file
+        path: {0}/plain.log
+    custom:
+        kind: file
+        path: {0}/custom.log
+        encoder:
+            pattern: '{{m}}'
+root:
+    appenders: [plain, custom]
+",
+            dir.path().display()
+        );
+        let config = ::serde_yaml::from_str::<RawConfig>(&cfg).unwrap();
+        let mut deserializers = Deserializers::new();
+        deserializers.set_default_pattern("{l}: {m}");
+        let (appenders, errors) = config.appenders_lossy(&deserializers);
+        assert!(errors.is_empty(), "{}", errors);
+
+        let config = config::Config::builder()
+            .appenders(appenders)
+            .build(config.root())
+            .unwrap();
+        let logger = crate::Logger::new(config);
+        logger.log(
+            &Record::builder()
+                .level(Level::Warn)
+                .args(format_args!("hello"))
+                .build(),
+        );
+        logger.flush();
+
+        let plain = fs::read_to_string(dir.path().join("plain.log")).unwrap();
+        assert_eq!(plain, "WARN: hello");
+        let custom = fs::read_to_string(dir.path().join("custom.log")).unwrap();
+        assert_eq!(custom, "hello");
+
+        deserializers.set_default_pattern("{nope}");
+        let config = ::serde_yaml::from_str::<RawConfig>(&cfg).unwrap();
+        let errors = config.appenders_lossy(&deserializers).1.to_string();
+        assert!(errors.contains("unknown formatter `nope`"), "{}", errors);
+    }
+
+    #[test]
+    #[cfg(all(feature = "yaml_format", feature = "file_appender"))]
+    fn logger_pattern() {
+        use log::{Level, Log, Record};
+
+        let dir = tempfile::tempdir().unwrap();
+        let cfg = format!(
+            "
+appenders:
+    file:
+        kind: file
+        path: {0}/app.log
+        encoder:
+            pattern: '{{l}} {{m}}{{n}}'
+root:
+    appenders: [file]
+loggers:
+    db:
+        level: info
+        appenders: [file]
+        additive: false
+        pattern: '{{t}}: {{m}}{{n}}'
+",
+            dir.path().display()
+        );
+        let config = ::serde_yaml::from_str::<RawConfig>(&cfg).unwrap();
+        let (appenders, errors) = config.appenders_lossy(&Deserializers::default());
+        assert!(errors.is_empty(), "{}", errors);
+        let names = appenders
+            .iter()
+            .map(config::Appender::name)
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["file", "file@db"]);
+
+        let config = config::Config::builder()
+            .appenders(appenders)
+            .loggers(config.loggers())
+            .build(config.root())
+            .unwrap();
+        let logger = crate::Logger::new(config);
+        for target in ["app", "db"] {
+            logger.log(
+                &Record::builder()
+                    .level(Level::Warn)
+                    .target(target)
+                    .args(format_args!("hello"))
+                    .build(),
+            );
+        }
+        logger.flush();
+
+        let log = fs::read_to_string(dir.path().join("app.log")).unwrap();
+        assert_eq!(log.lines().collect::<Vec<_>>(), ["WARN hello", "db: hello"]);
+
+        let cfg = cfg.replace("'{t}: {m}{n}'", "'{nope}'");
+        let config = ::serde_yaml::from_str::<RawConfig>(&cfg).unwrap();
+        let errors = config
+            .appenders_lossy(&Deserializers::default())
+            .1
+            .to_string();
+        assert!(errors.contains("file@db"), "{}", errors);
+        assert!(errors.contains("unknown formatter `nope`"), "{}", errors);
+    }
+
+    #[test]
+    #[cfg(all(
+        feature = "yaml_format",
+        feature = "file_appender",
+        feature = "async_appender",
+        feature = "console_appender"
+    ))]
+    fn logger_pattern_shares_appender() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        use log::{Level, Log, Record};
+
+        use crate::append::file::{FileAppenderConfig, FileAppenderDeserializer};
+
+        struct Counting(Arc<AtomicUsize>);
+
+        impl Deserialize for Counting {
+            type Trait = dyn crate::append::Append;
+
+            type Config = FileAppenderConfig;
+
+            fn deserialize(
+                &self,
+                config: FileAppenderConfig,
+                deserializers: &Deserializers,
+            ) -> anyhow::Result<Box<dyn crate::append::Append>> {
+                self.0.fetch_add(1, Ordering::SeqCst);
+                FileAppenderDeserializer.deserialize(config, deserializers)
+            }
+
+            fn supports_encoder_override(&self) -> bool {
+                true
+            }
+        }
+
+        let dir = tempfile::tempdir().unwrap();
+        let cfg = format!(
+            "
+appenders:
+    file:
+        kind: file
+        path: {0}/app.log
+        auto_flush: false
+        encoder:
+            pattern: '{{m}}{{n}}'
+    queue:
+        kind: async
+        appender:
+            kind: console
+root:
+    appenders: [file]
+loggers:
+    db:
+        level: info
+        appenders: [file, queue]
+        pattern: 'db: {{m}}{{n}}'
+",
+            dir.path().display()
+        );
+        let config = ::serde_yaml::from_str::<RawConfig>(&cfg).unwrap();
+        let opened = Arc::new(AtomicUsize::new(0));
+        let mut deserializers = Deserializers::default();
+        deserializers.insert("file", Counting(opened.clone()));
+        let (appenders, errors) = config.appenders_lossy(&deserializers);
+        assert_eq!(opened.load(Ordering::SeqCst), 1);
+        assert_eq!(
+            errors.to_string(),
+            "errors deserializing appenders: error deserializing appender queue@db: \
+             logger patterns are not supported by appenders of kind `async`"
+        );
+        let names = appenders
+            .iter()
+            .map(config::Appender::name)
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["file", "queue", "file@db", "queue@db"]);
+
+        let loggers = config.loggers();
+        assert_eq!(loggers[0].appenders(), ["file@db", "queue@db"]);
+        let config = config::Config::builder()
+            .appenders(appenders)
+            .loggers(loggers)
+            .build(config.root())
+            .unwrap();
+        let logger = crate::Logger::new(config);
+        for (target, message) in [("app", "one"), ("db", "two"), ("app", "three")] {
+            logger.log(
+                &Record::builder()
+                    .level(Level::Warn)
+                    .target(target)
+                    .args(format_args!("{}", message))
+                    .build(),
+            );
+        }
+        logger.flush();
+
+        let log = fs::read_to_string(dir.path().join("app.log")).unwrap();
+        assert_eq!(
+            log.lines().collect::<Vec<_>>(),
+            ["one", "db: two", "two", "three"]
+        );
+    }
+
+    #[test]
+    #[cfg(all(feature = "yaml_format", feature = "file_appender"))]
+    fn logger_pattern_aliased_kind() {
+        let dir = tempfile::tempdir().unwrap();
+        let cfg = format!(
+            "
+appenders:
+    file:
+        kind: FileAppender
+        path: {0}/app.log
+loggers:
+    db:
+        level: info
+        appenders: [file]
+        pattern: 'db: {{m}}{{n}}'
+",
+            dir.path().display()
+        );
+        let config = ::serde_yaml::from_str::<RawConfig>(&cfg).unwrap();
+        let mut deserializers = Deserializers::default();
+        assert!(deserializers.add_appender_alias("FileAppender", "file"));
+        let (appenders, errors) = config.appenders_lossy(&deserializers);
+        assert!(errors.is_empty(), "{}", errors);
+        let names = appenders
+            .iter()
+            .map(config::Appender::name)
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["file", "file@db"]);
+    }
+
+    #[test]
+    #[cfg(feature = "yaml_format")]
+    fn levels_table() {
+        let cfg = "
+levels:
+    app::db: debug
+    hyper: warn
+loggers:
+    app:
+        level: info
+";
+        let config = ::serde_yaml::from_str::<RawConfig>(cfg).unwrap();
+        let loggers = config
+            .loggers()
+            .iter()
+            .map(|l| {
+                (
+                    l.name().to_owned(),
+                    l.level(),
+                    l.appenders().len(),
+                    l.additive(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            loggers,
+            [
+                ("app".to_owned(), LevelFilter::Info, 0, true),
+                ("app::db".to_owned(), LevelFilter::Debug, 0, true),
+                ("hyper".to_owned(), LevelFilter::Warn, 0, true),
+            ]
+        );
+
+        let err = ::serde_yaml::from_str::<RawConfig>(&format!("{}levels:\n    app: warn\n", cfg))
+            .unwrap_err();
+        assert!(err.to_string().contains("duplicate"), "{}", err);
+        let err = ::serde_yaml::from_str::<RawConfig>(&cfg.replace("hyper", "app")).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("logger `app` is configured in both `levels` and `loggers`"),
+            "{}",
+            err
+        );
+        let err = ::serde_yaml::from_str::<RawConfig>("levels:\n    app: loud\n").unwrap_err();
+        assert!(err.to_string().contains("unknown level `loud`"), "{}", err);
+    }
+
+    #[test]
+    #[cfg(feature = "toml_format")]
+    fn levels_table_toml() {
+        let cfg = r#"
+[levels]
+"app::db" = "debug"
+
+[loggers.app]
+level = "info"
+"#;
+        let config = ::toml::from_str::<RawConfig>(cfg).unwrap();
+        let names = config
+            .loggers()
+            .iter()
+            .map(|l| l.name().to_owned())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["app", "app::db"]);
+    }
+
+    #[test]
+    #[cfg(feature = "toml_format")]
+    fn named_patterns() {
+        let cfg = r#"
+[patterns]
+detailed = "{d} {l} {M}:{L} - {m}{n}"
+
+[appenders.stdout]
+kind = "console"
+encoder = { pattern = "@detailed" }
+
+[appenders.literal]
+kind = "console"
+encoder = { pattern = "@@{m}{n}" }
+
+[appenders.dedup]
+kind = "dedup"
+appender = { kind = "console", encoder = { pattern = "@detailed" } }
+
+[appenders.json]
+kind = "console"
+encoder = { kind = "json", pattern = "@detailed" }
+
+[loggers.app]
+level = "info"
+pattern = "@detailed"
+"#;
+        let config = ::toml::from_str::<RawConfig>(cfg).unwrap();
+        let pattern = |config: &Value| match config {
+            Value::Map(map) => match &map[&Value::String("encoder".to_owned())] {
+                Value::Map(encoder) => encoder[&Value::String("pattern".to_owned())].clone(),
+                _ => panic!(),
+            },
+            _ => panic!(),
+        };
+        let detailed = Value::String("{d} {l} {M}:{L} - {m}{n}".to_owned());
+        assert_eq!(pattern(&config.appenders["stdout"].config), detailed);
+        assert_eq!(
+            pattern(&config.appenders["literal"].config),
+            Value::String("@{m}{n}".to_owned())
+        );
+        match &config.appenders["dedup"].config {
+            Value::Map(map) => assert_eq!(
+                pattern(&map[&Value::String("appender".to_owned())]),
+                detailed
+            ),
+            _ => panic!(),
+        }
+        assert_eq!(
+            pattern(&config.appenders["json"].config),
+            Value::String("@detailed".to_owned())
+        );
+        assert_eq!(
+            config.loggers["app"].pattern.as_deref(),
+            Some("{d} {l} {M}:{L} - {m}{n}")
+        );
+
+        let err = ::toml::from_str::<RawConfig>(&cfg.replace("= \"@detailed\"", "= \"@brief\""))
+            .unwrap_err()
+            .to_string();
+        assert!(
+            err.contains("unknown pattern `brief`, known patterns are `detailed`"),
+            "{}",
+            err
+        );
+
+        let err = ::toml::from_str::<RawConfig>(
+            "[appenders.stdout]\nkind = \"console\"\nencoder = { pattern = \"@brief\" }\n",
+        )
+        .unwrap_err()
+        .to_string();
+        assert!(
+            err.contains("unknown pattern `brief`, no patterns are defined"),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "yaml_format")]
+    fn unattached_appenders() {
+        let cfg = "
+appenders:
+    console:
+        kind: console
+    requests:
+        kind: console
+    forgotten:
+        kind: console
+    unused:
+        kind: console
+loggers:
+    app::requests:
+        level: info
+        appenders: [requests]
+";
+        let config = ::serde_yaml::from_str::<RawConfig>(cfg).unwrap();
+        assert_eq!(
+            config.unattached_appenders(),
+            ["console", "forgotten", "unused"]
+        );
+
+        let config =
+            ::serde_yaml::from_str::<RawConfig>(&format!("{}root:\n    appenders: [console]", cfg))
+                .unwrap();
+        assert_eq!(config.unattached_appenders(), ["forgotten", "unused"]);
+
+        let mut config = ::serde_yaml::from_str::<RawConfig>(cfg).unwrap();
+        let mut deserializers = Deserializers::new();
+        deserializers.set_unattached_appenders(UnattachedAppenders::AttachToRoot);
+        config.handle_unattached_appenders(&deserializers);
+        assert_eq!(
+            config.root().appenders(),
+            ["console", "forgotten", "requests", "unused"]
+        );
+        assert!(config.unattached_appenders().is_empty());
+    }
+
+    #[test]
+    #[cfg(feature = "yaml_format")]
+    fn override_levels() {
+        let cfg = "
+root:
+    level: info
+loggers:
+    app:
+        level: info
+        appenders: [file]
+        additive: false
+";
+        let mut config = ::serde_yaml::from_str::<RawConfig>(cfg).unwrap();
+        config.override_levels("warn, app=trace,,new::module=ERROR,bogus,=info,app=loud");
+
+        assert_eq!(config.root().level(), LevelFilter::Warn);
+        let mut loggers = config.loggers();
+        loggers.sort_by(|a, b| a.name().cmp(b.name()));
+        let loggers = loggers
+            .iter()
+            .map(|l| (l.name(), l.level(), l.appenders().len(), l.additive()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            loggers,
+            [
+                ("app", LevelFilter::Trace, 1, false),
+                ("new::module", LevelFilter::Error, 0, true),
+            ]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "yaml_format")]
+    fn stable_order() {
+        let cfg = "
+appenders:
+    zeta:
+        kind: zeta
+    alpha:
+        kind: alpha
+    mid:
+        kind: mid
+loggers:
+    b:
+        level: info
+    c:
+        level: info
+    a:
+        level: info
+";
+        let config = ::serde_yaml::from_str::<RawConfig>(cfg).unwrap();
+        let errors = config
+            .appenders_lossy(&Deserializers::empty())
+            .1
+            .to_string();
+        let kinds = ["alpha", "mid", "zeta"]
+            .iter()
+            .map(|kind| errors.find(&format!("kind `{}`", kind)).unwrap())
+            .collect::<Vec<_>>();
+        assert!(kinds.windows(2).all(|w| w[0] < w[1]), "{}", errors);
+
+        let loggers = config.loggers();
+        let names = loggers.iter().map(config::Logger::name).collect::<Vec<_>>();
+        assert_eq!(names, ["a", "b", "c"]);
+    }
 }
@@ -1,7 +1,7 @@
 //! log4rs configuration
 
 use log::LevelFilter;
-use std::collections::HashSet;
+use std::{collections::HashSet, error, fmt};
 use thiserror::Error;
 
 use crate::{append::Append, filter::Filter};
@@ -43,6 +43,14 @@ impl Config {
         &self.loggers
     }
 
+    pub(crate) fn view(&self) -> ConfigView {
+        ConfigView {
+            appenders: self.appenders.iter().map(|a| a.name.clone()).collect(),
+            root: self.root.clone(),
+            loggers: self.loggers.clone(),
+        }
+    }
+
     pub(crate) fn unpack(self) -> (Vec<Appender>, Root, Vec<Logger>) {
         let Config {
             appenders,
@@ -53,6 +61,34 @@ impl Config {
     }
 }
 
+/// A read-only view of the configuration a logger is running with.
+///
+/// The appenders themselves are owned by the logger, so they are only listed
+/// by name.
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub struct ConfigView {
+    appenders: Vec<String>,
+    root: Root,
+    loggers: Vec<Logger>,
+}
+
+impl ConfigView {
+    /// Returns the names of the configured appenders.
+    pub fn appenders(&self) -> &[String] {
+        &self.appenders
+    }
+
+    /// Returns the `Root` of the configuration.
+    pub fn root(&self) -> &Root {
+        &self.root
+    }
+
+    /// Returns the `Logger`s of the configuration.
+    pub fn loggers(&self) -> &[Logger] {
+        &self.loggers
+    }
+}
+
 /// A builder for `Config`s.
 #[derive(Debug, Default)]
 pub struct ConfigBuilder {
@@ -167,7 +203,7 @@ impl ConfigBuilder {
 }
 
 /// Configuration for the root logger.
-#[derive(Debug)]
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
 pub struct Root {
     level: LevelFilter,
     appenders: Vec<String>,
@@ -420,10 +456,33 @@ fn check_logger_name(name: &str) -> Result<(), ConfigError> {
 }
 
 /// Errors encountered when validating a log4rs `Config`.
-#[derive(Debug, Error)]
-#[error("Configuration errors: {0:#?}")]
+///
+/// Displays as a single line listing each error, separated by semicolons.
+#[derive(Debug)]
 pub struct ConfigErrors(Vec<ConfigError>);
 
+impl fmt::Display for ConfigErrors {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.write_str("Configuration errors: ")?;
+        for (i, error) in self.0.iter().enumerate() {
+            if i > 0 {
+                fmt.write_str("; ")?;
+            }
+            write!(fmt, "{}", error)?;
+        }
+        Ok(())
+    }
+}
+
+impl error::Error for ConfigErrors {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self.0 {
+            [ref error] => Some(error),
+            _ => None,
+        }
+    }
+}
+
 impl ConfigErrors {
     /// There were no config errors.
     pub fn is_empty(&self) -> bool {
@@ -467,6 +526,71 @@ pub enum ConfigError {
 
 #[cfg(test)]
 mod test {
+    use log::{LevelFilter, Record};
+
+    use super::*;
+
+    #[derive(Debug)]
+    struct NopAppender;
+
+    impl Append for NopAppender {
+        fn append(&self, _: &Record) -> anyhow::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn duplicate_names() {
+        let errors = Config::builder()
+            .appender(Appender::builder().build("foo", Box::new(NopAppender)))
+            .appender(Appender::builder().build("foo", Box::new(NopAppender)))
+            .logger(Logger::builder().build("a::b", LevelFilter::Info))
+            .logger(Logger::builder().build("a::b", LevelFilter::Warn))
+            .build(Root::builder().build(LevelFilter::Info))
+            .unwrap_err();
+
+        let errors = errors.errors();
+        assert_eq!(errors.len(), 2);
+        assert!(matches!(errors[0], ConfigError::DuplicateAppenderName(ref n) if n == "foo"));
+        assert!(matches!(errors[1], ConfigError::DuplicateLoggerName(ref n) if n == "a::b"));
+        assert_eq!(errors[0].to_string(), "Duplicate appender name `foo`");
+    }
+
+    #[test]
+    fn nonexistent_appenders() {
+        let builder = || {
+            Config::builder()
+                .appender(Appender::builder().build("stdout", Box::new(NopAppender)))
+                .logger(
+                    Logger::builder()
+                        .appender("stdout")
+                        .appender("requets")
+                        .build("app", LevelFilter::Info),
+                )
+        };
+        let root = || Root::builder().appender("stdot").build(LevelFilter::Warn);
+
+        let errors = builder().build(root()).unwrap_err();
+        assert_eq!(
+            errors.to_string(),
+            "Configuration errors: Reference to nonexistent appender: `stdot`; \
+             Reference to nonexistent appender: `requets`"
+        );
+        let names = errors
+            .errors()
+            .iter()
+            .map(|e| match *e {
+                ConfigError::NonexistentAppender(ref name) => name.as_str(),
+                ref e => panic!("unexpected error {}", e),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["stdot", "requets"]);
+
+        let (config, _) = builder().build_lossy(root());
+        assert!(config.root().appenders().is_empty());
+        assert_eq!(config.loggers()[0].appenders(), ["stdout"]);
+    }
+
     #[test]
     fn check_logger_name() {
         let tests = [
@@ -24,6 +24,11 @@
 //!     }
 //! }
 //! ```
+//!
+//! The `module_path`, `file` and `line` fields make up the location, which is
+//! only present when the record carries it. The location and the `thread` and
+//! `thread_id` fields can each be left out with `JsonEncoder::location` and
+//! `JsonEncoder::thread`.
 
 use chrono::{
     format::{DelayedFormat, Fixed, Item},
@@ -42,19 +47,47 @@ use crate::encode::{Encode, Write, NEWLINE};
 #[derive(Clone, Eq, PartialEq, Hash, Debug, Default, serde::Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct JsonEncoderConfig {
-    #[serde(skip_deserializing)]
-    _p: (),
+    location: Option<bool>,
+    thread: Option<bool>,
 }
 
 /// An `Encode`r which writes a JSON object.
-#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Default)]
-pub struct JsonEncoder(());
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct JsonEncoder {
+    location: bool,
+    thread: bool,
+}
+
+impl Default for JsonEncoder {
+    fn default() -> Self {
+        JsonEncoder {
+            location: true,
+            thread: true,
+        }
+    }
+}
 
 impl JsonEncoder {
     /// Returns a new `JsonEncoder` with a default configuration.
     pub fn new() -> Self {
         Self::default()
     }
+
+    /// Determines if the `module_path`, `file` and `line` fields are written.
+    ///
+    /// Defaults to `true`.
+    pub fn location(mut self, location: bool) -> Self {
+        self.location = location;
+        self
+    }
+
+    /// Determines if the `thread` and `thread_id` fields are written.
+    ///
+    /// Defaults to `true`.
+    pub fn thread(mut self, thread: bool) -> Self {
+        self.thread = thread;
+        self
+    }
 }
 
 impl JsonEncoder {
@@ -69,12 +102,12 @@ impl JsonEncoder {
             time: time.format_with_items(Some(Item::Fixed(Fixed::RFC3339)).into_iter()),
             level: record.level(),
             message: record.args(),
-            module_path: record.module_path(),
-            file: record.file(),
-            line: record.line(),
+            module_path: record.module_path().filter(|_| self.location),
+            file: record.file().filter(|_| self.location),
+            line: record.line().filter(|_| self.location),
             target: record.target(),
-            thread: thread.name(),
-            thread_id: thread_id::get(),
+            thread: Some(thread.name()).filter(|_| self.thread),
+            thread_id: Some(thread_id::get()).filter(|_| self.thread),
             mdc: Mdc,
         };
         message.serialize(&mut serde_json::Serializer::new(&mut *w))?;
@@ -103,8 +136,10 @@ struct Message<'a> {
     #[serde(skip_serializing_if = "Option::is_none")]
     line: Option<u32>,
     target: &'a str,
-    thread: Option<&'a str>,
-    thread_id: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    thread: Option<Option<&'a str>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    thread_id: Option<usize>,
     mdc: Mdc,
 }
 
@@ -143,6 +178,12 @@ impl ser::Serialize for Mdc {
 ///
 /// ```yaml
 /// kind: json
+///
+/// # Determines if the module path, file and line are written. Defaults to `true`.
+/// location: false
+///
+/// # Determines if the thread name and id are written. Defaults to `true`.
+/// thread: false
 /// ```
 #[cfg(feature = "config_parsing")]
 #[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Default)]
@@ -156,10 +197,17 @@ impl Deserialize for JsonEncoderDeserializer {
 
     fn deserialize(
         &self,
-        _: JsonEncoderConfig,
+        config: JsonEncoderConfig,
         _: &Deserializers,
     ) -> anyhow::Result<Box<dyn Encode>> {
-        Ok(Box::<JsonEncoder>::default())
+        let mut encoder = JsonEncoder::new();
+        if let Some(location) = config.location {
+            encoder = encoder.location(location);
+        }
+        if let Some(thread) = config.thread {
+            encoder = encoder.thread(thread);
+        }
+        Ok(Box::new(encoder))
     }
 }
 
@@ -221,4 +269,35 @@ mod test {
         );
         assert_eq!(expected, String::from_utf8(buf).unwrap().trim());
     }
+
+    #[test]
+    fn without_location_or_thread() {
+        let time = DateTime::parse_from_rfc3339("2016-03-20T14:22:20.644420340-08:00")
+            .unwrap()
+            .with_timezone(&Local);
+        let encoder = JsonEncoder::new().location(false).thread(false);
+
+        let mut buf = vec![];
+        encoder
+            .encode_inner(
+                &mut SimpleWriter(&mut buf),
+                time,
+                &Record::builder()
+                    .level(Level::Info)
+                    .target("target")
+                    .module_path(Some("module_path"))
+                    .file(Some("file"))
+                    .line(Some(100))
+                    .args(format_args!("message"))
+                    .build(),
+            )
+            .unwrap();
+
+        let expected = format!(
+            "{{\"time\":\"{}\",\"level\":\"INFO\",\"message\":\"message\",\
+            \"target\":\"target\",\"mdc\":{{}}}}",
+            time.to_rfc3339(),
+        );
+        assert_eq!(expected, String::from_utf8(buf).unwrap().trim());
+    }
 }
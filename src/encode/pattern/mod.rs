@@ -29,6 +29,11 @@
 //! In addition, these characters may also be escaped by prefixing them with a
 //! `\` character. That is, `\{` will be rendered as `{`.
 //!
+//! `%` is not a special character and needs no escaping. It only has a
+//! meaning inside the argument of the `d` formatter, where `%%` renders a
+//! literal `%`. A pattern ending in an unmatched `{` or a lone `\` is
+//! rejected.
+//!
 //! # Formatters
 //!
 //! A formatter inserts a dynamic portion of text into the pattern. It may be
@@ -40,42 +45,75 @@
 //! a formatter does not accept any argument.
 //!
 //! * `d`, `date` - The current time. By default, the ISO 8601 format is used.
-//!     A custom format may be provided in the syntax accepted by `chrono`.
-//!     The timezone defaults to local, but can be specified explicitly by
-//!     passing a second argument of `utc` for UTC or `local` for local time.
-//!     * `{d}` - `2016-03-20T14:22:20.644420340-08:00`
-//!     * `{d(%Y-%m-%d %H:%M:%S)}` - `2016-03-20 14:22:20`
-//!     * `{d(%Y-%m-%d %H:%M:%S %Z)(utc)}` - `2016-03-20 22:22:20 UTC`
+//!   A custom format may be provided in the syntax accepted by `chrono`;
+//!   a format `chrono` cannot parse is treated as a pattern error.
+//!   The timezone defaults to local, but can be specified explicitly by
+//!   passing a second argument of `utc` for UTC or `local` for local time.
+//!   * `{d}` - `2016-03-20T14:22:20.644420340-08:00`, with as many digits
+//!     of the fraction of a second as the clock provides
+//!   * `{d(%Y-%m-%dT%H:%M:%S%.3f%:z)}` - `2016-03-20T14:22:20.644-08:00`,
+//!     always to the millisecond
+//!   * `{d(%H:%M:%S%.6f)}` - `14:22:20.644420`, always to the microsecond,
+//!     and `{d(%H:%M:%S%.9f)}` - `14:22:20.644420340`, to the nanosecond
+//!   * `{d(%Y-%m-%d %H:%M:%S)}` - `2016-03-20 14:22:20`
+//!   * `{d(%Y-%m-%d %H:%M:%S %Z)(utc)}` - `2016-03-20 22:22:20 UTC`
+//!   * `{d(%G-W%V day %j)}` - `2016-W11 day 080`, the ISO 8601 week-based
+//!     year and week number, and the day of the year
+//!
+//!   The time is read from the system clock for each event without rounding,
+//!   but its actual resolution depends on the platform: nanoseconds on Linux,
+//!   microseconds on macOS and 100 nanoseconds on Windows. Digits beyond the
+//!   resolution are written as zeros.
 //! * `f`, `file` - The source file that the log message came from, or `???` if
-//!     not provided.
+//!   not provided.
 //! * `h`, `highlight` - Styles its argument according to the log level. The
-//!     style is intense red for errors, red for warnings, blue for info, and
-//!     the default style for all other levels.
-//!     * `{h(the level is {l})}` -
-//!         <code style="color: red; font-weight: bold">the level is ERROR</code>
+//!   style is intense red for errors, red for warnings, blue for info, and
+//!   the default style for all other levels.
+//!   * `{h(the level is {l})}` -
+//!     <code style="color: red; font-weight: bold">the level is ERROR</code>
+//! * `H`, `hostname` - The hostname of the machine, looked up once, or
+//!   `unknown` if it can't be determined.
 //! * `D`, `debug` - Outputs its arguments ONLY in debug build.
 //! * `R`, `release` - Outputs its arguments ONLY in release build.
 //! * `l`, `level` - The log level.
+//!
+//!   With the argument `number`, the level is written as a number instead,
+//!   from 1 for `ERROR` to 5 for `TRACE`, so that it sorts by severity.
+//!   * `{l(number)}` - `2` for a warning
 //! * `L`, `line` - The line that the log message came from, or `???` if not
-//!     provided.
+//!   provided.
 //! * `m`, `message` - The log message.
 //! * `M`, `module` - The module that the log message came from, or `???` if not
-//!     provided.
+//!   provided.
 //! * `P`, `pid` - The current process id.
 //! * `i`, `tid` - The current system-wide unique thread ID.
-//! * `n` - A platform-specific newline.
-//! * `t`, `target` - The target of the log message.
-//! * `T`, `thread` - The name of the current thread.
+//! * `n` - A platform-specific newline, `\r\n` on Windows and `\n` elsewhere.
+//!   To get the same line ending on every platform, put a literal `\r\n` or
+//!   `\n` in the pattern instead. Appenders never add a newline of their own,
+//!   so a pattern without one runs records together on a single line.
+//! * `r`, `relative` - The number of milliseconds elapsed since the first
+//!   pattern encoder was created, which is usually when logging was
+//!   initialized.
+//! * `t`, `target` - The target of the log message, which names the logger it
+//!   is sent to. An optional first argument keeps only that many of the last
+//!   components of the target, which are separated by `::` unless a different
+//!   separator is passed as the second argument. A target with no more
+//!   components than that is written whole.
+//!   * `{t(2)}` - `db::connection` for the target
+//!     `my_app::services::db::connection`
+//!   * `{t(1)(.)}` - `Connection` for the target `com.example.db.Connection`
+//! * `T`, `thread` - The name of the current thread, or `unnamed` if it has
+//!   none. Pair it with `I` to tell unnamed threads apart.
 //! * `I`, `thread_id` - The pthread ID of the current thread.
 //! * `X`, `mdc` - A value from the [MDC][MDC]. The first argument specifies
-//!     the key, and the second argument specifies the default value if the
-//!     key is not present in the MDC. The second argument is optional, and
-//!     defaults to the empty string.
-//!     * `{X(user_id)}` - `123e4567-e89b-12d3-a456-426655440000`
-//!     * `{X(nonexistent_key)(no mapping)}` - `no mapping`
+//!   the key, and the second argument specifies the default value if the
+//!   key is not present in the MDC. The second argument is optional, and
+//!   defaults to the empty string.
+//!   * `{X(user_id)}` - `123e4567-e89b-12d3-a456-426655440000`
+//!   * `{X(nonexistent_key)(no mapping)}` - `no mapping`
 //! * An "unnamed" formatter simply formats its argument, applying the format
-//!     specification.
-//!     * `{({l} {m})}` - `INFO hello`
+//!   specification.
+//!   * `{({l} {m})}` - `INFO hello`
 //!
 //! # Format Specification
 //!
@@ -92,7 +130,7 @@
 //!
 //! * `<` - Left align by appending the fill character to the formatter output
 //! * `>` - Right align by prepending the fill character to the formatter
-//!     output.
+//!   output.
 //!
 //! ## Width
 //!
@@ -121,10 +159,14 @@
 //!
 //! [MDC]: https://crates.io/crates/log-mdc
 
-use chrono::{Local, Utc};
+use chrono::{
+    format::{Item, StrftimeItems},
+    Local, Utc,
+};
 use derivative::Derivative;
 use log::{Level, Record};
-use std::{default::Default, io, process, thread};
+use once_cell::sync::Lazy;
+use std::{default::Default, io, process, sync::Arc, thread, time::Instant};
 
 use crate::encode::{
     self,
@@ -142,6 +184,34 @@ thread_local!(
     static TID: usize = thread_id::get()
 );
 
+/// The instant `r` measures from, fixed when the first encoder is created.
+static START: Lazy<Instant> = Lazy::new(Instant::now);
+
+/// The hostname `H` renders, which doesn't change while the process runs.
+static HOSTNAME: Lazy<String> = Lazy::new(|| hostname().unwrap_or_else(|| "unknown".to_owned()));
+
+#[cfg(unix)]
+fn hostname() -> Option<String> {
+    let mut buf = [0u8; 256];
+    if unsafe { libc::gethostname(buf.as_mut_ptr() as *mut libc::c_char, buf.len()) } != 0 {
+        return None;
+    }
+    let len = buf.iter().position(|&b| b == 0).unwrap_or(buf.len());
+    String::from_utf8(buf[..len].to_vec())
+        .ok()
+        .filter(|name| !name.is_empty())
+}
+
+#[cfg(windows)]
+fn hostname() -> Option<String> {
+    std::env::var("COMPUTERNAME").ok()
+}
+
+#[cfg(not(any(unix, windows)))]
+fn hostname() -> Option<String> {
+    None
+}
+
 /// The pattern encoder's configuration.
 #[cfg(feature = "config_parsing")]
 #[derive(Clone, Eq, PartialEq, Hash, Debug, Default, serde::Deserialize)]
@@ -312,6 +382,22 @@ enum Chunk {
 }
 
 impl Chunk {
+    fn error(&self) -> Option<&str> {
+        match *self {
+            Chunk::Text(_) => None,
+            Chunk::Formatted { ref chunk, .. } => chunk.error(),
+            Chunk::Error(ref s) => Some(s),
+        }
+    }
+
+    #[cfg(feature = "config_parsing")]
+    fn writes_message(&self) -> bool {
+        match *self {
+            Chunk::Formatted { ref chunk, .. } => chunk.writes_message(),
+            Chunk::Text(_) | Chunk::Error(_) => false,
+        }
+    }
+
     fn encode(&self, w: &mut dyn encode::Write, record: &Record) -> io::Result<()> {
         match *self {
             Chunk::Text(ref s) => w.write_all(s.as_bytes()),
@@ -397,13 +483,9 @@ impl<'a> From<Piece<'a>> for Chunk {
                                 match *piece {
                                     Piece::Text(text) => format.push_str(text),
                                     Piece::Argument { .. } => {
-                                        format.push_str("{ERROR: unexpected formatter}");
-                                    }
-                                    Piece::Error(ref err) => {
-                                        format.push_str("{ERROR: ");
-                                        format.push_str(err);
-                                        format.push('}');
+                                        return Chunk::Error("unexpected formatter".to_owned());
                                     }
+                                    Piece::Error(ref err) => return Chunk::Error(err.clone()),
                                 }
                             }
                             format
@@ -411,6 +493,14 @@ impl<'a> From<Piece<'a>> for Chunk {
                         None => "%+".to_owned(),
                     };
 
+                    // Parse the format once here rather than on every record.
+                    let items = match parse_date_format(&format) {
+                        Some(items) => items,
+                        None => {
+                            return Chunk::Error(format!("invalid date format `{}`", format));
+                        }
+                    };
+
                     let timezone = match formatter.args.get(1) {
                         Some(arg) => {
                             if let Some(arg) = arg.first() {
@@ -430,7 +520,7 @@ impl<'a> From<Piece<'a>> for Chunk {
                     };
 
                     Chunk::Formatted {
-                        chunk: FormattedChunk::Time(format, timezone),
+                        chunk: FormattedChunk::Time(items, timezone),
                         params: parameters,
                     }
                 }
@@ -485,7 +575,25 @@ impl<'a> From<Piece<'a>> for Chunk {
                         params: parameters,
                     }
                 }
-                "l" | "level" => no_args(&formatter.args, parameters, FormattedChunk::Level),
+                "l" | "level" => {
+                    if formatter.args.len() > 1 {
+                        return Chunk::Error("expected at most one argument".to_owned());
+                    }
+
+                    let chunk = match formatter.args.first().map(|arg| &arg[..]) {
+                        None => FormattedChunk::Level,
+                        Some([Piece::Text("number")]) => FormattedChunk::LevelNumber,
+                        Some([Piece::Text(format)]) => {
+                            return Chunk::Error(format!("invalid level format `{}`", format));
+                        }
+                        Some(_) => return Chunk::Error("invalid level format".to_owned()),
+                    };
+
+                    Chunk::Formatted {
+                        chunk,
+                        params: parameters,
+                    }
+                }
                 "m" | "message" => no_args(&formatter.args, parameters, FormattedChunk::Message),
                 "M" | "module" => no_args(&formatter.args, parameters, FormattedChunk::Module),
                 "n" => no_args(&formatter.args, parameters, FormattedChunk::Newline),
@@ -495,7 +603,38 @@ impl<'a> From<Piece<'a>> for Chunk {
                 "I" | "thread_id" => no_args(&formatter.args, parameters, FormattedChunk::ThreadId),
                 "P" | "pid" => no_args(&formatter.args, parameters, FormattedChunk::ProcessId),
                 "i" | "tid" => no_args(&formatter.args, parameters, FormattedChunk::SystemThreadId),
-                "t" | "target" => no_args(&formatter.args, parameters, FormattedChunk::Target),
+                "t" | "target" => {
+                    if formatter.args.len() > 2 {
+                        return Chunk::Error("expected at most two arguments".to_owned());
+                    }
+
+                    let depth = match formatter.args.first().map(|arg| &arg[..]) {
+                        None => {
+                            return Chunk::Formatted {
+                                chunk: FormattedChunk::Target,
+                                params: parameters,
+                            }
+                        }
+                        Some([Piece::Text(depth)]) => match depth.parse::<usize>() {
+                            Ok(depth) if depth > 0 => depth,
+                            _ => return Chunk::Error(format!("invalid target depth `{}`", depth)),
+                        },
+                        Some(_) => return Chunk::Error("invalid target depth".to_owned()),
+                    };
+
+                    let separator = match formatter.args.get(1).map(|arg| &arg[..]) {
+                        None => "::",
+                        Some([Piece::Text(separator)]) => separator,
+                        Some(_) => return Chunk::Error("invalid target separator".to_owned()),
+                    };
+
+                    Chunk::Formatted {
+                        chunk: FormattedChunk::TargetTail(depth, separator.to_owned()),
+                        params: parameters,
+                    }
+                }
+                "r" | "relative" => no_args(&formatter.args, parameters, FormattedChunk::Relative),
+                "H" | "hostname" => no_args(&formatter.args, parameters, FormattedChunk::Hostname),
                 "X" | "mdc" => {
                     if formatter.args.len() > 2 {
                         return Chunk::Error("expected at most two arguments".to_owned());
@@ -560,6 +699,22 @@ impl<'a> From<Piece<'a>> for Chunk {
     }
 }
 
+/// Parses a `chrono` format string into items which don't borrow from it, or
+/// returns `None` if it is malformed.
+pub(crate) fn parse_date_format(format: &str) -> Option<Vec<Item<'static>>> {
+    StrftimeItems::new(format)
+        .map(|item| match item {
+            Item::Literal(s) => Some(Item::OwnedLiteral(s.into())),
+            Item::Space(s) => Some(Item::OwnedSpace(s.into())),
+            Item::OwnedLiteral(s) => Some(Item::OwnedLiteral(s)),
+            Item::OwnedSpace(s) => Some(Item::OwnedSpace(s)),
+            Item::Numeric(n, pad) => Some(Item::Numeric(n, pad)),
+            Item::Fixed(f) => Some(Item::Fixed(f)),
+            Item::Error => None,
+        })
+        .collect()
+}
+
 fn no_args(arg: &[Vec<Piece>], params: Parameters, chunk: FormattedChunk) -> Chunk {
     if arg.is_empty() {
         Chunk::Formatted { chunk, params }
@@ -568,6 +723,19 @@ fn no_args(arg: &[Vec<Piece>], params: Parameters, chunk: FormattedChunk) -> Chu
     }
 }
 
+/// Returns the last `depth` components of `name`, or all of it if it has no
+/// more than that.
+fn last_components<'a>(name: &'a str, depth: usize, separator: &str) -> &'a str {
+    let mut start = name.len();
+    for _ in 0..depth {
+        match name[..start].rfind(separator) {
+            Some(idx) => start = idx,
+            None => return name,
+        }
+    }
+    &name[start + separator.len()..]
+}
+
 #[derive(Clone, Eq, PartialEq, Hash, Debug)]
 enum Timezone {
     Utc,
@@ -576,8 +744,9 @@ enum Timezone {
 
 #[derive(Clone, Eq, PartialEq, Hash, Debug)]
 enum FormattedChunk {
-    Time(String, Timezone),
+    Time(Vec<Item<'static>>, Timezone),
     Level,
+    LevelNumber,
     Message,
     Module,
     File,
@@ -587,6 +756,9 @@ enum FormattedChunk {
     ProcessId,
     SystemThreadId,
     Target,
+    TargetTail(usize, String),
+    Relative,
+    Hostname,
     Newline,
     Align(Vec<Chunk>),
     Highlight(Vec<Chunk>),
@@ -596,13 +768,38 @@ enum FormattedChunk {
 }
 
 impl FormattedChunk {
+    fn error(&self) -> Option<&str> {
+        match *self {
+            FormattedChunk::Align(ref chunks)
+            | FormattedChunk::Highlight(ref chunks)
+            | FormattedChunk::Debug(ref chunks)
+            | FormattedChunk::Release(ref chunks) => chunks.iter().find_map(Chunk::error),
+            _ => None,
+        }
+    }
+
+    #[cfg(feature = "config_parsing")]
+    fn writes_message(&self) -> bool {
+        match *self {
+            FormattedChunk::Message => true,
+            FormattedChunk::Align(ref chunks)
+            | FormattedChunk::Highlight(ref chunks)
+            | FormattedChunk::Debug(ref chunks)
+            | FormattedChunk::Release(ref chunks) => chunks.iter().any(Chunk::writes_message),
+            _ => false,
+        }
+    }
+
     fn encode(&self, w: &mut dyn encode::Write, record: &Record) -> io::Result<()> {
         match *self {
-            FormattedChunk::Time(ref fmt, Timezone::Utc) => write!(w, "{}", Utc::now().format(fmt)),
-            FormattedChunk::Time(ref fmt, Timezone::Local) => {
-                write!(w, "{}", Local::now().format(fmt))
+            FormattedChunk::Time(ref items, Timezone::Utc) => {
+                write!(w, "{}", Utc::now().format_with_items(items.iter()))
+            }
+            FormattedChunk::Time(ref items, Timezone::Local) => {
+                write!(w, "{}", Local::now().format_with_items(items.iter()))
             }
             FormattedChunk::Level => write!(w, "{}", record.level()),
+            FormattedChunk::LevelNumber => write!(w, "{}", record.level() as usize),
             FormattedChunk::Message => w.write_fmt(*record.args()),
             FormattedChunk::Module => w.write_all(record.module_path().unwrap_or("???").as_bytes()),
             FormattedChunk::File => w.write_all(record.file().unwrap_or("???").as_bytes()),
@@ -613,12 +810,15 @@ impl FormattedChunk {
             FormattedChunk::Thread => {
                 w.write_all(thread::current().name().unwrap_or("unnamed").as_bytes())
             }
-            FormattedChunk::ThreadId => w.write_all(thread_id::get().to_string().as_bytes()),
-            FormattedChunk::ProcessId => w.write_all(process::id().to_string().as_bytes()),
-            FormattedChunk::SystemThreadId => {
-                TID.with(|tid| w.write_all(tid.to_string().as_bytes()))
-            }
+            FormattedChunk::ThreadId => write!(w, "{}", thread_id::get()),
+            FormattedChunk::ProcessId => write!(w, "{}", process::id()),
+            FormattedChunk::SystemThreadId => TID.with(|tid| write!(w, "{}", tid)),
             FormattedChunk::Target => w.write_all(record.target().as_bytes()),
+            FormattedChunk::TargetTail(depth, ref separator) => {
+                w.write_all(last_components(record.target(), depth, separator).as_bytes())
+            }
+            FormattedChunk::Relative => write!(w, "{}", START.elapsed().as_millis()),
+            FormattedChunk::Hostname => w.write_all(HOSTNAME.as_bytes()),
             FormattedChunk::Newline => w.write_all(NEWLINE.as_bytes()),
             FormattedChunk::Align(ref chunks) => {
                 for chunk in chunks {
@@ -671,13 +871,18 @@ impl FormattedChunk {
 }
 
 /// An `Encode`r configured via a format string.
+///
+/// The pattern is parsed once, when the encoder is created. Clones share the
+/// parsed pattern rather than copying it, so one encoder can be cloned into
+/// several appenders cheaply, and they all format identically. Encoding keeps
+/// no state, so the clones can be used from any number of threads at once.
 #[derive(Derivative)]
 #[derivative(Debug)]
 #[derive(Clone, Eq, PartialEq, Hash)]
 pub struct PatternEncoder {
     #[derivative(Debug = "ignore")]
-    chunks: Vec<Chunk>,
-    pattern: String,
+    chunks: Arc<[Chunk]>,
+    pattern: Arc<str>,
 }
 
 /// Returns a `PatternEncoder` using the default pattern of `{d} {l} {t} - {m}{n}`.
@@ -689,7 +894,7 @@ impl Default for PatternEncoder {
 
 impl Encode for PatternEncoder {
     fn encode(&self, w: &mut dyn encode::Write, record: &Record) -> anyhow::Result<()> {
-        for chunk in &self.chunks {
+        for chunk in self.chunks.iter() {
             chunk.encode(w, record)?;
         }
         Ok(())
@@ -701,11 +906,58 @@ impl PatternEncoder {
     ///
     /// The pattern string syntax is documented in the `pattern` module.
     pub fn new(pattern: &str) -> PatternEncoder {
+        Lazy::force(&START);
         PatternEncoder {
             chunks: Parser::new(pattern).map(From::from).collect(),
-            pattern: pattern.to_owned(),
+            pattern: pattern.into(),
+        }
+    }
+
+    /// Creates a `PatternEncoder` from a pattern string, failing if the
+    /// pattern is malformed.
+    ///
+    /// The error names the first malformed fragment of the pattern, or the
+    /// innermost one nested in its arguments, and the column, counted in
+    /// characters from 1, at which it starts. `new` instead renders errors
+    /// inline as `{ERROR: ...}`.
+    pub fn try_new(pattern: &str) -> anyhow::Result<PatternEncoder> {
+        Lazy::force(&START);
+        let mut parser = Parser::new(pattern);
+        let mut chunks = vec![];
+        loop {
+            let start = parser.pos();
+            let chunk = match parser.next() {
+                Some(piece) => Chunk::from(piece),
+                None => break,
+            };
+            if let Some(err) = chunk.error() {
+                let (start, end, err) = innermost_error(pattern, start, parser.pos(), err);
+                anyhow::bail!(
+                    "invalid pattern `{}`: {} at column {} (`{}`)",
+                    pattern,
+                    err,
+                    pattern[..start].chars().count() + 1,
+                    &pattern[start..end]
+                );
+            }
+            chunks.push(chunk);
+        }
+        Ok(PatternEncoder {
+            chunks: chunks.into(),
+            pattern: pattern.into(),
+        })
+    }
+}
+
+/// Narrows the malformed fragment `pattern[start..end]` down to the innermost
+/// fragment nested in its arguments which is malformed itself.
+fn innermost_error(pattern: &str, start: usize, end: usize, err: &str) -> (usize, usize, String) {
+    for (start, end, piece) in Parser::nested(pattern, start) {
+        if let Some(err) = Chunk::from(piece).error() {
+            return innermost_error(pattern, start, end, err);
         }
     }
+    (start, end, err.to_owned())
 }
 
 /// A deserializer for the `PatternEncoder`.
@@ -716,7 +968,8 @@ impl PatternEncoder {
 /// kind: pattern
 ///
 /// # The pattern to follow when formatting logs. Defaults to
-/// # "{d} {l} {t} - {m}{n}".
+/// # "{d} {l} {t} - {m}{n}". A malformed pattern fails deserialization, and
+/// # one without `{m}` is reported as a warning.
 /// pattern: "{d} {l} {t} - {m}{n}"
 /// ```
 #[cfg(feature = "config_parsing")]
@@ -732,19 +985,25 @@ impl Deserialize for PatternEncoderDeserializer {
     fn deserialize(
         &self,
         config: PatternEncoderConfig,
-        _: &Deserializers,
+        deserializers: &Deserializers,
     ) -> anyhow::Result<Box<dyn Encode>> {
         let encoder = match config.pattern {
-            Some(pattern) => PatternEncoder::new(&pattern),
+            Some(pattern) => PatternEncoder::try_new(&pattern)?,
             None => PatternEncoder::default(),
         };
+        if !encoder.chunks.iter().any(Chunk::writes_message) {
+            deserializers.warn(&format!(
+                "pattern `{}` never writes the message, add `{{m}}` to include it",
+                encoder.pattern
+            ));
+        }
         Ok(Box::new(encoder))
     }
 }
 
 #[cfg(test)]
 mod tests {
-    #[cfg(feature = "simple_writer")]
+    #[cfg(any(feature = "simple_writer", feature = "ansi_writer"))]
     use log::{Level, Record};
     #[cfg(feature = "simple_writer")]
     use std::process;
@@ -754,14 +1013,14 @@ mod tests {
     use super::{Chunk, PatternEncoder};
     #[cfg(feature = "simple_writer")]
     use crate::encode::writer::simple::SimpleWriter;
-    #[cfg(feature = "simple_writer")]
+    #[cfg(any(feature = "simple_writer", feature = "ansi_writer"))]
     use crate::encode::Encode;
 
     fn error_free(encoder: &PatternEncoder) -> bool {
-        encoder.chunks.iter().all(|c| match *c {
-            Chunk::Error(_) => false,
-            _ => true,
-        })
+        encoder
+            .chunks
+            .iter()
+            .all(|c| !matches!(*c, Chunk::Error(_)))
     }
 
     #[test]
@@ -774,6 +1033,57 @@ mod tests {
         assert!(!error_free(&PatternEncoder::new("{d(%Y-%m-%d)")));
     }
 
+    #[test]
+    fn try_new() {
+        assert!(PatternEncoder::try_new("{d} {l} {f}:{L} - {m}{n}").is_ok());
+        let err = PatternEncoder::try_new("{h({x})}").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid pattern `{h({x})}`: unknown formatter `x` at column 4 (`{x}`)"
+        );
+        assert!(PatternEncoder::try_new("{m").is_err());
+    }
+
+    #[test]
+    fn try_new_error_position() {
+        let err = PatternEncoder::try_new("{d} {l} – {q} {m}").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid pattern `{d} {l} – {q} {m}`: unknown formatter `q` at column 11 (`{q}`)"
+        );
+
+        let err = PatternEncoder::try_new("{d} {l(wide)}{n}").unwrap_err();
+        let err = err.to_string();
+        assert!(err.contains("invalid level format `wide`"), "{}", err);
+        assert!(err.ends_with("at column 5 (`{l(wide)}`)"), "{}", err);
+
+        let err = PatternEncoder::try_new("{m} }").unwrap_err();
+        assert!(err.to_string().ends_with("unmatched '}' at column 5 (`}`)"));
+
+        let err = PatternEncoder::try_new("{m} {h({d(%Q)} {l})}").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid pattern `{m} {h({d(%Q)} {l})}`: invalid date format `%Q` at column 8 (`{d(%Q)}`)"
+        );
+
+        let err = PatternEncoder::try_new("{h({l} {M}{)}").unwrap_err();
+        assert!(err.to_string().ends_with("at column 11 (`{)}`)"), "{}", err);
+    }
+
+    #[test]
+    #[cfg(feature = "simple_writer")]
+    fn missing_location() {
+        let pw = PatternEncoder::new("{f}:{L} {m}");
+        let mut buf = vec![];
+        pw.encode(
+            &mut SimpleWriter(&mut buf),
+            &Record::builder().args(format_args!("the message")).build(),
+        )
+        .unwrap();
+
+        assert_eq!(buf, &b"???:??? the message"[..]);
+    }
+
     #[test]
     #[cfg(feature = "simple_writer")]
     fn log() {
@@ -794,6 +1104,66 @@ mod tests {
         assert_eq!(buf, &b"DEBUG the message at path in file:132"[..]);
     }
 
+    #[test]
+    #[cfg(feature = "ansi_writer")]
+    fn highlight_level_only() {
+        use crate::encode::writer::ansi::AnsiWriter;
+
+        let pw = PatternEncoder::new("[{h({l})}] {m}");
+        let mut buf = vec![];
+        pw.encode(
+            &mut AnsiWriter(&mut buf),
+            &Record::builder()
+                .level(Level::Error)
+                .args(format_args!("the message"))
+                .build(),
+        )
+        .unwrap();
+
+        assert_eq!(buf, &b"[\x1b[0;31;1mERROR\x1b[0m] the message"[..]);
+    }
+
+    #[test]
+    #[cfg(feature = "simple_writer")]
+    fn highlight_without_color() {
+        let pw = PatternEncoder::new("[{h({l})}] {m}");
+        let mut buf = vec![];
+        pw.encode(
+            &mut SimpleWriter(&mut buf),
+            &Record::builder()
+                .level(Level::Error)
+                .args(format_args!("the message"))
+                .build(),
+        )
+        .unwrap();
+
+        assert_eq!(buf, &b"[ERROR] the message"[..]);
+    }
+
+    #[test]
+    #[cfg(feature = "simple_writer")]
+    fn target_and_module() {
+        let pw = PatternEncoder::new("[{t}] [{M}]");
+        let mut buf = vec![];
+        pw.encode(
+            &mut SimpleWriter(&mut buf),
+            &Record::builder()
+                .target("audit")
+                .module_path(Some("app::db"))
+                .build(),
+        )
+        .unwrap();
+        assert_eq!(buf, b"[audit] [app::db]");
+
+        let mut buf = vec![];
+        pw.encode(
+            &mut SimpleWriter(&mut buf),
+            &Record::builder().target("").build(),
+        )
+        .unwrap();
+        assert_eq!(buf, b"[] [???]");
+    }
+
     #[test]
     #[cfg(feature = "simple_writer")]
     fn unnamed_thread() {
@@ -913,6 +1283,26 @@ mod tests {
         assert_eq!(buf, b"foobar");
     }
 
+    #[test]
+    fn invalid_format_spec() {
+        assert!(error_free(&PatternEncoder::new(
+            "{l:<5} {t:>20.20} {T:*<10}"
+        )));
+        let err = |pattern| PatternEncoder::try_new(pattern).unwrap_err().to_string();
+        assert_eq!(
+            err("{l:5x}"),
+            "invalid pattern `{l:5x}`: unexpected `x` in format spec at column 1 (`{l:5x}`)"
+        );
+        assert_eq!(
+            err("{l:-5}"),
+            "invalid pattern `{l:-5}`: unexpected `-` in format spec at column 1 (`{l:-5}`)"
+        );
+        assert_eq!(
+            err("{m:.}"),
+            "invalid pattern `{m:.}`: expected a max width after '.' at column 1 (`{m:.}`)"
+        );
+    }
+
     #[test]
     #[cfg(feature = "simple_writer")]
     fn left_align_formatter() {
@@ -954,6 +1344,73 @@ mod tests {
         )));
     }
 
+    #[test]
+    fn iso_week_and_day_of_year() {
+        use chrono::{TimeZone, Utc};
+
+        assert!(PatternEncoder::try_new("{d(%G-W%V-%u %j)(utc)}").is_ok());
+        // The 1st of January 2021 was a Friday, in the last ISO week of 2020.
+        let items = super::parse_date_format("%G-W%V-%u %j").unwrap();
+        let render = |y, m, d| {
+            Utc.with_ymd_and_hms(y, m, d, 12, 0, 0)
+                .unwrap()
+                .format_with_items(items.iter())
+                .to_string()
+        };
+        assert_eq!(render(2021, 1, 1), "2020-W53-5 001");
+        assert_eq!(render(2016, 3, 20), "2016-W11-7 080");
+        assert_eq!(render(2024, 12, 31), "2025-W01-2 366");
+    }
+
+    #[test]
+    fn date_precision() {
+        use chrono::{TimeZone, Utc};
+
+        let time = Utc.with_ymd_and_hms(2016, 3, 20, 22, 22, 20).unwrap()
+            + chrono::Duration::nanoseconds(644_420_340);
+        let render = |format| {
+            time.format_with_items(super::parse_date_format(format).unwrap().iter())
+                .to_string()
+        };
+        assert_eq!(render("%+"), "2016-03-20T22:22:20.644420340+00:00");
+        assert_eq!(
+            render("%Y-%m-%dT%H:%M:%S%.3f%:z"),
+            "2016-03-20T22:22:20.644+00:00"
+        );
+        assert_eq!(render("%H:%M:%S%.6f"), "22:22:20.644420");
+        assert_eq!(render("%H:%M:%S%.9f"), "22:22:20.644420340");
+    }
+
+    #[test]
+    #[cfg(feature = "simple_writer")]
+    fn date_fraction_digits() {
+        for digits in [3, 6, 9] {
+            let pw = PatternEncoder::new(&format!("{{d(%S%.{}f)(utc)}}", digits));
+            let mut buf = vec![];
+            pw.encode(&mut SimpleWriter(&mut buf), &Record::builder().build())
+                .unwrap();
+            let time = String::from_utf8(buf).unwrap();
+            let (seconds, fraction) = time.split_once('.').unwrap();
+            assert_eq!(seconds.len(), 2, "{}", time);
+            assert_eq!(fraction.len(), digits, "{}", time);
+            assert!(fraction.bytes().all(|b| b.is_ascii_digit()), "{}", time);
+        }
+    }
+
+    #[test]
+    fn invalid_date_format() {
+        assert!(error_free(&PatternEncoder::new(
+            "{d(%Y-%m-%dT%H:%M:%S%.3f)}"
+        )));
+        assert!(!error_free(&PatternEncoder::new("{d(%Y-%Q)}")));
+        assert!(!error_free(&PatternEncoder::new("{d(%Y {m})}")));
+        let err = PatternEncoder::try_new("{d(%Y-%Q)}").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid pattern `{d(%Y-%Q)}`: invalid date format `%Y-%Q` at column 1 (`{d(%Y-%Q)}`)"
+        );
+    }
+
     #[test]
     fn timezones() {
         assert!(error_free(&PatternEncoder::new("{d(%+)(utc)}")));
@@ -961,6 +1418,20 @@ mod tests {
         assert!(!error_free(&PatternEncoder::new("{d(%+)(foo)}")));
     }
 
+    #[test]
+    #[cfg(feature = "simple_writer")]
+    fn utc_timezone() {
+        let pw = PatternEncoder::new("{d(%Z %z)(utc)}");
+
+        let mut buf = vec![];
+        pw.encode(
+            &mut SimpleWriter(&mut buf),
+            &Record::builder().args(format_args!("")).build(),
+        )
+        .unwrap();
+        assert_eq!(buf, b"UTC +0000");
+    }
+
     #[test]
     fn unescaped_parens() {
         assert!(!error_free(&PatternEncoder::new("(hi)")));
@@ -980,6 +1451,185 @@ mod tests {
         assert_eq!(buf, b"{foobar!()}");
     }
 
+    #[test]
+    #[cfg(feature = "simple_writer")]
+    fn percent_is_literal() {
+        let pw = PatternEncoder::new("{m} 100% %%");
+
+        let mut buf = vec![];
+        pw.encode(
+            &mut SimpleWriter(&mut buf),
+            &Record::builder().args(format_args!("at")).build(),
+        )
+        .unwrap();
+        assert_eq!(buf, b"at 100% %%");
+        assert!(error_free(&PatternEncoder::new("{m}%")));
+        assert!(error_free(&PatternEncoder::new("{d(%H%%)}")));
+    }
+
+    #[test]
+    fn trailing_escapes() {
+        let err = PatternEncoder::try_new("{m}\\").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid pattern `{m}\\`: unexpected '\\' at column 4 (`\\`)"
+        );
+        let err = PatternEncoder::try_new("{m}{").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid pattern `{m}{`: expected '}' at column 4 (`{`)"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "simple_writer")]
+    fn relative_time() {
+        let pw = PatternEncoder::new("{r}");
+        std::thread::sleep(std::time::Duration::from_millis(5));
+
+        let mut buf = vec![];
+        pw.encode(
+            &mut SimpleWriter(&mut buf),
+            &Record::builder().args(format_args!("")).build(),
+        )
+        .unwrap();
+        let elapsed = String::from_utf8(buf).unwrap().parse::<u64>().unwrap();
+        assert!(elapsed >= 5, "{}", elapsed);
+    }
+
+    #[test]
+    #[cfg(feature = "simple_writer")]
+    fn clones_share_chunks() {
+        let pw = PatternEncoder::new("{l} {m}");
+        let clones = (0..4).map(|_| pw.clone()).collect::<Vec<_>>();
+        assert!(clones
+            .iter()
+            .all(|clone| std::sync::Arc::ptr_eq(&clone.chunks, &pw.chunks)));
+
+        let threads = clones
+            .into_iter()
+            .map(|clone| {
+                thread::spawn(move || {
+                    let mut buf = vec![];
+                    clone
+                        .encode(
+                            &mut SimpleWriter(&mut buf),
+                            &Record::builder().args(format_args!("shared")).build(),
+                        )
+                        .unwrap();
+                    buf
+                })
+            })
+            .collect::<Vec<_>>();
+        for thread in threads {
+            assert_eq!(thread.join().unwrap(), b"INFO shared");
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "simple_writer")]
+    fn hostname() {
+        let pw = PatternEncoder::new("{H} {hostname}");
+
+        let mut buf = vec![];
+        pw.encode(&mut SimpleWriter(&mut buf), &Record::builder().build())
+            .unwrap();
+        let hostname = super::hostname().unwrap_or_else(|| "unknown".to_owned());
+        assert!(!hostname.is_empty());
+        assert_eq!(buf, format!("{0} {0}", hostname).as_bytes());
+    }
+
+    #[test]
+    #[cfg(feature = "simple_writer")]
+    fn target_depth() {
+        let render = |pattern, target| {
+            let mut buf = vec![];
+            PatternEncoder::new(pattern)
+                .encode(
+                    &mut SimpleWriter(&mut buf),
+                    &Record::builder().target(target).build(),
+                )
+                .unwrap();
+            String::from_utf8(buf).unwrap()
+        };
+
+        let target = "my_app::services::db::connection";
+        assert_eq!(render("{t(2)}", target), "db::connection");
+        assert_eq!(render("{t(1)}", target), "connection");
+        assert_eq!(render("{t(4)}", target), target);
+        assert_eq!(render("{t(10)}", target), target);
+        assert_eq!(render("{t(2)}", "main"), "main");
+        assert_eq!(
+            render("{t(2)(.)}", "com.example.db.Connection"),
+            "db.Connection"
+        );
+        assert_eq!(render("{t(2)(.)}", target), target);
+
+        for (pattern, err) in [
+            ("{t(0)}", "invalid target depth `0`"),
+            ("{t(two)}", "invalid target depth `two`"),
+            ("{t(2)()}", "invalid target separator"),
+            ("{t(1)(.)(x)}", "expected at most two arguments"),
+        ] {
+            let msg = PatternEncoder::try_new(pattern).unwrap_err().to_string();
+            assert!(msg.contains(err), "{}", msg);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "simple_writer")]
+    fn level_number() {
+        let pw = PatternEncoder::new("{l(number)} {l}");
+
+        let mut buf = vec![];
+        for level in [Level::Error, Level::Trace] {
+            pw.encode(
+                &mut SimpleWriter(&mut buf),
+                &Record::builder().level(level).build(),
+            )
+            .unwrap();
+        }
+        assert_eq!(buf, b"1 ERROR5 TRACE");
+
+        let err = PatternEncoder::try_new("{l(name)}").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid pattern `{l(name)}`: invalid level format `name` at column 1 (`{l(name)}`)"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "simple_writer")]
+    fn multi_line() {
+        let pw = PatternEncoder::new("{l}{n}  {m}{n}");
+
+        let mut buf = vec![];
+        pw.encode(
+            &mut SimpleWriter(&mut buf),
+            &Record::builder()
+                .level(Level::Warn)
+                .args(format_args!("details"))
+                .build(),
+        )
+        .unwrap();
+        let expected = format!("WARN{0}  details{0}", crate::encode::NEWLINE);
+        assert_eq!(buf, expected.as_bytes());
+    }
+
+    #[test]
+    #[cfg(feature = "simple_writer")]
+    fn literal_line_ending() {
+        let pw = PatternEncoder::new("{m}\r\n");
+
+        let mut buf = vec![];
+        pw.encode(
+            &mut SimpleWriter(&mut buf),
+            &Record::builder().args(format_args!("line")).build(),
+        )
+        .unwrap();
+        assert_eq!(buf, b"line\r\n");
+    }
+
     #[test]
     #[cfg(feature = "simple_writer")]
     fn quote_braces_with_backslash() {
@@ -45,6 +45,38 @@ impl<'a> Parser<'a> {
         }
     }
 
+    /// Returns the byte offset of the next piece.
+    pub fn pos(&mut self) -> usize {
+        match self.it.peek() {
+            Some(&(pos, _)) => pos,
+            None => self.pattern.len(),
+        }
+    }
+
+    /// Parses the argument starting at byte offset `start` again, returning
+    /// the pieces of its formatter's arguments with their byte ranges.
+    pub fn nested(pattern: &'a str, start: usize) -> Vec<(usize, usize, Piece<'a>)> {
+        let mut parser = Parser::new(pattern);
+        while parser.pos() < start {
+            parser.it.next();
+        }
+        let mut pieces = vec![];
+        if !parser.consume('{') {
+            return pieces;
+        }
+        parser.name();
+        while parser.consume('(') {
+            while !parser.consume(')') {
+                let start = parser.pos();
+                match parser.next() {
+                    Some(piece) => pieces.push((start, parser.pos(), piece)),
+                    None => return pieces,
+                }
+            }
+        }
+        pieces
+    }
+
     fn consume(&mut self, ch: char) -> bool {
         match self.it.peek() {
             Some(&(_, c)) if c == ch => {
@@ -61,9 +93,12 @@ impl<'a> Parser<'a> {
             Err(err) => return Piece::Error(err),
         };
 
-        Piece::Argument {
-            formatter,
-            parameters: self.parameters(),
+        match self.parameters() {
+            Ok(parameters) => Piece::Argument {
+                formatter,
+                parameters,
+            },
+            Err(err) => Piece::Error(err),
         }
     }
 
@@ -120,7 +155,7 @@ impl<'a> Parser<'a> {
         }
     }
 
-    fn parameters(&mut self) -> Parameters {
+    fn parameters(&mut self) -> Result<Parameters, String> {
         let mut params = Parameters {
             fill: ' ',
             align: Alignment::Left,
@@ -129,7 +164,7 @@ impl<'a> Parser<'a> {
         };
 
         if !self.consume(':') {
-            return params;
+            return Ok(params);
         }
 
         if let Some(&(_, ch)) = self.it.peek() {
@@ -153,12 +188,16 @@ impl<'a> Parser<'a> {
         }
 
         if self.consume('.') {
-            if let Some(max_width) = self.integer() {
-                params.max_width = Some(max_width);
+            match self.integer() {
+                Some(max_width) => params.max_width = Some(max_width),
+                None => return Err("expected a max width after '.'".to_owned()),
             }
         }
 
-        params
+        match self.it.peek() {
+            Some(&(_, ch)) if ch != '}' => Err(format!("unexpected `{}` in format spec", ch)),
+            _ => Ok(params),
+        }
     }
 
     fn integer(&mut self) -> Option<usize> {
@@ -209,7 +248,10 @@ impl<'a> Iterator for Parser<'a> {
                         Some(piece)
                     } else {
                         for _ in &mut self.it {}
-                        Some(Piece::Error("expected '}'".to_owned()))
+                        match piece {
+                            Piece::Error(_) => Some(piece),
+                            _ => Some(Piece::Error("expected '}'".to_owned())),
+                        }
                     }
                 }
             }
@@ -31,12 +31,18 @@ static COLOR_MODE: Lazy<ColorMode> = Lazy::new(|| {
 });
 
 /// The color output mode for a `ConsoleAppender`
-#[derive(Clone, Copy, Default)]
+///
+/// Unless set explicitly, it is taken from the `NO_COLOR`, `CLICOLOR_FORCE`
+/// and `CLICOLOR` environment variables, and is `Auto` if none are set.
+#[cfg_attr(feature = "config_parsing", derive(serde::Deserialize))]
+#[cfg_attr(feature = "config_parsing", serde(rename_all = "lowercase"))]
+#[derive(Clone, Copy, Eq, PartialEq, Hash, Debug, Default)]
 pub enum ColorMode {
     /// Print color only if the output is recognized as a console
     #[default]
     Auto,
-    /// Force color output
+    /// Force color output, for example when piping into a pager which
+    /// understands ANSI escapes. On Windows, color still requires a console.
     Always,
     /// Never print color
     Never,
@@ -51,7 +57,13 @@ impl ConsoleWriter {
     /// Returns `None` if standard out is not a console buffer on Windows, and
     /// if it is not a TTY on Unix.
     pub fn stdout() -> Option<ConsoleWriter> {
-        imp::Writer::stdout().map(ConsoleWriter)
+        ConsoleWriter::stdout_with_color_mode(*COLOR_MODE)
+    }
+
+    /// Like `stdout`, but with the color mode given rather than taken from
+    /// the environment.
+    pub fn stdout_with_color_mode(mode: ColorMode) -> Option<ConsoleWriter> {
+        imp::Writer::stdout(mode).map(ConsoleWriter)
     }
 
     /// Returns a new `ConsoleWriter` that will write to standard error.
@@ -59,11 +71,17 @@ impl ConsoleWriter {
     /// Returns `None` if standard error is not a console buffer on Windows, and
     /// if it is not a TTY on Unix.
     pub fn stderr() -> Option<ConsoleWriter> {
-        imp::Writer::stderr().map(ConsoleWriter)
+        ConsoleWriter::stderr_with_color_mode(*COLOR_MODE)
+    }
+
+    /// Like `stderr`, but with the color mode given rather than taken from
+    /// the environment.
+    pub fn stderr_with_color_mode(mode: ColorMode) -> Option<ConsoleWriter> {
+        imp::Writer::stderr(mode).map(ConsoleWriter)
     }
 
     /// Locks the console, preventing other threads from writing concurrently.
-    pub fn lock(&self) -> ConsoleWriterLock {
+    pub fn lock(&self) -> ConsoleWriterLock<'_> {
         ConsoleWriterLock(self.0.lock())
     }
 }
@@ -126,10 +144,7 @@ mod imp {
     use crate::{
         encode::{
             self,
-            writer::{
-                ansi::AnsiWriter,
-                console::{ColorMode, COLOR_MODE},
-            },
+            writer::{ansi::AnsiWriter, console::ColorMode},
             Style,
         },
         priv_io::{StdWriter, StdWriterLock},
@@ -138,9 +153,9 @@ mod imp {
     pub struct Writer(AnsiWriter<StdWriter>);
 
     impl Writer {
-        pub fn stdout() -> Option<Writer> {
+        pub fn stdout(mode: ColorMode) -> Option<Writer> {
             let writer = || Writer(AnsiWriter(StdWriter::stdout()));
-            match *COLOR_MODE {
+            match mode {
                 ColorMode::Auto => {
                     if unsafe { libc::isatty(libc::STDOUT_FILENO) } != 1 {
                         None
@@ -153,9 +168,9 @@ mod imp {
             }
         }
 
-        pub fn stderr() -> Option<Writer> {
+        pub fn stderr(mode: ColorMode) -> Option<Writer> {
             let writer = || Writer(AnsiWriter(StdWriter::stderr()));
-            match *COLOR_MODE {
+            match mode {
                 ColorMode::Auto => {
                     if unsafe { libc::isatty(libc::STDERR_FILENO) } != 1 {
                         None
@@ -168,7 +183,7 @@ mod imp {
             }
         }
 
-        pub fn lock(&self) -> WriterLock {
+        pub fn lock(&self) -> WriterLock<'_> {
             WriterLock(AnsiWriter((self.0).0.lock()))
         }
     }
@@ -237,11 +252,7 @@ mod imp {
     };
 
     use crate::{
-        encode::{
-            self,
-            writer::console::{ColorMode, COLOR_MODE},
-            Color, Style,
-        },
+        encode::{self, writer::console::ColorMode, Color, Style},
         priv_io::{StdWriter, StdWriterLock},
     };
 
@@ -315,7 +326,7 @@ mod imp {
     }
 
     impl Writer {
-        pub fn stdout() -> Option<Writer> {
+        pub fn stdout(mode: ColorMode) -> Option<Writer> {
             unsafe {
                 let handle = processenv::GetStdHandle(winbase::STD_OUTPUT_HANDLE);
                 if handle.is_null() || handle == handleapi::INVALID_HANDLE_VALUE {
@@ -335,14 +346,14 @@ mod imp {
                     inner: StdWriter::stdout(),
                 };
 
-                match *COLOR_MODE {
+                match mode {
                     ColorMode::Auto | ColorMode::Always => Some(writer),
                     ColorMode::Never => None,
                 }
             }
         }
 
-        pub fn stderr() -> Option<Writer> {
+        pub fn stderr(mode: ColorMode) -> Option<Writer> {
             unsafe {
                 let handle = processenv::GetStdHandle(winbase::STD_ERROR_HANDLE);
                 if handle.is_null() || handle == handleapi::INVALID_HANDLE_VALUE {
@@ -362,7 +373,7 @@ mod imp {
                     inner: StdWriter::stdout(),
                 };
 
-                match *COLOR_MODE {
+                match mode {
                     ColorMode::Auto | ColorMode::Always => Some(writer),
                     ColorMode::Never => None,
                 }
@@ -0,0 +1,143 @@
+//! The level range filter.
+//!
+//! Requires the `level_range_filter` feature.
+
+use log::{LevelFilter, Record};
+
+#[cfg(feature = "config_parsing")]
+use crate::config::{Deserialize, Deserializers};
+use crate::filter::{Filter, Response};
+
+/// The level range filter's configuration.
+#[cfg(feature = "config_parsing")]
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LevelRangeFilterConfig {
+    min_level: Option<LevelFilter>,
+    max_level: Option<LevelFilter>,
+}
+
+/// A filter that rejects all events outside of a band of levels.
+///
+/// Levels are ordered by severity, so a filter from `Warn` to `Error` passes
+/// on warnings and errors and rejects everything else.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct LevelRangeFilter {
+    min: LevelFilter,
+    max: LevelFilter,
+}
+
+impl LevelRangeFilter {
+    /// Creates a new `LevelRangeFilter` passing on events from the `min`
+    /// level up to and including the more severe `max` level.
+    pub fn new(min: LevelFilter, max: LevelFilter) -> LevelRangeFilter {
+        LevelRangeFilter { min, max }
+    }
+}
+
+impl Filter for LevelRangeFilter {
+    fn filter(&self, record: &Record) -> Response {
+        if record.level() > self.min || record.level() < self.max {
+            Response::Reject
+        } else {
+            Response::Neutral
+        }
+    }
+}
+
+/// A deserializer for the `LevelRangeFilter`.
+///
+/// # Configuration
+///
+/// ```yaml
+/// kind: level_range
+///
+/// # The least severe level passed on. Defaults to `trace`.
+/// min_level: warn
+///
+/// # The most severe level passed on. Defaults to `error`.
+/// max_level: error
+/// ```
+#[cfg(feature = "config_parsing")]
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct LevelRangeFilterDeserializer;
+
+#[cfg(feature = "config_parsing")]
+impl Deserialize for LevelRangeFilterDeserializer {
+    type Trait = dyn Filter;
+
+    type Config = LevelRangeFilterConfig;
+
+    fn deserialize(
+        &self,
+        config: LevelRangeFilterConfig,
+        _: &Deserializers,
+    ) -> anyhow::Result<Box<dyn Filter>> {
+        let min = config.min_level.unwrap_or(LevelFilter::Trace);
+        let max = config.max_level.unwrap_or(LevelFilter::Error);
+        if min < max {
+            anyhow::bail!(
+                "min_level `{}` is more severe than max_level `{}`",
+                min,
+                max
+            );
+        }
+        Ok(Box::new(LevelRangeFilter::new(min, max)))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use log::Level;
+
+    use super::*;
+
+    fn response(filter: &LevelRangeFilter, level: Level) -> Response {
+        filter.filter(
+            &Record::builder()
+                .level(level)
+                .args(format_args!("the message"))
+                .build(),
+        )
+    }
+
+    #[test]
+    fn band() {
+        let filter = LevelRangeFilter::new(LevelFilter::Warn, LevelFilter::Error);
+
+        assert_eq!(response(&filter, Level::Error), Response::Neutral);
+        assert_eq!(response(&filter, Level::Warn), Response::Neutral);
+        assert_eq!(response(&filter, Level::Info), Response::Reject);
+        assert_eq!(response(&filter, Level::Trace), Response::Reject);
+
+        let filter = LevelRangeFilter::new(LevelFilter::Debug, LevelFilter::Info);
+
+        assert_eq!(response(&filter, Level::Error), Response::Reject);
+        assert_eq!(response(&filter, Level::Info), Response::Neutral);
+        assert_eq!(response(&filter, Level::Debug), Response::Neutral);
+        assert_eq!(response(&filter, Level::Trace), Response::Reject);
+    }
+
+    #[test]
+    #[cfg(all(feature = "config_parsing", feature = "yaml_format"))]
+    fn deserialize() {
+        let config: LevelRangeFilterConfig = ::serde_yaml::from_str("min_level: info").unwrap();
+        let filter = LevelRangeFilterDeserializer
+            .deserialize(config, &Deserializers::default())
+            .unwrap();
+        let record = Record::builder().level(Level::Error).build();
+        assert_eq!(filter.filter(&record), Response::Neutral);
+        let record = Record::builder().level(Level::Debug).build();
+        assert_eq!(filter.filter(&record), Response::Reject);
+
+        let config: LevelRangeFilterConfig =
+            ::serde_yaml::from_str("min_level: error\nmax_level: warn").unwrap();
+        let err = LevelRangeFilterDeserializer
+            .deserialize(config, &Deserializers::default())
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "min_level `ERROR` is more severe than max_level `WARN`"
+        );
+    }
+}
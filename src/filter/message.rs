@@ -0,0 +1,137 @@
+//! The message filter.
+//!
+//! Requires the `message_filter` feature.
+
+use log::Record;
+
+#[cfg(feature = "config_parsing")]
+use crate::config::{Deserialize, Deserializers};
+use crate::filter::{Filter, Response};
+
+/// The message filter's configuration.
+#[cfg(feature = "config_parsing")]
+#[derive(Clone, Eq, PartialEq, Hash, Debug, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MessageFilterConfig {
+    pattern: String,
+    action: Option<Action>,
+}
+
+/// What a `MessageFilter` does with records whose message contains its
+/// pattern.
+#[cfg_attr(feature = "config_parsing", derive(serde::Deserialize))]
+#[cfg_attr(feature = "config_parsing", serde(rename_all = "lowercase"))]
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub enum Action {
+    /// Pass on matching records and reject all others.
+    Accept,
+    /// Reject matching records and pass on all others.
+    #[default]
+    Deny,
+}
+
+/// A filter that selects log events by a substring of their message.
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub struct MessageFilter {
+    pattern: String,
+    action: Action,
+}
+
+impl MessageFilter {
+    /// Creates a new `MessageFilter` applying `action` to events whose
+    /// message contains `pattern`.
+    pub fn new(pattern: &str, action: Action) -> MessageFilter {
+        MessageFilter {
+            pattern: pattern.to_owned(),
+            action,
+        }
+    }
+}
+
+impl Filter for MessageFilter {
+    fn filter(&self, record: &Record) -> Response {
+        let matched = match record.args().as_str() {
+            Some(message) => message.contains(&self.pattern),
+            None => record.args().to_string().contains(&self.pattern),
+        };
+        match (self.action, matched) {
+            (Action::Accept, true) | (Action::Deny, false) => Response::Neutral,
+            (Action::Accept, false) | (Action::Deny, true) => Response::Reject,
+        }
+    }
+}
+
+/// A deserializer for the `MessageFilter`.
+///
+/// # Configuration
+///
+/// ```yaml
+/// kind: message
+///
+/// # The text to look for in the formatted message. Required.
+/// pattern: "connection reset"
+///
+/// # `deny` rejects events whose message contains the pattern, `accept`
+/// # rejects all other events. Defaults to `deny`.
+/// action: deny
+/// ```
+#[cfg(feature = "config_parsing")]
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct MessageFilterDeserializer;
+
+#[cfg(feature = "config_parsing")]
+impl Deserialize for MessageFilterDeserializer {
+    type Trait = dyn Filter;
+
+    type Config = MessageFilterConfig;
+
+    fn deserialize(
+        &self,
+        config: MessageFilterConfig,
+        _: &Deserializers,
+    ) -> anyhow::Result<Box<dyn Filter>> {
+        Ok(Box::new(MessageFilter::new(
+            &config.pattern,
+            config.action.unwrap_or_default(),
+        )))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn deny() {
+        let filter = MessageFilter::new("noisy", Action::Deny);
+        let id = 7;
+
+        let reject = filter.filter(
+            &Record::builder()
+                .args(format_args!("a noisy line {}", id))
+                .build(),
+        );
+        assert_eq!(reject, Response::Reject);
+        let record = Record::builder().args(format_args!("a quiet line")).build();
+        assert_eq!(filter.filter(&record), Response::Neutral);
+    }
+
+    #[test]
+    fn accept() {
+        let filter = MessageFilter::new("audit", Action::Accept);
+
+        let record = Record::builder().args(format_args!("audit: login")).build();
+        assert_eq!(filter.filter(&record), Response::Neutral);
+        let record = Record::builder().args(format_args!("cache miss")).build();
+        assert_eq!(filter.filter(&record), Response::Reject);
+    }
+
+    #[test]
+    #[cfg(all(feature = "config_parsing", feature = "yaml_format"))]
+    fn deserialize() {
+        let config: MessageFilterConfig =
+            ::serde_yaml::from_str("pattern: audit\naction: accept").unwrap();
+        assert_eq!(config.action, Some(Action::Accept));
+        ::serde_yaml::from_str::<MessageFilterConfig>("pattern: audit\naction: drop").unwrap_err();
+    }
+}
@@ -12,6 +12,12 @@ use std::fmt;
 #[cfg(feature = "config_parsing")]
 use crate::config::Deserializable;
 
+#[cfg(feature = "level_range_filter")]
+pub mod level_range;
+#[cfg(feature = "message_filter")]
+pub mod message;
+#[cfg(feature = "sample_filter")]
+pub mod sample;
 #[cfg(feature = "threshold_filter")]
 pub mod threshold;
 
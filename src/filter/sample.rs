@@ -0,0 +1,119 @@
+//! The sample filter.
+//!
+//! Requires the `sample_filter` feature.
+
+use log::Record;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+#[cfg(feature = "config_parsing")]
+use crate::config::{Deserialize, Deserializers};
+use crate::filter::{Filter, Response};
+
+/// The sample filter's configuration.
+#[cfg(feature = "config_parsing")]
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SampleFilterConfig {
+    rate: u64,
+}
+
+/// A filter that passes on one of every `rate` events and rejects the rest.
+///
+/// The first event is passed on, then the one after `rate` more, and so on,
+/// counted across all threads. Each filter keeps its own count, starting from
+/// zero when it is created, so the count restarts whenever the configuration
+/// is reloaded.
+#[derive(Debug)]
+pub struct SampleFilter {
+    rate: u64,
+    count: AtomicU64,
+}
+
+impl SampleFilter {
+    /// Creates a new `SampleFilter` passing on one of every `rate` events.
+    ///
+    /// A rate of 0 is treated as 1, passing on every event.
+    pub fn new(rate: u64) -> SampleFilter {
+        SampleFilter {
+            rate: rate.max(1),
+            count: AtomicU64::new(0),
+        }
+    }
+}
+
+impl Filter for SampleFilter {
+    fn filter(&self, _: &Record) -> Response {
+        if self.count.fetch_add(1, Ordering::Relaxed) % self.rate == 0 {
+            Response::Neutral
+        } else {
+            Response::Reject
+        }
+    }
+}
+
+/// A deserializer for the `SampleFilter`.
+///
+/// # Configuration
+///
+/// ```yaml
+/// kind: sample
+///
+/// # One of every `rate` events is passed on. Required, and at least 1.
+/// rate: 100
+/// ```
+#[cfg(feature = "config_parsing")]
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct SampleFilterDeserializer;
+
+#[cfg(feature = "config_parsing")]
+impl Deserialize for SampleFilterDeserializer {
+    type Trait = dyn Filter;
+
+    type Config = SampleFilterConfig;
+
+    fn deserialize(
+        &self,
+        config: SampleFilterConfig,
+        _: &Deserializers,
+    ) -> anyhow::Result<Box<dyn Filter>> {
+        if config.rate == 0 {
+            anyhow::bail!("rate must be at least 1");
+        }
+        Ok(Box::new(SampleFilter::new(config.rate)))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn one_in_rate() {
+        let filter = SampleFilter::new(3);
+        let record = Record::builder().build();
+
+        let responses = (0..7).map(|_| filter.filter(&record)).collect::<Vec<_>>();
+        assert_eq!(
+            responses,
+            [
+                Response::Neutral,
+                Response::Reject,
+                Response::Reject,
+                Response::Neutral,
+                Response::Reject,
+                Response::Reject,
+                Response::Neutral,
+            ]
+        );
+    }
+
+    #[test]
+    #[cfg(all(feature = "config_parsing", feature = "yaml_format"))]
+    fn deserialize() {
+        let config: SampleFilterConfig = ::serde_yaml::from_str("rate: 0").unwrap();
+        let err = SampleFilterDeserializer
+            .deserialize(config, &Deserializers::default())
+            .unwrap_err();
+        assert_eq!(err.to_string(), "rate must be at least 1");
+    }
+}
@@ -14,9 +14,20 @@
 //! Implementations:
 //!   - [console](append/console/struct.ConsoleAppenderDeserializer.html#configuration): requires the `console_appender` feature.
 //!   - [file](append/file/struct.FileAppenderDeserializer.html#configuration): requires the `file_appender` feature.
+//!   - [async](append/async_appender/struct.AsyncAppenderDeserializer.html#configuration): requires the `async_appender` feature.
+//!   - [composite](append/composite/struct.CompositeAppenderDeserializer.html#configuration): requires the `composite_appender` feature.
+//!   - [dedup](append/dedup/struct.DedupAppenderDeserializer.html#configuration): requires the `dedup_appender` feature.
+//!   - [fallback](append/fallback/struct.FallbackAppenderDeserializer.html#configuration): requires the `fallback_appender` feature.
+//!   - [null](append/null/struct.NullAppenderDeserializer.html#configuration): requires the `null_appender` feature.
+//!   - [memory](append/memory/struct.MemoryAppender.html): requires the `memory_appender` feature. It can only be built programmatically.
+//!   - [syslog](append/syslog/struct.SyslogAppenderDeserializer.html#configuration): requires the `syslog_appender` feature.
+//!   - [tcp](append/tcp/struct.TcpAppenderDeserializer.html#configuration): requires the `tcp_appender` feature.
+//!   - [udp](append/udp/struct.UdpAppenderDeserializer.html#configuration): requires the `udp_appender` feature.
+//!   - [writer](append/writer/struct.WriterAppender.html): requires the `writer_appender` feature. It can only be built programmatically.
 //!   - [rolling_file](append/rolling_file/struct.RollingFileAppenderDeserializer.html#configuration): requires the `rolling_file_appender` feature and can be configured with the `compound_policy`.
 //!     - [compound](append/rolling_file/policy/compound/struct.CompoundPolicyDeserializer.html#configuration): requires the `compound_policy` feature
 //!       - Rollers
+//!         - [dated](append/rolling_file/policy/compound/roll/dated/struct.DatedRollerDeserializer.html#configuration): requires the `dated_roller` feature
 //!         - [delete](append/rolling_file/policy/compound/roll/delete/struct.DeleteRollerDeserializer.html#configuration): requires the `delete_roller` feature
 //!         - [fixed_window](append/rolling_file/policy/compound/roll/fixed_window/struct.FixedWindowRollerDeserializer.html#configuration): requires the `fixed_window_roller` feature
 //!       - Triggers
@@ -41,6 +52,9 @@
 //!
 //! Implementations:
 //!   - [threshold](filter/threshold/struct.ThresholdFilterDeserializer.html#configuration): requires the `threshold_filter` feature
+//!   - [message](filter/message/struct.MessageFilterDeserializer.html#configuration): requires the `message_filter` feature
+//!   - [level_range](filter/level_range/struct.LevelRangeFilterDeserializer.html#configuration): requires the `level_range_filter` feature
+//!   - [sample](filter/sample/struct.SampleFilterDeserializer.html#configuration): requires the `sample_filter` feature
 //!
 //! ## Loggers
 //!
@@ -65,6 +79,15 @@
 //! filtered out by the logger's maximum log level will be sent to all
 //! associated appenders.
 //!
+//! Appenders are called one after the other, on the thread that logged the
+//! event, in a fixed order: first the logger's own appenders in the order
+//! they are listed in its configuration, then those of its parent in the same
+//! way, and so on up to the root. An appender listed by several of these
+//! loggers is called once for each. An appender which fails does not keep the
+//! event from the others. Its error is passed to the logger's error handler,
+//! which writes it to standard error unless one was given to
+//! `Logger::new_with_err_handler` or `init_config_with_err_handler`.
+//!
 //! The "root" logger is the ancestor of all other loggers. Since it has no
 //! ancestors, its additivity cannot be configured.
 //!
@@ -174,6 +197,21 @@
 //! # fn main() {}
 //! ```
 //!
+//! ## Keeping records logged before initialization
+//!
+//! Records logged before a logger is installed are lost. To keep them, call
+//! `init_buffered` first thing in `main`; the bounded buffer it fills is
+//! replayed through the configuration installed later.
+//!
+//! ```no_run
+//! # #[cfg(feature = "config_parsing")]
+//! # fn f() {
+//! log4rs::init_buffered(log::LevelFilter::Info, 1000).unwrap();
+//! // ... libraries may log while the application starts up ...
+//! log4rs::init_file("log4rs.yml", Default::default()).unwrap();
+//! # }
+//! ```
+//!
 //! For more examples see the [examples](https://github.com/estk/log4rs/tree/main/examples).
 //!
 
@@ -181,12 +219,23 @@
 #![warn(missing_docs)]
 
 use std::{
-    cmp, collections::HashMap, fmt, hash::BuildHasherDefault, io, io::prelude::*, sync::Arc,
+    backtrace::Backtrace,
+    cell::Cell,
+    cmp,
+    collections::{HashMap, VecDeque},
+    fmt,
+    hash::BuildHasherDefault,
+    io,
+    io::prelude::*,
+    panic,
+    sync::{Arc, Mutex, PoisonError, RwLock},
+    thread,
 };
 
 use arc_swap::ArcSwap;
 use fnv::FnvHasher;
 use log::{Level, LevelFilter, Metadata, Record};
+use once_cell::sync::{Lazy, OnceCell};
 
 pub mod append;
 pub mod config;
@@ -194,16 +243,41 @@ pub mod encode;
 pub mod filter;
 #[cfg(feature = "console_writer")]
 mod priv_io;
+mod priv_record;
 
-pub use config::{init_config, Config};
+pub use config::{init_buffered, init_config, Config};
 
 #[cfg(feature = "config_parsing")]
-pub use config::{init_file, init_raw_config};
+pub use config::{init_file, init_raw_config, init_string};
+
+/// The thread-local mapped diagnostic context read by the `X` formatter of
+/// the pattern encoder and by the JSON encoder.
+///
+/// Values are set per thread, so a request handler can attach an id once and
+/// have it show up on every line logged while handling the request:
+///
+/// ```
+/// let _guard = log4rs::mdc::insert_scoped("request_id", "5f2c");
+/// // Lines logged here render `{X(request_id)}` as `5f2c`. The key is
+/// // removed again when `_guard` is dropped.
+/// # log4rs::mdc::get("request_id", |v| assert_eq!(v, Some("5f2c")));
+/// ```
+#[cfg(any(feature = "json_encoder", feature = "pattern_encoder"))]
+pub use log_mdc as mdc;
 
-use self::{append::Append, filter::Filter};
+use self::{append::Append, filter::Filter, priv_record::OwnedRecord};
 
 type FnvHashMap<K, V> = HashMap<K, V, BuildHasherDefault<FnvHasher>>;
 
+/// The handle to the logger installed by the `init_*` functions, used by
+/// `shutdown`.
+static HANDLE: OnceCell<Handle> = OnceCell::new();
+
+type ErrorHandler = Arc<dyn Fn(&anyhow::Error) + Send + Sync>;
+
+/// The handler set by `set_error_handler`, if any.
+static ERROR_HANDLER: Lazy<RwLock<Option<ErrorHandler>>> = Lazy::new(RwLock::default);
+
 #[derive(Debug)]
 struct ConfiguredLogger {
     level: LevelFilter,
@@ -313,10 +387,81 @@ impl Appender {
     }
 }
 
+/// Records logged before a configuration is installed by a logger from
+/// `init_buffered`.
+struct EarlyBuffer {
+    state: Mutex<EarlyState>,
+    capacity: usize,
+}
+
+#[derive(Default)]
+struct EarlyState {
+    records: VecDeque<OwnedRecord>,
+    dropped: usize,
+    closed: bool,
+}
+
+impl EarlyBuffer {
+    /// Buffers `record`, dropping the oldest record if the buffer is full.
+    ///
+    /// Returns `false` if the buffer has already been replayed.
+    fn push(&self, record: &Record) -> bool {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        if state.closed {
+            return false;
+        }
+        if self.capacity == 0 {
+            state.dropped += 1;
+            return true;
+        }
+        if state.records.len() >= self.capacity {
+            state.records.pop_front();
+            state.dropped += 1;
+        }
+        state.records.push_back(OwnedRecord::new(record));
+        true
+    }
+
+    /// Logs the buffered records to `logger`.
+    fn replay(&self, logger: &SharedLogger) {
+        let state = {
+            let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+            state.closed = true;
+            std::mem::take(&mut *state)
+        };
+        if state.dropped > 0 {
+            (logger.err_handler)(&anyhow::anyhow!(
+                "{} records logged before log4rs was configured were dropped",
+                state.dropped
+            ));
+        }
+        for record in &state.records {
+            record.with_record(|record| logger.log(record));
+        }
+    }
+
+    /// Discards the buffered records when the logger is shut down before a
+    /// configuration was installed, reporting them to `logger`.
+    fn discard(&self, logger: &SharedLogger) {
+        let state = {
+            let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+            state.closed = true;
+            std::mem::take(&mut *state)
+        };
+        (logger.err_handler)(&anyhow::anyhow!(
+            "log4rs was shut down before it was configured; {} records logged before then were discarded",
+            state.records.len() + state.dropped
+        ));
+    }
+}
+
 struct SharedLogger {
     root: ConfiguredLogger,
     appenders: Vec<Appender>,
+    view: config::ConfigView,
     err_handler: Box<dyn Send + Sync + Fn(&anyhow::Error)>,
+    shut_down: bool,
+    early: Option<EarlyBuffer>,
 }
 
 impl fmt::Debug for SharedLogger {
@@ -330,17 +475,13 @@ impl fmt::Debug for SharedLogger {
 
 impl SharedLogger {
     fn new(config: config::Config) -> SharedLogger {
-        Self::new_with_err_handler(
-            config,
-            Box::new(|e: &anyhow::Error| {
-                let _ = writeln!(io::stderr(), "log4rs: {}", e);
-            }),
-        )
+        Self::new_with_err_handler(config, Box::new(handle_error))
     }
     fn new_with_err_handler(
         config: config::Config,
         err_handler: Box<dyn Send + Sync + Fn(&anyhow::Error)>,
     ) -> SharedLogger {
+        let view = config.view();
         let (appenders, root, mut loggers) = config.unpack();
 
         let root = {
@@ -385,7 +526,19 @@ impl SharedLogger {
         SharedLogger {
             root,
             appenders,
+            view,
             err_handler,
+            shut_down: false,
+            early: None,
+        }
+    }
+
+    fn log(&self, record: &log::Record) {
+        let _dispatching = Dispatching::enter();
+        if let Err(errs) = self.root.find(record.target()).log(record, &self.appenders) {
+            for e in errs {
+                (self.err_handler)(&e)
+            }
         }
     }
 }
@@ -414,6 +567,11 @@ impl Logger {
     pub fn max_log_level(&self) -> LevelFilter {
         self.0.load().root.max_log_level()
     }
+
+    /// Returns a view of the configuration the logger is running with.
+    pub fn config(&self) -> config::ConfigView {
+        self.0.load().view.clone()
+    }
 }
 
 impl log::Log for Logger {
@@ -427,26 +585,160 @@ impl log::Log for Logger {
 
     fn log(&self, record: &log::Record) {
         let shared = self.0.load();
-        if let Err(errs) = shared
-            .root
-            .find(record.target())
-            .log(record, &shared.appenders)
-        {
-            for e in errs {
-                (shared.err_handler)(&e)
+        match shared.early {
+            Some(ref early) => {
+                let enabled = shared.root.find(record.target()).enabled(record.level());
+                if enabled && !early.push(record) {
+                    // a configuration was installed after the load above
+                    drop(shared);
+                    self.log(record);
+                }
             }
+            None => shared.log(record),
         }
     }
 
     fn flush(&self) {
+        let _dispatching = Dispatching::enter();
         for appender in &self.0.load().appenders {
             appender.flush();
         }
     }
 }
 
+thread_local! {
+    static DISPATCHING: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Marks the current thread as being inside an appender until dropped,
+/// including while a panic unwinds out of it.
+struct Dispatching(bool);
+
+impl Dispatching {
+    fn enter() -> Dispatching {
+        Dispatching(DISPATCHING.with(|dispatching| dispatching.replace(true)))
+    }
+
+    /// Returns whether the current thread is inside an appender.
+    fn is_active() -> bool {
+        DISPATCHING.with(Cell::get)
+    }
+}
+
+impl Drop for Dispatching {
+    fn drop(&mut self) {
+        DISPATCHING.with(|dispatching| dispatching.set(self.0));
+    }
+}
+
+/// Shuts down the global logger installed by one of the `init_*` functions,
+/// flushing and then dropping all of its appenders.
+///
+/// This should be called before the process exits so that buffered records
+/// are not lost. Logging afterwards is a no-op. Nothing happens if no logger
+/// was installed by log4rs. See `Handle::shutdown`.
+pub fn shutdown() {
+    if let Some(handle) = HANDLE.get() {
+        handle.shutdown();
+    }
+}
+
+/// Sets the handler for log4rs's own diagnostics, replacing any set before.
+///
+/// These include the errors of appenders, unless the logger was given an
+/// error handler of its own, and errors found when loading or reloading a
+/// configuration file. By default, they are written to standard error with a
+/// `log4rs: ` prefix. An application which must not write to standard error
+/// can capture them here instead.
+///
+/// The handler may be called from any thread that logs, and from the thread
+/// reloading the configuration.
+///
+/// ```
+/// log4rs::set_error_handler(Box::new(|e| {
+///     // Hand the diagnostic to the application's own error reporting.
+///     let _ = e;
+/// }));
+/// ```
+pub fn set_error_handler(handler: Box<dyn Fn(&anyhow::Error) + Send + Sync>) {
+    *ERROR_HANDLER
+        .write()
+        .unwrap_or_else(PoisonError::into_inner) = Some(handler.into());
+}
+
 pub(crate) fn handle_error(e: &anyhow::Error) {
-    let _ = writeln!(io::stderr(), "log4rs: {}", e);
+    // The lock is released before calling the handler, which may itself log.
+    let handler = ERROR_HANDLER
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clone();
+    match handler {
+        Some(handler) => handler(e),
+        None => {
+            let _ = writeln!(io::stderr(), "log4rs: {}", e);
+        }
+    }
+}
+
+/// Installs a panic hook which logs each panic at the `Error` level with the
+/// given target, then runs the previously installed hook.
+///
+/// The message names the panicking thread and the location of the panic. If
+/// `backtrace` is set, a backtrace is captured and logged with it regardless
+/// of `RUST_BACKTRACE`. The logger is flushed afterwards, so the message is
+/// written even if the panic goes on to abort the process.
+///
+/// Panics are only logged once this has been called. A panic inside an
+/// appender, encoder or filter is not logged, as the appender may still hold
+/// the lock it would need to write the message, and is only passed on to the
+/// previous hook.
+///
+/// ```
+/// log4rs::log_panics("panic", false);
+/// ```
+pub fn log_panics(target: &str, backtrace: bool) {
+    let target = target.to_owned();
+    let previous = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        if Dispatching::is_active() {
+            previous(info);
+            return;
+        }
+        let payload = info.payload();
+        let message = match payload.downcast_ref::<&str>() {
+            Some(s) => *s,
+            None => match payload.downcast_ref::<String>() {
+                Some(s) => s,
+                None => "Box<dyn Any>",
+            },
+        };
+        let thread = thread::current();
+        let thread = thread.name().unwrap_or("<unnamed>");
+        let location = match info.location() {
+            Some(location) => format!(" at {}", location),
+            None => String::new(),
+        };
+        if backtrace {
+            log::error!(
+                target: &target,
+                "thread '{}' panicked{}: {}\n{}",
+                thread,
+                location,
+                message,
+                Backtrace::force_capture()
+            );
+        } else {
+            log::error!(
+                target: &target,
+                "thread '{}' panicked{}: {}",
+                thread,
+                location,
+                message
+            );
+        }
+        log::logger().flush();
+        previous(info);
+    }));
 }
 
 /// A handle to the active logger.
@@ -457,10 +749,67 @@ pub struct Handle {
 
 impl Handle {
     /// Sets the logging configuration.
+    ///
+    /// This does nothing once the logger has been shut down.
     pub fn set_config(&self, config: Config) {
-        let shared = SharedLogger::new(config);
+        if self.shared.load().shut_down {
+            return;
+        }
+        self.install(Arc::new(SharedLogger::new(config)));
+    }
+
+    /// Replaces the active logger with `shared`, replaying any records
+    /// buffered by a logger from `init_buffered`.
+    fn install(&self, shared: Arc<SharedLogger>) {
         log::set_max_level(shared.root.max_log_level());
-        self.shared.store(Arc::new(shared));
+        let old = self.shared.swap(shared);
+        if let Some(ref early) = old.early {
+            early.replay(&self.shared.load());
+        }
+    }
+
+    /// Returns whether this is the handle of a logger from `init_buffered`
+    /// which has no configuration installed yet.
+    fn is_buffering(&self) -> bool {
+        self.shared.load().early.is_some()
+    }
+
+    /// Shuts the logger down, flushing and then dropping all of its
+    /// appenders.
+    ///
+    /// Appenders which buffer records, such as the async appender or a file
+    /// appender without auto flush, write everything they hold before this
+    /// returns. A record being logged concurrently on another thread may
+    /// keep its appenders alive until it has been written.
+    ///
+    /// If the logger came from `init_buffered` and no configuration was
+    /// installed yet, the records it buffered have nowhere to go; they are
+    /// discarded and the error handler is told how many there were.
+    ///
+    /// Logging after shutdown is a no-op, and later calls to `set_config`
+    /// are ignored.
+    pub fn shutdown(&self) {
+        let config = Config::builder()
+            .build(config::Root::builder().build(LevelFilter::Off))
+            .expect("an empty config is valid");
+        let mut shared = SharedLogger::new(config);
+        shared.shut_down = true;
+        log::set_max_level(LevelFilter::Off);
+        let old = self.shared.swap(Arc::new(shared));
+        if let Some(ref early) = old.early {
+            early.discard(&old);
+        }
+        for appender in &old.appenders {
+            appender.flush();
+        }
+    }
+
+    /// Returns a view of the active logging configuration.
+    ///
+    /// This may be called concurrently with logging and with `set_config`;
+    /// the view reflects whichever configuration was active at the time.
+    pub fn config(&self) -> config::ConfigView {
+        self.shared.load().view.clone()
     }
 }
 
@@ -577,4 +926,335 @@ mod test {
                 .build()
         ));
     }
+
+    #[derive(Debug, Default)]
+    struct Capture(Arc<std::sync::Mutex<Vec<String>>>);
+
+    impl Append for Capture {
+        fn append(&self, record: &Record) -> anyhow::Result<()> {
+            self.0.lock().unwrap().push(record.args().to_string());
+            Ok(())
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "threshold_filter")]
+    fn appender_filters_are_per_appender() {
+        use crate::filter::threshold::ThresholdFilter;
+
+        let console = Capture::default();
+        let console_records = console.0.clone();
+        let file = Capture::default();
+        let file_records = file.0.clone();
+
+        let config = config::Config::builder()
+            .appender(
+                config::Appender::builder()
+                    .filter(Box::new(ThresholdFilter::new(LevelFilter::Warn)))
+                    .build("console", Box::new(console)),
+            )
+            .appender(config::Appender::builder().build("file", Box::new(file)))
+            .logger(
+                config::Logger::builder()
+                    .appender("console")
+                    .build("app", LevelFilter::Trace),
+            )
+            .build(
+                config::Root::builder()
+                    .appender("file")
+                    .build(LevelFilter::Trace),
+            )
+            .unwrap();
+        let logger = super::Logger::new(config);
+
+        for (level, msg) in [(Level::Info, "info"), (Level::Warn, "warn")] {
+            logger.log(
+                &Record::builder()
+                    .level(level)
+                    .target("app")
+                    .args(format_args!("{}", msg))
+                    .build(),
+            );
+        }
+
+        assert_eq!(*console_records.lock().unwrap(), ["warn"]);
+        assert_eq!(*file_records.lock().unwrap(), ["info", "warn"]);
+    }
+
+    #[test]
+    fn non_additive_logger() {
+        let root = Capture::default();
+        let root_records = root.0.clone();
+        let app = Capture::default();
+        let app_records = app.0.clone();
+        let db = Capture::default();
+        let db_records = db.0.clone();
+
+        let config = config::Config::builder()
+            .appender(config::Appender::builder().build("root", Box::new(root)))
+            .appender(config::Appender::builder().build("app", Box::new(app)))
+            .appender(config::Appender::builder().build("db", Box::new(db)))
+            .logger(
+                config::Logger::builder()
+                    .appender("app")
+                    .additive(false)
+                    .build("app", LevelFilter::Info),
+            )
+            .logger(
+                config::Logger::builder()
+                    .appender("db")
+                    .build("app::db", LevelFilter::Info),
+            )
+            .build(
+                config::Root::builder()
+                    .appender("root")
+                    .build(LevelFilter::Info),
+            )
+            .unwrap();
+        let logger = super::Logger::new(config);
+
+        for target in ["app", "app::db", "other"] {
+            logger.log(
+                &Record::builder()
+                    .level(Level::Info)
+                    .target(target)
+                    .args(format_args!("{}", target))
+                    .build(),
+            );
+        }
+
+        assert_eq!(*root_records.lock().unwrap(), ["other"]);
+        assert_eq!(*app_records.lock().unwrap(), ["app", "app::db"]);
+        assert_eq!(*db_records.lock().unwrap(), ["app::db"]);
+    }
+
+    #[test]
+    fn off_levels() {
+        let root = Capture::default();
+        let root_records = root.0.clone();
+
+        let config = config::Config::builder()
+            .appender(config::Appender::builder().build("root", Box::new(root)))
+            .logger(config::Logger::builder().build("chatty", LevelFilter::Off))
+            .logger(config::Logger::builder().build("app", LevelFilter::Info))
+            .build(
+                config::Root::builder()
+                    .appender("root")
+                    .build(LevelFilter::Off),
+            )
+            .unwrap();
+        let logger = super::Logger::new(config);
+
+        for target in ["chatty", "chatty::db", "other", "app"] {
+            logger.log(
+                &Record::builder()
+                    .level(Level::Error)
+                    .target(target)
+                    .args(format_args!("{}", target))
+                    .build(),
+            );
+        }
+
+        // Only the `app` logger is enabled, and it inherits the root's
+        // appenders.
+        assert_eq!(*root_records.lock().unwrap(), ["app"]);
+        assert!(!logger.enabled(
+            &Metadata::builder()
+                .level(Level::Error)
+                .target("chatty::db")
+                .build()
+        ));
+    }
+
+    #[test]
+    fn config_view() {
+        let config = || {
+            config::Config::builder()
+                .appender(config::Appender::builder().build("root", Box::new(Capture::default())))
+                .logger(config::Logger::builder().build("app::db", LevelFilter::Debug))
+        };
+        let logger = super::Logger::new(
+            config()
+                .build(
+                    config::Root::builder()
+                        .appender("root")
+                        .build(LevelFilter::Warn),
+                )
+                .unwrap(),
+        );
+        let handle = Handle {
+            shared: logger.0.clone(),
+        };
+
+        let view = handle.config();
+        assert_eq!(view.appenders(), ["root"]);
+        assert_eq!(view.root().level(), LevelFilter::Warn);
+        assert_eq!(view.root().appenders(), ["root"]);
+        assert_eq!(view.loggers()[0].name(), "app::db");
+        assert_eq!(view.loggers()[0].level(), LevelFilter::Debug);
+
+        handle.set_config(
+            config()
+                .build(config::Root::builder().build(LevelFilter::Error))
+                .unwrap(),
+        );
+        assert_eq!(logger.config().root().level(), LevelFilter::Error);
+        assert_eq!(view.root().level(), LevelFilter::Warn);
+    }
+
+    #[test]
+    fn hierarchical_loggers() {
+        let root = Capture::default();
+        let root_records = root.0.clone();
+        let ab = Capture::default();
+        let ab_records = ab.0.clone();
+
+        let config = config::Config::builder()
+            .appender(config::Appender::builder().build("root", Box::new(root)))
+            .appender(config::Appender::builder().build("ab", Box::new(ab)))
+            .logger(config::Logger::builder().build("a", LevelFilter::Error))
+            .logger(
+                config::Logger::builder()
+                    .appender("ab")
+                    .additive(false)
+                    .build("a::b", LevelFilter::Debug),
+            )
+            .build(
+                config::Root::builder()
+                    .appender("root")
+                    .build(LevelFilter::Info),
+            )
+            .unwrap();
+        let logger = super::Logger::new(config);
+
+        for target in ["a::b::c", "a::bc", "a::b", "b"] {
+            logger.log(
+                &Record::builder()
+                    .level(Level::Debug)
+                    .target(target)
+                    .args(format_args!("{}", target))
+                    .build(),
+            );
+        }
+
+        // `a::b::c` resolves to the `a::b` logger, while `a::bc` is not one of
+        // its children and falls back to `a`, which rejects debug records.
+        assert_eq!(*ab_records.lock().unwrap(), ["a::b::c", "a::b"]);
+        assert!(root_records.lock().unwrap().is_empty());
+    }
+
+    #[derive(Debug)]
+    struct Named(&'static str, Arc<std::sync::Mutex<Vec<&'static str>>>);
+
+    impl Append for Named {
+        fn append(&self, _: &Record) -> anyhow::Result<()> {
+            self.1.lock().unwrap().push(self.0);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn appender_order() {
+        let calls = Arc::new(std::sync::Mutex::new(vec![]));
+        let appender =
+            |name| config::Appender::builder().build(name, Box::new(Named(name, calls.clone())));
+        let config = config::Config::builder()
+            .appender(appender("first"))
+            .appender(appender("second"))
+            .appender(appender("db"))
+            .appender(appender("root1"))
+            .appender(appender("root2"))
+            .logger(
+                config::Logger::builder()
+                    .appender("second")
+                    .appender("first")
+                    .build("app", LevelFilter::Info),
+            )
+            .logger(
+                config::Logger::builder()
+                    .appender("db")
+                    .build("app::db", LevelFilter::Info),
+            )
+            .build(
+                config::Root::builder()
+                    .appender("root2")
+                    .appender("root1")
+                    .build(LevelFilter::Info),
+            )
+            .unwrap();
+        let logger = super::Logger::new(config);
+
+        logger.log(&Record::builder().target("app::db::pool").build());
+
+        assert_eq!(
+            *calls.lock().unwrap(),
+            ["db", "second", "first", "root2", "root1"]
+        );
+    }
+
+    #[derive(Debug)]
+    struct Fail;
+
+    impl Append for Fail {
+        fn append(&self, _: &Record) -> anyhow::Result<()> {
+            Err(anyhow::anyhow!("disk full"))
+        }
+    }
+
+    #[test]
+    fn failing_appender_doesnt_stop_others() {
+        let working = Capture::default();
+        let records = working.0.clone();
+        let errors = Arc::new(std::sync::Mutex::new(vec![]));
+        let config = config::Config::builder()
+            .appender(config::Appender::builder().build("failing", Box::new(Fail)))
+            .appender(config::Appender::builder().build("working", Box::new(working)))
+            .build(
+                config::Root::builder()
+                    .appender("failing")
+                    .appender("working")
+                    .build(LevelFilter::Info),
+            )
+            .unwrap();
+        let logger = {
+            let errors = errors.clone();
+            super::Logger::new_with_err_handler(
+                config,
+                Box::new(move |e: &anyhow::Error| errors.lock().unwrap().push(e.to_string())),
+            )
+        };
+
+        logger.log(&Record::builder().args(format_args!("hello")).build());
+
+        assert_eq!(*records.lock().unwrap(), ["hello"]);
+        assert_eq!(*errors.lock().unwrap(), ["disk full"]);
+    }
+
+    #[test]
+    fn early_buffer_without_capacity() {
+        let errors = Arc::new(std::sync::Mutex::new(vec![]));
+        let config = config::Config::builder()
+            .build(config::Root::builder().build(LevelFilter::Info))
+            .unwrap();
+        let logger = {
+            let errors = errors.clone();
+            SharedLogger::new_with_err_handler(
+                config,
+                Box::new(move |e: &anyhow::Error| errors.lock().unwrap().push(e.to_string())),
+            )
+        };
+        let early = EarlyBuffer {
+            state: Mutex::default(),
+            capacity: 0,
+        };
+        for _ in 0..3 {
+            assert!(early.push(&Record::builder().args(format_args!("lost")).build()));
+        }
+        early.replay(&logger);
+
+        assert_eq!(
+            *errors.lock().unwrap(),
+            ["3 records logged before log4rs was configured were dropped"]
+        );
+    }
 }
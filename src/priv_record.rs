@@ -0,0 +1,39 @@
+use log::{Level, Record};
+
+/// A record copied out of a logging call so it can be appended later.
+pub struct OwnedRecord {
+    level: Level,
+    target: String,
+    message: String,
+    module_path: Option<String>,
+    file: Option<String>,
+    line: Option<u32>,
+}
+
+impl OwnedRecord {
+    pub fn new(record: &Record) -> OwnedRecord {
+        OwnedRecord {
+            level: record.level(),
+            target: record.target().to_owned(),
+            message: record.args().to_string(),
+            module_path: record.module_path().map(ToOwned::to_owned),
+            file: record.file().map(ToOwned::to_owned),
+            line: record.line(),
+        }
+    }
+
+    /// Calls `f` with a `Record` borrowing from this one.
+    pub fn with_record<F, R>(&self, f: F) -> R
+    where
+        F: FnOnce(&Record) -> R,
+    {
+        f(&Record::builder()
+            .level(self.level)
+            .target(&self.target)
+            .args(format_args!("{}", self.message))
+            .module_path(self.module_path.as_deref())
+            .file(self.file.as_deref())
+            .line(self.line)
+            .build())
+    }
+}
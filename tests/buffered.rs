@@ -0,0 +1,42 @@
+#![cfg(feature = "memory_appender")]
+
+use std::sync::{Arc, Mutex};
+
+use log::LevelFilter;
+use log4rs::{
+    append::memory::MemoryAppender,
+    config::{Appender, Config, Root},
+};
+
+#[test]
+fn replays_records_logged_before_init() {
+    let errors = Arc::new(Mutex::new(vec![]));
+    {
+        let errors = errors.clone();
+        log4rs::set_error_handler(Box::new(move |e| {
+            errors.lock().unwrap().push(e.to_string())
+        }));
+    }
+
+    log4rs::init_buffered(LevelFilter::Info, 2).unwrap();
+    log::info!("one");
+    log::debug!("filtered");
+    log::info!("two");
+    log::warn!("three");
+    assert!(errors.lock().unwrap().is_empty());
+
+    let memory = MemoryAppender::builder().build();
+    let config = Config::builder()
+        .appender(Appender::builder().build("memory", Box::new(memory.clone())))
+        .build(Root::builder().appender("memory").build(LevelFilter::Warn))
+        .unwrap();
+    log4rs::init_config(config).unwrap();
+    log::warn!("four");
+
+    // "two" was buffered but is below the installed level
+    assert_eq!(memory.records(), ["three", "four"]);
+    assert_eq!(
+        *errors.lock().unwrap(),
+        ["1 records logged before log4rs was configured were dropped"]
+    );
+}
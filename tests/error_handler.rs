@@ -0,0 +1,37 @@
+use std::sync::{Arc, Mutex};
+
+use log::{LevelFilter, Log, Record};
+use log4rs::{
+    append::Append,
+    config::{Appender, Config, Root},
+    Logger,
+};
+
+#[derive(Debug)]
+struct Fail;
+
+impl Append for Fail {
+    fn append(&self, _: &Record) -> anyhow::Result<()> {
+        Err(anyhow::anyhow!("disk full"))
+    }
+}
+
+#[test]
+fn appender_errors_reach_the_handler() {
+    let errors = Arc::new(Mutex::new(vec![]));
+    {
+        let errors = errors.clone();
+        log4rs::set_error_handler(Box::new(move |e| {
+            errors.lock().unwrap().push(e.to_string())
+        }));
+    }
+
+    let config = Config::builder()
+        .appender(Appender::builder().build("fail", Box::new(Fail)))
+        .build(Root::builder().appender("fail").build(LevelFilter::Info))
+        .unwrap();
+    let logger = Logger::new(config);
+    logger.log(&Record::builder().args(format_args!("hello")).build());
+
+    assert_eq!(*errors.lock().unwrap(), ["disk full"]);
+}
@@ -0,0 +1,50 @@
+#![cfg(all(feature = "yaml_format", feature = "file_appender"))]
+
+use std::{
+    fs,
+    sync::{Arc, Mutex},
+};
+
+use log4rs::config::{Deserializers, Warning};
+
+#[test]
+fn init_from_string() {
+    let warnings = Arc::new(Mutex::new(vec![]));
+    {
+        let warnings = warnings.clone();
+        log4rs::set_error_handler(Box::new(move |e| {
+            assert!(e.downcast_ref::<Warning>().is_some(), "{}", e);
+            warnings.lock().unwrap().push(e.to_string())
+        }));
+    }
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("app.log");
+    let config = format!(
+        "
+refresh_rate: 30 seconds
+appenders:
+  file:
+    kind: file
+    path: {}
+    encoder:
+      pattern: '{{l}} {{m}}{{n}}'
+root:
+  level: info
+  appenders: [file]
+",
+        path.display()
+    );
+    log4rs::init_string(&config, "yaml", Deserializers::default()).unwrap();
+    log::info!("hello");
+    log::logger().flush();
+
+    assert_eq!(fs::read_to_string(&path).unwrap().trim_end(), "INFO hello");
+    assert_eq!(
+        *warnings.lock().unwrap(),
+        ["warning: refresh_rate is ignored for a configuration read from a string"]
+    );
+
+    let err = log4rs::init_string("", "ini", Deserializers::default()).unwrap_err();
+    assert_eq!(err.to_string(), "unsupported file format `ini`");
+}
@@ -0,0 +1,62 @@
+use std::{
+    panic,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use log::{Level, LevelFilter, Record};
+use log4rs::{
+    append::Append,
+    config::{Appender, Config, Root},
+};
+
+#[derive(Debug)]
+struct Capture(Arc<Mutex<Vec<(Level, String, String)>>>);
+
+impl Append for Capture {
+    fn append(&self, record: &Record) -> anyhow::Result<()> {
+        self.0.lock().unwrap().push((
+            record.level(),
+            record.target().to_owned(),
+            record.args().to_string(),
+        ));
+        Ok(())
+    }
+}
+
+static PREVIOUS_RAN: AtomicBool = AtomicBool::new(false);
+
+#[test]
+fn panics_are_logged() {
+    let records = Arc::new(Mutex::new(vec![]));
+    let config = Config::builder()
+        .appender(Appender::builder().build("capture", Box::new(Capture(records.clone()))))
+        .build(Root::builder().appender("capture").build(LevelFilter::Info))
+        .unwrap();
+    log4rs::init_config(config).unwrap();
+
+    panic::set_hook(Box::new(|_| PREVIOUS_RAN.store(true, Ordering::SeqCst)));
+    log4rs::log_panics("panic", false);
+
+    let line = line!() + 1;
+    panic::catch_unwind(|| panic!("boom {}", 42)).unwrap_err();
+
+    // Restore the default hook so a failed assertion is reported as usual.
+    drop(panic::take_hook());
+
+    let records = records.lock().unwrap().clone();
+    assert_eq!(records.len(), 1);
+    let (level, target, message) = &records[0];
+    assert_eq!(*level, Level::Error);
+    assert_eq!(target, "panic");
+    let prefix = format!(
+        "thread 'panics_are_logged' panicked at {}:{}:",
+        file!(),
+        line
+    );
+    assert!(message.starts_with(&prefix), "{}", message);
+    assert!(message.ends_with(": boom 42"), "{}", message);
+    assert!(PREVIOUS_RAN.load(Ordering::SeqCst));
+}
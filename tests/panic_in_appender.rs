@@ -0,0 +1,53 @@
+use std::{
+    panic,
+    sync::{Arc, Mutex},
+};
+
+use log::{LevelFilter, Record};
+use log4rs::{
+    append::Append,
+    config::{Appender, Config, Root},
+};
+
+/// Holds its lock while appending, like the file and TCP appenders, and
+/// panics on the record `boom`.
+#[derive(Debug)]
+struct Panicking(Arc<Mutex<Vec<String>>>);
+
+impl Append for Panicking {
+    fn append(&self, record: &Record) -> anyhow::Result<()> {
+        let mut records = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        let message = record.args().to_string();
+        if message == "boom" {
+            panic!("appender failed");
+        }
+        records.push(message);
+        Ok(())
+    }
+}
+
+#[test]
+fn panics_inside_appenders_are_not_logged() {
+    let records = Arc::new(Mutex::new(vec![]));
+    let config = Config::builder()
+        .appender(Appender::builder().build("panicking", Box::new(Panicking(records.clone()))))
+        .build(
+            Root::builder()
+                .appender("panicking")
+                .build(LevelFilter::Info),
+        )
+        .unwrap();
+    log4rs::init_config(config).unwrap();
+
+    panic::set_hook(Box::new(|_| {}));
+    log4rs::log_panics("panic", false);
+
+    // Logging the panic would lock the appender again and deadlock.
+    panic::catch_unwind(|| log::info!("boom")).unwrap_err();
+    log::info!("after");
+
+    drop(panic::take_hook());
+
+    let records = records.lock().unwrap_or_else(|e| e.into_inner());
+    assert_eq!(*records, ["after"]);
+}
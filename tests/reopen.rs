@@ -0,0 +1,65 @@
+#![cfg(all(unix, feature = "file_appender"))]
+
+use std::{
+    fs,
+    sync::{Arc, Mutex},
+};
+
+use log::Record;
+use log4rs::{
+    append::{file::FileAppender, Append},
+    encode::pattern::PatternEncoder,
+};
+
+#[test]
+fn reopens_on_sighup() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("app.log");
+    let rotated = dir.path().join("app.log.1");
+    let appender = FileAppender::builder()
+        .encoder(Box::new(PatternEncoder::new("{m}{n}")))
+        .build(&path)
+        .unwrap();
+    let log = |msg: &str| {
+        appender
+            .append(&Record::builder().args(format_args!("{}", msg)).build())
+            .unwrap()
+    };
+
+    log4rs::append::file::reopen_on_sighup().unwrap();
+    log("one");
+    fs::rename(&path, &rotated).unwrap();
+    log("two");
+    assert_eq!(unsafe { libc::raise(libc::SIGHUP) }, 0);
+    log("three");
+
+    assert_eq!(fs::read_to_string(&rotated).unwrap(), "one\ntwo\n");
+    assert_eq!(fs::read_to_string(&path).unwrap(), "three\n");
+
+    // a failed reopen keeps writing to the old file
+    let errors = Arc::new(Mutex::new(vec![]));
+    {
+        let errors = errors.clone();
+        log4rs::set_error_handler(Box::new(move |e| {
+            errors.lock().unwrap().push(e.to_string())
+        }));
+    }
+    let logs = dir.path().join("logs");
+    fs::create_dir(&logs).unwrap();
+    let appender = FileAppender::builder()
+        .encoder(Box::new(PatternEncoder::new("{m}{n}")))
+        .create_dirs(false)
+        .build(logs.join("app.log"))
+        .unwrap();
+    fs::rename(&logs, dir.path().join("old")).unwrap();
+    log4rs::append::file::reopen_files();
+    appender
+        .append(&Record::builder().args(format_args!("four")).build())
+        .unwrap();
+
+    let old = dir.path().join("old").join("app.log");
+    assert_eq!(fs::read_to_string(old).unwrap(), "four\n");
+    let errors = errors.lock().unwrap();
+    assert_eq!(errors.len(), 1);
+    assert!(errors[0].starts_with("error opening "), "{}", errors[0]);
+}
@@ -0,0 +1,75 @@
+use std::sync::{Arc, Mutex};
+
+use log::{LevelFilter, Record};
+use log4rs::{
+    append::Append,
+    config::{Appender, Config, Root},
+};
+
+#[derive(Debug, Default)]
+struct Records {
+    buffered: Vec<String>,
+    written: Vec<String>,
+    dropped: bool,
+}
+
+/// Holds records until flushed, like a buffered file appender.
+#[derive(Debug)]
+struct Buffered(Arc<Mutex<Records>>);
+
+impl Append for Buffered {
+    fn append(&self, record: &Record) -> anyhow::Result<()> {
+        self.0
+            .lock()
+            .unwrap()
+            .buffered
+            .push(record.args().to_string());
+        Ok(())
+    }
+
+    fn flush(&self) {
+        let mut records = self.0.lock().unwrap();
+        let buffered = std::mem::take(&mut records.buffered);
+        records.written.extend(buffered);
+    }
+}
+
+impl Drop for Buffered {
+    fn drop(&mut self) {
+        self.0.lock().unwrap().dropped = true;
+    }
+}
+
+#[test]
+fn shutdown_flushes_and_drops_appenders() {
+    let records = Arc::new(Mutex::new(Records::default()));
+    let config = Config::builder()
+        .appender(Appender::builder().build("buffered", Box::new(Buffered(records.clone()))))
+        .build(
+            Root::builder()
+                .appender("buffered")
+                .build(LevelFilter::Info),
+        )
+        .unwrap();
+    let handle = log4rs::init_config(config).unwrap();
+
+    log::info!("before");
+    assert!(records.lock().unwrap().written.is_empty());
+
+    log4rs::shutdown();
+    {
+        let records = records.lock().unwrap();
+        assert_eq!(records.written, ["before"]);
+        assert!(records.dropped);
+    }
+
+    log::error!("after");
+    handle.set_config(
+        Config::builder()
+            .build(Root::builder().build(LevelFilter::Info))
+            .unwrap(),
+    );
+    assert_eq!(log::max_level(), LevelFilter::Off);
+    assert!(handle.config().appenders().is_empty());
+    assert!(records.lock().unwrap().buffered.is_empty());
+}
@@ -0,0 +1,28 @@
+use std::sync::{Arc, Mutex};
+
+use log::LevelFilter;
+
+#[test]
+fn shutdown_before_config_reports_buffered_records() {
+    let errors = Arc::new(Mutex::new(vec![]));
+    {
+        let errors = errors.clone();
+        log4rs::set_error_handler(Box::new(move |e| {
+            errors.lock().unwrap().push(e.to_string())
+        }));
+    }
+
+    log4rs::init_buffered(LevelFilter::Info, 2).unwrap();
+    log::info!("one");
+    log::info!("two");
+    log::info!("three");
+    assert!(errors.lock().unwrap().is_empty());
+
+    log4rs::shutdown();
+    log::info!("after");
+    assert_eq!(log::max_level(), LevelFilter::Off);
+    assert_eq!(
+        *errors.lock().unwrap(),
+        ["log4rs was shut down before it was configured; 3 records logged before then were discarded"]
+    );
+}
@@ -0,0 +1,65 @@
+#![cfg(all(feature = "config_parsing", feature = "pattern_encoder"))]
+
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex},
+};
+
+use log4rs::{
+    config::{Deserializers, Warning},
+    encode::Encode,
+};
+
+#[test]
+fn problems_are_reported_as_warnings() {
+    let warnings = Arc::new(Mutex::new(vec![]));
+    {
+        let warnings = warnings.clone();
+        log4rs::set_error_handler(Box::new(move |e| {
+            assert!(e.downcast_ref::<Warning>().is_some(), "{}", e);
+            warnings.lock().unwrap().push(e.to_string())
+        }));
+    }
+
+    let deserializers = Deserializers::default();
+    for pattern in ["{d} {l}", "{d} {h({l} {m})}{n}"] {
+        let config = serde_value::to_value(BTreeMap::from([("pattern", pattern)])).unwrap();
+        deserializers
+            .deserialize::<dyn Encode>("pattern", config)
+            .unwrap();
+    }
+
+    assert_eq!(
+        *warnings.lock().unwrap(),
+        ["warning: pattern `{d} {l}` never writes the message, add `{m}` to include it"]
+    );
+
+    // Problems found while loading a file are warnings too.
+    #[cfg(all(feature = "yaml_format", feature = "console_appender"))]
+    {
+        warnings.lock().unwrap().clear();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("log4rs.yaml");
+        std::fs::write(&path, "appenders:\n  stdout:\n    kind: console\n").unwrap();
+        std::env::set_var("LOG4RS_WARNINGS_TEST", "app=loud");
+        let mut deserializers = Deserializers::default();
+        deserializers.set_level_env("LOG4RS_WARNINGS_TEST");
+        log4rs::config::load_config_file(&path, deserializers.clone()).unwrap();
+        assert_eq!(
+            *warnings.lock().unwrap(),
+            [
+                "warning: ignoring malformed level override `app=loud`",
+                "warning: appender `stdout` is not attached to the root or any logger, so it \
+                 receives no events",
+            ]
+        );
+
+        warnings.lock().unwrap().clear();
+        deserializers.set_unattached_appenders(log4rs::config::UnattachedAppenders::Ignore);
+        log4rs::config::load_config_file(&path, deserializers).unwrap();
+        assert_eq!(
+            *warnings.lock().unwrap(),
+            ["warning: ignoring malformed level override `app=loud`"]
+        );
+    }
+}